@@ -10,24 +10,105 @@ mod module_bank {
     use std::collections::HashMap;
     use std::fmt;
     use std::fmt::Display;
+    use std::hash::{Hash, Hasher};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
     use super::module_card::*;
     use rand::prelude::*;
 
-    /// 东西的类型
-    #[derive(Hash)]
-    #[derive(Eq, PartialEq)]
+    /// 东西的类型，OwnedType是名字不是静态字符串的GeneralType，比如从存档里读出来的，两者名字和面值相同时视为同一种东西
     #[derive(Clone)]
     pub enum StuffType {
         GeneralType(&'static str,i32),
+        OwnedType(String,i32),
         CardType(Card),
     }
 
+    /// 存档里东西的类型
+    #[derive(Clone)]
+    #[derive(Serialize, Deserialize)]
+    enum StuffTypeData {
+        General(String,i32),
+        Card(Card),
+    }
+
+    impl From<StuffTypeData> for StuffType {
+        fn from(data: StuffTypeData) -> StuffType {
+            match data {
+                StuffTypeData::General(name, value) => StuffType::OwnedType(name, value),
+                StuffTypeData::Card(card) => StuffType::CardType(card),
+            }
+        }
+    }
+
+    impl From<StuffType> for StuffTypeData {
+        fn from(stuff: StuffType) -> StuffTypeData {
+            match stuff {
+                StuffType::GeneralType(name, value) => StuffTypeData::General(name.to_string(), value),
+                StuffType::OwnedType(name, value) => StuffTypeData::General(name, value),
+                StuffType::CardType(card) => StuffTypeData::Card(card),
+            }
+        }
+    }
+
+    impl Serialize for StuffType {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            StuffTypeData::from(self.clone()).serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for StuffType {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<StuffType, D::Error> {
+            StuffTypeData::deserialize(deserializer).map(StuffType::from)
+        }
+    }
+
+    impl PartialEq for StuffType {
+        fn eq(&self, other: &StuffType) -> bool {
+            match (self, other) {
+                (StuffType::CardType(a), StuffType::CardType(b)) => a == b,
+                (StuffType::CardType(..), _) | (_, StuffType::CardType(..)) => false,
+                _ => self.get_name() == other.get_name() && self.get_value() == other.get_value(),
+            }
+        }
+    }
+
+    impl Eq for StuffType {}
+
+    impl Hash for StuffType {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            match self {
+                StuffType::CardType(card) => {
+                    1.hash(state);
+                    card.hash(state);
+                }
+                _ => {
+                    0.hash(state);
+                    self.get_name().hash(state);
+                    self.get_value().hash(state);
+                }
+            }
+        }
+    }
+
     /// 我的银行
     #[derive(Clone)]
+    #[derive(Serialize, Deserialize)]
     pub struct MyBank {
+        #[serde(serialize_with = "serialize_basket", deserialize_with = "deserialize_basket")]
         basket:HashMap<StuffType, i32>,
     }
 
+    /// JSON的键只能是字符串，篮子存成列表
+    fn serialize_basket<S: Serializer>(basket: &HashMap<StuffType, i32>, serializer: S) -> Result<S::Ok, S::Error> {
+        let items: Vec<(&StuffType, &i32)> = basket.iter().collect();
+        items.serialize(serializer)
+    }
+
+    fn deserialize_basket<'de, D: Deserializer<'de>>(deserializer: D) -> Result<HashMap<StuffType, i32>, D::Error> {
+        let items: Vec<(StuffType, i32)> = Vec::deserialize(deserializer)?;
+        Ok(items.into_iter().collect())
+    }
+
     /// 银行管理特性
     pub trait BankManager<T,U>{
 
@@ -46,6 +127,7 @@ mod module_bank {
         fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
             match self {
                 StuffType::GeneralType(string, value) => write!(f, "面值{0}$的{1}", value, string),
+                StuffType::OwnedType(string, value) => write!(f, "面值{0}$的{1}", value, string),
                 StuffType::CardType(card) => write!(f, "{0}", card),
             }
         }
@@ -55,9 +137,18 @@ mod module_bank {
         pub fn get_value(&self) -> i32 {
             return match self {
                 StuffType::GeneralType(.., value) => *value,
+                StuffType::OwnedType(.., value) => *value,
                 StuffType::CardType(..) => 0,
             }
         }
+        /// 名字，卡牌没有名字
+        pub fn get_name(&self) -> &str {
+            return match self {
+                StuffType::GeneralType(name, ..) => name,
+                StuffType::OwnedType(name, ..) => name,
+                StuffType::CardType(..) => "",
+            }
+        }
     }
 
     /// 银行实现特性
@@ -261,6 +352,9 @@ mod module_bank {
         /// 根据价值，将自己银行的东西用最大面额组合起来
         pub fn collect_stuff(&mut self, value:i32) -> Result<Vec<(StuffType,i32)>, String> {
             let mut sort_basket = self.get_basket_vec();
+            if sort_basket.is_empty() {
+                return Err(format!("Fail to collect stuffs with value of {0} from an empty bank", value));
+            }
             let fun = |a:&(&StuffType,i32),b:&(&StuffType,i32)| {
                 let x1 = a.0.get_value();
                 let x2 = b.0.get_value();
@@ -361,6 +455,114 @@ mod module_bank {
         }
     }
 
+    impl MyBank {
+        /// 凑不出value时，把银行里的筹码拿到house换成更小、但不小于min_value的面额，直到凑得出来；
+        /// held是已经下在面前的筹码，和银行里的一起凑
+        pub fn make_change(&mut self, value:i32, held:&MyBank, house:&mut MyBank, min_value:i32) -> Result<String, String> {
+            let mut te_stack = String::new();
+            loop {
+                let mut all = self.clone();
+                let held_clone = held.clone();
+                let _ = all.stuff_in(&held_clone.get_basket_vec(), true);
+                if value <= 0 || all.collect_stuff(value).is_ok() {
+                    return Ok(te_stack);
+                }
+                if value > all.get_values_of_bank() {
+                    return Err(format!("Fail to make change for {0} because the bank only has {1}!", value, all.get_values_of_bank()));
+                }
+                // 从小到大找一个能在house换开的筹码
+                let mut chips: Vec<StuffType> = self.get_basket_vec().iter().map(|item| item.0.clone()).filter(|stuff| stuff.get_value() > 0).collect();
+                chips.sort_by_key(|stuff| stuff.get_value());
+                let mut change = None;
+                for chip in chips.iter() {
+                    let mut smaller = MyBank::new();
+                    for item in house.get_basket_vec().iter() {
+                        if item.0.get_value() >= min_value && item.0.get_value() < chip.get_value() {
+                            let _ = smaller.stuff_in(item.0, item.1);
+                        }
+                    }
+                    if let Ok(result) = smaller.collect_stuff(chip.get_value()) {
+                        change = Some((chip.clone(), result));
+                        break;
+                    }
+                }
+                let (chip, result) = match change {
+                    Some(T) => T,
+                    None => return Err(format!("Fail to make change for {0} because no chip can be broken!", value)),
+                };
+                te_stack.push_str(&self.stuff_transfer(&chip, 1, house)?);
+                let result_process: Vec<(&StuffType, i32)> = result.iter().map(|item| (&(item.0), item.1)).collect();
+                te_stack.push_str(&house.stuff_transfer(&result_process, true, self)?);
+            }
+        }
+        /// 某种东西的个数
+        pub fn get_num_of(&self, what:&StuffType) -> i32 {
+            *self.basket.get(what).unwrap_or(&0)
+        }
+    }
+
+    impl Default for MyBank {
+        fn default() -> MyBank {
+            MyBank::new()
+        }
+    }
+
+    /// 一种筹码
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    pub struct Denomination {
+        pub name:String,
+        pub value:i32,
+    }
+
+    /// 一套筹码的面值，从小到大排好
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    pub struct ChipSet {
+        pub denominations:Vec<Denomination>,
+    }
+
+    impl ChipSet {
+        pub fn new(denominations:Vec<(&str, i32)>) -> ChipSet {
+            let mut denominations: Vec<Denomination> = denominations.iter().map(|item| Denomination { name: item.0.to_string(), value: item.1 }).collect();
+            denominations.sort_by_key(|denomination| denomination.value);
+            ChipSet { denominations }
+        }
+        /// 检查面值从小到大排好，每一种都是前一种的整数倍，这样才能换零和进位
+        pub fn check(&self) -> Result<(), String> {
+            if self.denominations.is_empty() {
+                return Err("The chip set has no denomination!".to_string());
+            }
+            if self.denominations[0].value <= 0 {
+                return Err(format!("The chip value {0} must be positive!", self.denominations[0].value));
+            }
+            for pair in self.denominations.windows(2) {
+                if pair[1].value <= pair[0].value || pair[1].value % pair[0].value != 0 {
+                    return Err(format!("The chip value {0} must be a larger multiple of {1}!", pair[1].value, pair[0].value));
+                }
+            }
+            Ok(())
+        }
+        pub fn get_stuff_type(&self, index:usize) -> StuffType {
+            let denomination = &self.denominations[index];
+            StuffType::OwnedType(denomination.name.clone(), denomination.value)
+        }
+        /// 用第from种以上的面额从大到小凑出value
+        pub fn get_chips(&self, value:i32, from:usize) -> Result<Vec<(StuffType, i32)>, String> {
+            let mut left = value;
+            let mut chips = Vec::new();
+            for index in (from..self.denominations.len()).rev() {
+                let num = left / self.denominations[index].value;
+                if num > 0 {
+                    chips.push((self.get_stuff_type(index), num));
+                    left -= num * self.denominations[index].value;
+                }
+            }
+            if left != 0 {
+                return Err(format!("Fail to make {0} out of the chip set!", value));
+            }
+            Ok(chips)
+        }
+    }
+
     /// 给银行实现Display特性
     impl Display for MyBank {
         fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -386,20 +588,36 @@ mod module_bank {
 
 mod module_player {
     use std::cmp::Ordering;
+    use std::collections::HashMap;
     use super::module_bank::*;
     use super::module_card::*;
+    use super::module_bot::BotProfile;
+    use serde::{Deserialize, Serialize};
     use std::fmt;
     use std::fmt::Display;
-    use rand::prelude::*;
 
     /// 玩家
     #[derive(Clone)]
+    #[derive(Serialize, Deserialize)]
     pub struct Player {
         pub name:String,
         pub role:Role,
         pub owned_bank:MyBank,
         pub bet_bank:MyBank,
         pub cards_bank:MyBank,
+        pub profile:BotProfile,
+        /// 现金局里坐出去的玩家留着筹码不打牌，角色是Quit
+        #[serde(default)]
+        pub sitting_out:bool,
+        /// 坐出去时盲注轮到自己照样交死钱，否则记下错过的盲注
+        #[serde(default)]
+        pub posting_blinds:bool,
+        /// 坐出去时错过的盲注，坐回来时作为死钱补上
+        #[serde(default)]
+        pub missed_blinds:i32,
+        /// 空座位上的占位玩家，不是真的有人坐着
+        #[serde(default)]
+        pub is_placeholder:bool,
     }
 
     /// 玩家的方法
@@ -442,58 +660,34 @@ mod module_player {
             return Err(te_stack);
         }
 
-        /// 根据上一者下注value自动跟注、加注或放弃的决策
-        pub fn place_a_bet_with_last_value(&mut self, last_player_value:i32, max_bet_value:i32, min_value_unit:i32) -> Result<String, String> {
-            let mut te_stack = String::new();
-            let mut rng = thread_rng();
-            let rand_num = rng.gen_range(1..=100);
-            // 有20%概率放弃
-            if rand_num <= 20 {
-                self.role = Role::GiveUp;
-                return Err(te_stack);
-            }
-            for times in 0..20 {
-                let mut rng = thread_rng();
-                let rand_num = rng.gen_range(1..=100);
-                let mut value = last_player_value;
-                let mut d_value = 0;
-                if rand_num <= 50 {
-                    // 有50%概率跟注
-                    d_value = 0;
-                } else {
-                    let mut rng = thread_rng();
-                    let rand_num = rng.gen_range(1..=100);
-                    if rand_num <= 70 {
-                        // 有70%概率加注10%
-                        d_value = max_bet_value/10;
-                    } else {
-                        let mut rng = thread_rng();
-                        let rand_num = rng.gen_range(2..=5);
-                        d_value = max_bet_value/10*rand_num;
-                    }
-                }
-                if d_value%min_value_unit != 0 {
-                    d_value = d_value + (min_value_unit - d_value%min_value_unit);
-                }
-                if value + d_value > max_bet_value {
-                    continue;
+        /// 底牌的名字，按点数和花色排序
+        pub fn get_cards_names(&self) -> Vec<String> {
+            return self.get_cards().iter().map(|card| card.to_string()).collect();
+        }
+        /// 按点数花色排好序的底牌
+        pub fn get_cards(&self) -> Vec<Card> {
+            let mut cards = Vec::new();
+            for item in self.cards_bank.get_basket_vec().iter() {
+                if let StuffType::CardType(card) = item.0 {
+                    cards.push(card.clone());
                 }
-                let self_clone = self.clone();
-                let bet_bank_backup = self_clone.bet_bank.get_basket_vec();
-                match self.place_a_bet_with_value(value+d_value) {
-                    Ok(T) => {
-                        te_stack.push_str(&T);
-                        self.role = Role::PlaceBet;
-                        return Ok(te_stack);
-                    }
-                    Err(E) => {
-                        self.place_a_bet(&bet_bank_backup);
-                        te_stack.push_str(&E);
-                    }
+            }
+            cards.sort_by_key(|card| card.get_sort_key());
+            return cards;
+        }
+        /// 底牌的强度百分位
+        pub fn get_two_cards_strength(&self) -> f64 {
+            let mut cards = Vec::new();
+            for item in self.cards_bank.get_basket_vec().iter() {
+                if let StuffType::CardType(card) = item.0 {
+                    cards.push(card);
                 }
             }
-            self.role = Role::GiveUp;
-            return Err(te_stack);
+            return if cards.len() == 2 {
+                get_two_cards_strength(cards[0], cards[1])
+            } else {
+                0.5
+            }
         }
 
         /// 下注指定的物品，并检查是否等于value
@@ -561,6 +755,17 @@ mod module_player {
             self.bet_bank = MyBank::new();
             self.owned_bank.stuff_in(&bets, true)
         }
+        /// 下前注，从银行直接交到钱池里，角色不变
+        pub fn place_an_ante(&mut self, value:i32, to:&mut CashPool) -> Result<String, String> {
+            let mut owned_bank_clone = self.owned_bank.clone();
+            match owned_bank_clone.collect_stuff(value) {
+                Ok(result) => {
+                    let ante: Vec<(&StuffType, i32)> = result.iter().map(|item| (&(item.0), item.1)).collect();
+                    self.owned_bank.stuff_transfer(&ante, true, &mut (to.cash_pool))
+                }
+                Err(E) => Err(E),
+            }
+        }
         /// 游戏结束或放弃时，输家把钱交到钱池里
         pub fn send_bets_to_pool(&mut self, to:&mut CashPool) -> Result<String, String> {
             let bet_bank_temp = self.bet_bank.clone();
@@ -581,6 +786,10 @@ mod module_player {
         }
         /// 抽取两张卡
         pub fn get_two_cards(&mut self, from:&mut CardPool) -> Result<String, String> {
+            if let Some(cards) = from.take_from_order(2) {
+                let two_cards:Vec<(&StuffType,i32)> = cards.iter().map(|card| (card, 1)).collect();
+                return from.card_pool.stuff_transfer(&two_cards, true, &mut (self.cards_bank));
+            }
             let card_pool_temp = from.card_pool.clone();
             let mut e_stack = String::new();
             loop {
@@ -635,6 +844,7 @@ mod module_player {
 
     /// 钱池
     #[derive(Clone)]
+    #[derive(Serialize, Deserialize)]
     pub struct CashPool {
         pub cash_pool:MyBank,
     }
@@ -648,9 +858,139 @@ mod module_player {
                 owned_bank: MyBank::new(),
                 bet_bank: MyBank::new(),
                 cards_bank: MyBank::new(),
+                profile: BotProfile::default_profile(),
+                sitting_out: false,
+                posting_blinds: false,
+                missed_blinds: 0,
+                is_placeholder: false,
             };
             return player;
         }
+        /// 空座位上出局的占位玩家
+        pub fn new_placeholder(name:&str) -> Player {
+            let mut player = Player::new(name);
+            player.role = Role::Quit;
+            player.is_placeholder = true;
+            return player;
+        }
+    }
+
+    /// 收银台：牌桌外的筹码架，买入和补码从这里拿筹码，兑现时还回来；账本记着每个人买入减去兑现的净额
+    #[derive(Clone)]
+    #[derive(Serialize, Deserialize)]
+    pub struct Cashier {
+        pub bank:MyBank,
+        pub ledger:HashMap<String, i32>,
+    }
+
+    impl Default for Cashier {
+        fn default() -> Cashier {
+            Cashier::new()
+        }
+    }
+
+    impl Cashier {
+        pub fn new() -> Cashier {
+            Cashier {
+                bank: MyBank::new(),
+                ledger: HashMap::new(),
+            }
+        }
+        /// 往筹码架上补筹码
+        pub fn stock(&mut self, assets:&Vec<(&StuffType,i32)>) -> Result<String, String> {
+            self.bank.stuff_in(assets, true)
+        }
+        /// 卖给玩家价值value的筹码，记到账本上
+        pub fn sell(&mut self, name:&str, value:i32, to:&mut MyBank) -> Result<String, String> {
+            let mut bank_clone = self.bank.clone();
+            let result = match bank_clone.collect_stuff(value) {
+                Ok(T) => T,
+                Err(E) => return Err(format!("The cashier cannot pay {0}: {1}", value, E)),
+            };
+            let chips: Vec<(&StuffType, i32)> = result.iter().map(|item| (&(item.0), item.1)).collect();
+            let te_stack = self.bank.stuff_transfer(&chips, true, to)?;
+            *self.ledger.entry(name.to_string()).or_insert(0) += value;
+            Ok(te_stack)
+        }
+        /// 把玩家银行里的筹码全部收回来，返回收回的价值
+        pub fn redeem(&mut self, name:&str, from:&mut MyBank) -> Result<i32, String> {
+            let value = from.get_values_of_bank();
+            let from_clone = from.clone();
+            let chips = from_clone.get_basket_vec();
+            from.stuff_transfer(&chips, true, &mut (self.bank))?;
+            *self.ledger.entry(name.to_string()).or_insert(0) -= value;
+            Ok(value)
+        }
+        /// 某个玩家买入减去兑现的净额
+        pub fn get_net_buy_in(&self, name:&str) -> i32 {
+            *self.ledger.get(name).unwrap_or(&0)
+        }
+    }
+
+    /// 庄家：抽水收进这里的银行，report统计这一场抽的水
+    #[derive(Clone)]
+    #[derive(Serialize, Deserialize)]
+    pub struct House {
+        pub bank:MyBank,
+        pub report:RakeReport,
+    }
+
+    /// 一场的抽水统计
+    #[derive(Clone, Copy, Default, Debug, PartialEq)]
+    #[derive(Serialize, Deserialize)]
+    pub struct RakeReport {
+        pub hands:u32,
+        pub raked_hands:u32,
+        pub total:i32,
+        pub max:i32,
+    }
+
+    impl Default for House {
+        fn default() -> House {
+            House::new()
+        }
+    }
+
+    impl House {
+        pub fn new() -> House {
+            House {
+                bank: MyBank::new(),
+                report: RakeReport::default(),
+            }
+        }
+        /// 从钱池里抽水，钱池里的筹码凑不出来时按unit往下少抽，返回实际抽到的
+        pub fn take_rake(&mut self, value:i32, unit:i32, from:&mut CashPool) -> i32 {
+            let mut value = value;
+            while value > 0 {
+                let mut cash_pool_clone = from.cash_pool.clone();
+                if let Ok(result) = cash_pool_clone.collect_stuff(value) {
+                    let chips: Vec<(&StuffType, i32)> = result.iter().map(|item| (&(item.0), item.1)).collect();
+                    if from.cash_pool.stuff_transfer(&chips, true, &mut (self.bank)).is_ok() {
+                        break;
+                    }
+                }
+                value -= unit.max(1);
+            }
+            let value = value.max(0);
+            self.report.hands += 1;
+            if value > 0 {
+                self.report.raked_hands += 1;
+            }
+            self.report.total += value;
+            self.report.max = self.report.max.max(value);
+            value
+        }
+        /// 结束这一场，返回这一场的统计，抽到的筹码留在银行里
+        pub fn end_session(&mut self) -> RakeReport {
+            std::mem::take(&mut self.report)
+        }
+    }
+
+    /// 给抽水统计实现Display特性
+    impl Display for RakeReport {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "共{0}手，抽水{1}手，合计{2}，单手最多{3}", self.hands, self.raked_hands, self.total, self.max)
+        }
     }
 
     /// 构造函数
@@ -667,6 +1007,7 @@ mod module_player {
     }
 
     #[derive(Clone)]
+    #[derive(Serialize, Deserialize)]
     pub enum Role {
         Normal,
         DaMang(i32),
@@ -681,7 +1022,7 @@ mod module_player {
     impl Display for Player {
         fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
             write!(f, "我是{0}，",self.name)?;
-            write!(f, "现在是{0}角色。", self.role)?;
+            write!(f, "现在是{0}角色，性格是{1}。", self.role, self.profile.name)?;
             write!(f, "我拥有的资产：{0}$ {1}。", self.owned_bank.get_values_of_bank(),self.owned_bank)?;
             write!(f, "我下注的资产：{0}$ {1}。", self.bet_bank.get_values_of_bank(),self.bet_bank)?;
             write!(f, "我的卡组：{0}。", self.cards_bank)
@@ -714,12 +1055,15 @@ mod module_card {
     use std::fmt;
     use std::fmt::Display;
     use crate::BankManager;
-    use crate::module_bank::MyBank;
+    use crate::module_bank::{MyBank, StuffType};
     use crate::module_bank::StuffType::*;
+    use rand::prelude::*;
+    use serde::{Deserialize, Serialize};
 
     #[derive(Hash)]
     #[derive(Eq, PartialEq)]
     #[derive(Clone)]
+    #[derive(Serialize, Deserialize)]
     pub struct Card {
         name:String,
         value:i32,
@@ -727,18 +1071,48 @@ mod module_card {
     }
 
     #[derive(Clone)]
+    #[derive(Serialize, Deserialize)]
     pub struct CardPool {
         pub card_pool:MyBank,
+        /// 预先排好的发牌顺序，为空时随机发牌
+        pub deal_order:Vec<Card>,
+        /// 指定给某个座位或某条街的牌，下一次发牌时生效
+        pub pinned:Vec<(DealTarget, Card)>,
+    }
+
+    /// 指定牌的去向
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    #[derive(Serialize, Deserialize)]
+    pub enum DealTarget {
+        Seat(usize),
+        Flop,
+        Turn,
+        River,
+    }
+
+    impl DealTarget {
+        /// 每个去向最多能放几张牌
+        pub fn get_capacity(&self) -> usize {
+            match self {
+                DealTarget::Seat(..) => 2,
+                DealTarget::Flop => 3,
+                DealTarget::Turn | DealTarget::River => 1,
+            }
+        }
     }
 
     #[derive(Clone)]
+    #[derive(Serialize, Deserialize)]
     pub struct FiveCards {
         pub five_cards:MyBank,
+        /// 公共牌的发牌顺序：前三张是翻牌，然后是转牌和河牌
+        pub board_order:Vec<Card>,
     }
 
     #[derive(Hash)]
     #[derive(Eq, PartialEq)]
     #[derive(Clone)]
+    #[derive(Serialize, Deserialize)]
     pub enum CardColor {
         HeiTao,
         HongTao,
@@ -748,10 +1122,22 @@ mod module_card {
 
     const CARD_GROUP:[&str;13] = ["2","3","4","5","6","7","8","9","10","J","Q","K","A"];
 
+    /// 点数名字对应的序号
+    fn get_rank_value(rank:&str) -> Result<i32, String> {
+        let rank = rank.to_uppercase();
+        let rank = if rank == "T" { "10".to_string() } else { rank };
+        match CARD_GROUP.iter().position(|name| *name == rank) {
+            Some(value) => Ok(value as i32),
+            None => Err(format!("Fail to parse the rank \"{0}\"!", rank)),
+        }
+    }
+
     impl CardPool {
         pub fn new() -> CardPool {
             let mut pool = CardPool {
-                card_pool: MyBank::new()
+                card_pool: MyBank::new(),
+                deal_order: Vec::new(),
+                pinned: Vec::new(),
             };
             for (val,&i) in CARD_GROUP.iter().enumerate() {
                 let no_use = pool.card_pool.stuff_in(&CardType(Card{name:i.to_string(),value:val as i32,color:CardColor::HeiTao}), 1);
@@ -765,21 +1151,185 @@ mod module_card {
         pub fn reset_card_pool(&self) -> CardPool {
             return CardPool::new();
         }
+        /// 用种子把卡组里的牌洗成固定的发牌顺序，相同种子发出相同的牌
+        pub fn shuffle_with_seed(&mut self, seed:u64) {
+            let mut cards = Vec::new();
+            for item in self.card_pool.get_basket_vec().iter() {
+                if let CardType(card) = item.0 {
+                    cards.push(card.clone());
+                }
+            }
+            cards.sort_by_key(|card| card.get_sort_key());
+            let mut rng = StdRng::seed_from_u64(seed);
+            cards.shuffle(&mut rng);
+            self.deal_order = cards;
+        }
+        /// 按给定的顺序发牌：先按座位每人两张，再发五张公共牌；没给出的牌随机排在后面
+        pub fn from_order(cards:&Vec<Card>) -> Result<CardPool, String> {
+            let mut pool = CardPool::new();
+            for card in cards.iter() {
+                if !pool.contains(card) || pool.deal_order.contains(card) {
+                    return Err(format!("Fail to deal {0} twice!", card));
+                }
+                pool.deal_order.push(card.clone());
+            }
+            let mut rest = pool.get_cards_left();
+            rest.shuffle(&mut thread_rng());
+            pool.deal_order.append(&mut rest);
+            return Ok(pool);
+        }
+        /// 卡组里是否还有这张牌
+        pub fn contains(&self, card:&Card) -> bool {
+            self.card_pool.get_basket_vec().iter().any(|item| item.0 == &CardType(card.clone()))
+        }
+        /// 卡组里还没有排进发牌顺序，也没有被指定的牌，按点数花色排序
+        fn get_cards_left(&self) -> Vec<Card> {
+            let mut cards = Vec::new();
+            for item in self.card_pool.get_basket_vec().iter() {
+                if let CardType(card) = item.0 {
+                    if !self.deal_order.contains(card) && !self.pinned.iter().any(|pin| &pin.1 == card) {
+                        cards.push(card.clone());
+                    }
+                }
+            }
+            cards.sort_by_key(|card| card.get_sort_key());
+            return cards;
+        }
+        /// 把牌指定给某个座位或某条街，同一张牌不能指定两次
+        pub fn pin_cards(&mut self, target:DealTarget, cards:&Vec<Card>) -> Result<String, String> {
+            let used = self.pinned.iter().filter(|pin| pin.0 == target).count();
+            if used + cards.len() > target.get_capacity() {
+                return Err(format!("Fail to pin {0} cards to {1:?} which holds only {2}!", used + cards.len(), target, target.get_capacity()));
+            }
+            for (i, card) in cards.iter().enumerate() {
+                if !self.contains(card) || self.pinned.iter().any(|pin| &pin.1 == card) || cards[..i].contains(card) {
+                    return Err(format!("Fail to pin {0} because it has been dealt or pinned!", card));
+                }
+            }
+            for card in cards.iter() {
+                self.pinned.push((target, card.clone()));
+            }
+            return Ok(format!("Succeed to pin {0} cards to {1:?}!", cards.len(), target));
+        }
+        /// 根据指定的牌排好这一次的发牌顺序，其余位置随机补齐；seat_num是要发底牌的座位数
+        pub fn arrange_pinned_deal(&mut self, seat_num:usize) -> Result<String, String> {
+            if self.pinned.is_empty() {
+                return Ok("There's no pinned card!".to_string());
+            }
+            self.deal_order.clear();
+            let mut rest = self.get_cards_left();
+            rest.shuffle(&mut thread_rng());
+            let mut targets = Vec::new();
+            for seat in 0..seat_num {
+                targets.push(DealTarget::Seat(seat));
+            }
+            targets.push(DealTarget::Flop);
+            targets.push(DealTarget::Turn);
+            targets.push(DealTarget::River);
+            for target in targets {
+                let mut cards:Vec<Card> = self.pinned.iter().filter(|pin| pin.0 == target).map(|pin| pin.1.clone()).collect();
+                while cards.len() < target.get_capacity() {
+                    match rest.pop() {
+                        Some(card) => cards.push(card),
+                        None => return Err("Fail to arrange the deal because there are not enough cards!".to_string()),
+                    }
+                }
+                self.deal_order.append(&mut cards);
+            }
+            if let Some(pin) = self.pinned.iter().find(|pin| !self.deal_order.contains(&pin.1)) {
+                return Err(format!("Fail to deal {0} to {1:?} because there are only {2} seats!", pin.1, pin.0, seat_num));
+            }
+            self.pinned.clear();
+            return Ok("Succeed to arrange the pinned deal!".to_string());
+        }
+        /// 按发牌顺序取出接下来的num张牌，没有预先排好的顺序时返回None
+        pub fn take_from_order(&mut self, num:usize) -> Option<Vec<StuffType>> {
+            if self.deal_order.len() < num {
+                return None;
+            }
+            let cards:Vec<StuffType> = self.deal_order.drain(0..num).map(CardType).collect();
+            return Some(cards);
+        }
+    }
+
+    impl Card {
+        /// 用点数序号（0是2，12是A）和花色构造一张牌
+        pub fn new(value:i32, color:CardColor) -> Result<Card, String> {
+            if !(0..13).contains(&value) {
+                return Err(format!("Fail to make a card with value {0}!", value));
+            }
+            Ok(Card { name: CARD_GROUP[value as usize].to_string(), value, color })
+        }
+        /// 解析牌的名字，支持“As”“Td”“10h”这样的简写和“黑桃A”这样的中文名
+        pub fn parse(text:&str) -> Result<Card, String> {
+            let text = text.trim();
+            let chinese = [("黑桃", CardColor::HeiTao), ("红桃", CardColor::HongTao), ("梅花", CardColor::MeiHua), ("方块", CardColor::FangKuai)];
+            for (prefix, color) in chinese {
+                if let Some(rank) = text.strip_prefix(prefix) {
+                    return Card::new(get_rank_value(rank)?, color);
+                }
+            }
+            let mut chars:Vec<char> = text.chars().collect();
+            let color = match chars.pop() {
+                Some('s') | Some('S') | Some('♠') => CardColor::HeiTao,
+                Some('h') | Some('H') | Some('♥') => CardColor::HongTao,
+                Some('c') | Some('C') | Some('♣') => CardColor::MeiHua,
+                Some('d') | Some('D') | Some('♦') => CardColor::FangKuai,
+                _ => return Err(format!("Fail to parse the card \"{0}\"!", text)),
+            };
+            let rank:String = chars.into_iter().collect();
+            Card::new(get_rank_value(&rank)?, color)
+        }
+        /// 解析用空格分隔的多张牌
+        pub fn parse_cards(text:&str) -> Result<Vec<Card>, String> {
+            text.split_whitespace().map(Card::parse).collect()
+        }
+        /// 英文简写，如“As”“Td”
+        pub fn get_short_name(&self) -> String {
+            let rank = if self.value == 8 { "T" } else { CARD_GROUP[self.value as usize] };
+            let color = match self.color {
+                CardColor::HeiTao => "s",
+                CardColor::HongTao => "h",
+                CardColor::MeiHua => "c",
+                CardColor::FangKuai => "d",
+            };
+            format!("{0}{1}", rank, color)
+        }
+        /// 用于排序的键：先按点数，再按花色
+        pub fn get_sort_key(&self) -> (i32, i32) {
+            let color = match self.color {
+                CardColor::HeiTao => 0,
+                CardColor::HongTao => 1,
+                CardColor::MeiHua => 2,
+                CardColor::FangKuai => 3,
+            };
+            (self.value, color)
+        }
     }
 
     impl FiveCards {
         pub fn new() -> FiveCards {
             let cards = FiveCards {
                 five_cards:MyBank::new(),
+                board_order:Vec::new(),
             };
             return cards;
         }
         pub fn clear_five_cards(&mut self) -> Result<String, String> {
+            self.board_order.clear();
             self.five_cards.stuff_clear()
         }
         /// 抽取五张卡
         pub fn get_five_cards(&mut self, from:&mut CardPool) -> Result<String, String> {
             let mut e_stack = String::new();
+            if let Some(cards) = from.take_from_order(5) {
+                let five_cards:Vec<(&StuffType,i32)> = cards.iter().map(|card| (card, 1)).collect();
+                let result = from.card_pool.stuff_transfer(&five_cards, true, &mut (self.five_cards));
+                if result.is_ok() {
+                    self.set_board_order(&five_cards);
+                }
+                return result;
+            }
             loop {
                 let card_pool_temp = from.card_pool.clone();
                 let mut five_cards = Vec::new();
@@ -789,13 +1339,26 @@ mod module_card {
                 five_cards.push((card_pool_temp.read_random_item(),1));
                 five_cards.push((card_pool_temp.read_random_item(),1));
                 match from.card_pool.stuff_transfer(&five_cards, true, &mut (self.five_cards)) {
-                    Ok(T) => return Ok(e_stack+&T),
+                    Ok(T) => {
+                        self.set_board_order(&five_cards);
+                        return Ok(e_stack+&T);
+                    },
                     Err(E) => e_stack.push_str(&(E)),
                 }
             }
         }
+        /// 记录公共牌的发牌顺序
+        fn set_board_order(&mut self, five_cards:&Vec<(&StuffType,i32)>) {
+            self.board_order.clear();
+            for item in five_cards.iter() {
+                if let CardType(card) = item.0 {
+                    self.board_order.push(card.clone());
+                }
+            }
+        }
         /// 还卡
         pub fn send_cards_back(&mut self, to:&mut CardPool) -> Result<String, String> {
+            self.board_order.clear();
             let five_cards_temp = self.five_cards.clone();
             let cards = five_cards_temp.get_basket_vec();
             self.five_cards.stuff_transfer(&cards, true, &mut (to.card_pool))
@@ -951,60 +1514,381 @@ mod module_card {
         return FiveCardsCategory::HighCard;
     }
 
-    // 检查是否是一对
-    fn is_pair(card1:&Card, card2:&Card) -> bool {
-        return if card1.value == card2.value {
-            true
+    /// 用Chen公式给两张底牌打分
+    fn get_chen_score(value1:i32, value2:i32, suited:bool) -> f64 {
+        let high = value1.max(value2);
+        let low = value1.min(value2);
+        let high_card_score = |value:i32| -> f64 {
+            match value {
+                12 => 10.0,
+                11 => 8.0,
+                10 => 7.0,
+                9 => 6.0,
+                _ => (value + 2) as f64 / 2.0,
+            }
+        };
+        let mut score = high_card_score(high);
+        if high == low {
+            score = (score * 2.0).max(5.0);
         } else {
-            false
+            if suited {
+                score += 2.0;
+            }
+            let gap = high - low - 1;
+            score -= match gap {
+                0 => 0.0,
+                1 => 1.0,
+                2 => 2.0,
+                3 => 4.0,
+                _ => 5.0,
+            };
+            if gap <= 1 && high < 10 {
+                score += 1.0;
+            }
         }
+        return score.ceil();
     }
 
-    // 检查花色是否一致
-    fn is_same_color(card1:&Card, card2:&Card) -> bool {
-        return if card1.color == card2.color {
-            true
-        } else {
-            false
+    /// 两张底牌在所有1326种起手牌中的百分位（0最差，1最好）
+    pub fn get_two_cards_strength(card1:&Card, card2:&Card) -> f64 {
+        let score = get_chen_score(card1.value, card2.value, card1.color == card2.color);
+        let mut worse = 0.0;
+        let mut equal = 0.0;
+        for high in 0..13 {
+            for low in 0..=high {
+                // 对子6种组合，同花4种，杂色12种
+                let mut classes = Vec::new();
+                if high == low {
+                    classes.push((get_chen_score(high, low, false), 6.0));
+                } else {
+                    classes.push((get_chen_score(high, low, true), 4.0));
+                    classes.push((get_chen_score(high, low, false), 12.0));
+                }
+                for (class_score, combos) in classes {
+                    if class_score < score {
+                        worse += combos;
+                    } else if class_score == score {
+                        equal += combos;
+                    }
+                }
+            }
         }
+        return (worse + equal / 2.0) / 1326.0;
     }
 
-    // 检查是否相邻（card1大，card2小）
-    fn is_near(card1:&Card, card2:&Card) -> bool {
-        return if card1.value == card2.value + 1 {
-            true
-        } else {
-            false
-        }
+    /// 用0到51的序号表示一张牌：点数序号乘4再加花色，算胜率时比Card快
+    pub fn get_card_index(card:&Card) -> usize {
+        let (value, color) = card.get_sort_key();
+        (value * 4 + color) as usize
     }
 
+    /// 序号对应的牌
+    pub fn get_card_by_index(index:usize) -> Card {
+        let color = match index % 4 {
+            0 => CardColor::HeiTao,
+            1 => CardColor::HongTao,
+            2 => CardColor::MeiHua,
+            _ => CardColor::FangKuai,
+        };
+        Card { name: CARD_GROUP[index / 4].to_string(), value: (index / 4) as i32, color }
+    }
 
-    /// 给卡实现Display特性
-    impl Display for Card {
-        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            match self.color {
-                CardColor::HeiTao => write!(f, "黑桃{0}",self.name),
-                CardColor::HongTao => write!(f, "红桃{0}",self.name),
-                CardColor::MeiHua => write!(f, "梅花{0}",self.name),
-                CardColor::FangKuai => write!(f, "方块{0}",self.name)
+    // 点数掩码里最大的五张连续点数的最小点数，A不能当1用
+    fn get_straight_low(mask:u16) -> Option<i32> {
+        for low in (0..=8).rev() {
+            if (mask >> low) & 0b11111 == 0b11111 {
+                return Some(low);
             }
         }
+        None
     }
 
-    /// 给五卡种类实现Display特性
-    impl Display for FiveCardsCategory {
-        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            match self {
-                FiveCardsCategory::RoyalFlush => write!(f, "皇家同花顺"),
-                FiveCardsCategory::StraightFlush => write!(f, "同花顺"),
-                FiveCardsCategory::FourOfAKind => write!(f, "四条"),
-                FiveCardsCategory::FullHouse => write!(f, "葫芦"),
-                FiveCardsCategory::Flush => write!(f, "同花"),
-                FiveCardsCategory::Straight => write!(f, "顺子"),
-                FiveCardsCategory::ThreeOfAKind => write!(f, "三条"),
-                FiveCardsCategory::TwoPairs => write!(f, "两对"),
-                FiveCardsCategory::Pair => write!(f, "对子"),
-                FiveCardsCategory::HighCard => write!(f, "高牌"),
+    // 点数掩码里从大到小取num个点数的和
+    fn get_top_values_sum(mask:u16, num:usize) -> i32 {
+        let (mut mask, mut sum) = (mask, 0);
+        for _ in 0..num {
+            if mask == 0 {
+                break;
+            }
+            let value = 15 - mask.leading_zeros() as i32;
+            sum += value;
+            mask &= !(1 << value);
+        }
+        sum
+    }
+
+    /// 七张牌里最好的五张牌组合的值，和摊牌时枚举21种组合用get_five_cards_value挑出的最大值一样：
+    /// 牌型分加上五张牌的点数和，所以同一牌型里挑点数和最大的五张
+    pub fn get_seven_cards_value(cards:&[usize;7]) -> i32 {
+        let mut counts = [0usize; 13];
+        let mut suits = [0u16; 4];
+        let mut mask = 0u16;
+        for &card in cards.iter() {
+            counts[card / 4] += 1;
+            suits[card % 4] |= 1 << (card / 4);
+            mask |= 1 << (card / 4);
+        }
+        let flush = suits.iter().find(|suit| suit.count_ones() >= 5).copied();
+        // 同花顺
+        if let Some(suit) = flush {
+            if let Some(low) = get_straight_low(suit) {
+                return 800 + low * 5 + 10;
+            }
+        }
+        // 从大到小记下四条、三条和对子的点数，对子包括三条和四条
+        let (mut quad, mut trips, mut pairs) = (-1, [-1i32; 2], [-1i32; 3]);
+        let (mut trip_num, mut pair_num) = (0, 0);
+        for value in (0..13).rev() {
+            let count = counts[value as usize];
+            if count >= 4 && quad < 0 {
+                quad = value;
+            }
+            if count >= 3 && trip_num < 2 {
+                trips[trip_num] = value;
+                trip_num += 1;
+            }
+            if count >= 2 && pair_num < 3 {
+                pairs[pair_num] = value;
+                pair_num += 1;
+            }
+        }
+        // 四条
+        if quad >= 0 {
+            return 700 + quad * 4 + get_top_values_sum(mask & !(1 << quad), 1);
+        }
+        // 三带二
+        if trip_num > 0 && pair_num > 1 {
+            let pair = if pairs[0] == trips[0] { pairs[1] } else { pairs[0] };
+            return 600 + trips[0] * 3 + pair * 2;
+        }
+        // 同花
+        if let Some(suit) = flush {
+            return 500 + get_top_values_sum(suit, 5);
+        }
+        // 顺子
+        if let Some(low) = get_straight_low(mask) {
+            return 400 + low * 5 + 10;
+        }
+        // 三条
+        if trip_num > 0 {
+            return 300 + trips[0] * 3 + get_top_values_sum(mask & !(1 << trips[0]), 2);
+        }
+        // 两对，第三个对子的点数也可以当单张
+        if pair_num >= 2 {
+            return 200 + (pairs[0] + pairs[1]) * 2 + get_top_values_sum(mask & !(1 << pairs[0]) & !(1 << pairs[1]), 1);
+        }
+        // 对子
+        if pair_num > 0 {
+            return 100 + pairs[0] * 2 + get_top_values_sum(mask & !(1 << pairs[0]), 3);
+        }
+        get_top_values_sum(mask, 5)
+    }
+
+    /// 起手牌的169种类型：对子、同花和不同花
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub struct HandClass {
+        pub high:i32,
+        pub low:i32,
+        pub suited:bool,
+    }
+
+    /// 起手牌类型的数量
+    pub const HAND_CLASS_NUM:usize = 169;
+
+    impl HandClass {
+        pub fn from_cards(card1:&Card, card2:&Card) -> HandClass {
+            HandClass { high: card1.value.max(card2.value), low: card1.value.min(card2.value), suited: card1.color == card2.color && card1.value != card2.value }
+        }
+        /// 在13×13表里的行和列：A在第0行第0列，对子在对角线上，同花在右上，不同花在左下
+        pub fn get_grid_position(&self) -> (usize, usize) {
+            let (high, low) = ((12 - self.high) as usize, (12 - self.low) as usize);
+            if self.suited { (high, low) } else { (low, high) }
+        }
+        /// 在13×13表里按行排的序号
+        pub fn get_index(&self) -> usize {
+            let (row, column) = self.get_grid_position();
+            row * 13 + column
+        }
+        pub fn from_index(index:usize) -> HandClass {
+            let (row, column) = (index / 13, index % 13);
+            let high = 12 - row.min(column) as i32;
+            let low = 12 - row.max(column) as i32;
+            HandClass { high, low, suited: column > row }
+        }
+        /// 名字，如“AKs”“T9o”“22”
+        pub fn get_name(&self) -> String {
+            let rank = |value:i32| if value == 8 { "T" } else { CARD_GROUP[value as usize] };
+            if self.high == self.low {
+                format!("{0}{0}", rank(self.high))
+            } else {
+                format!("{0}{1}{2}", rank(self.high), rank(self.low), if self.suited { "s" } else { "o" })
+            }
+        }
+        /// 解析“AKs”“T9o”“22”这样的名字
+        pub fn parse(text:&str) -> Result<HandClass, String> {
+            let chars:Vec<char> = text.trim().chars().collect();
+            if chars.len() < 2 || chars.len() > 3 {
+                return Err(format!("Fail to parse the hand class \"{0}\"!", text));
+            }
+            let value1 = get_rank_value(&chars[0].to_string())?;
+            let value2 = get_rank_value(&chars[1].to_string())?;
+            let suited = match chars.get(2) {
+                Some('s') | Some('S') if value1 != value2 => true,
+                Some('o') | Some('O') if value1 != value2 => false,
+                None if value1 == value2 => false,
+                _ => return Err(format!("Fail to parse the hand class \"{0}\"!", text)),
+            };
+            Ok(HandClass { high: value1.max(value2), low: value1.min(value2), suited })
+        }
+        /// 这一类的所有具体组合：对子6种，同花4种，不同花12种
+        pub fn get_combos(&self) -> Vec<[usize; 2]> {
+            let mut combos = Vec::new();
+            for color1 in 0..4 {
+                for color2 in 0..4 {
+                    let card1 = self.high as usize * 4 + color1;
+                    let card2 = self.low as usize * 4 + color2;
+                    let keep = if self.high == self.low { color1 < color2 } else if self.suited { color1 == color2 } else { color1 != color2 };
+                    if keep {
+                        combos.push([card1, card2]);
+                    }
+                }
+            }
+            combos
+        }
+    }
+
+    /// 169种起手牌两两之间的胜率表，平局算一半，胜率按摊牌时的牌型值抽样算出
+    #[derive(Clone, Serialize, Deserialize)]
+    pub struct EquityTable {
+        /// equities[a][b]：a类对b类的胜率
+        pub equities:Vec<Vec<f64>>,
+        /// combos[a][b]：拿着一手a类时，b类还有几种不冲突的组合
+        pub combos:Vec<Vec<f64>>,
+    }
+
+    impl EquityTable {
+        /// 每两类起手牌抽samples次不冲突的组合和公共牌
+        pub fn compute(samples:usize, seed:u64) -> EquityTable {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let classes:Vec<Vec<[usize; 2]>> = (0..HAND_CLASS_NUM).map(|index| HandClass::from_index(index).get_combos()).collect();
+            let mut equities = vec![vec![0.5; HAND_CLASS_NUM]; HAND_CLASS_NUM];
+            let mut combos = vec![vec![0.0; HAND_CLASS_NUM]; HAND_CLASS_NUM];
+            for a in 0..HAND_CLASS_NUM {
+                for b in 0..HAND_CLASS_NUM {
+                    let hero = &classes[a][0];
+                    combos[a][b] = classes[b].iter().filter(|combo| !combo.iter().any(|card| hero.contains(card))).count() as f64;
+                }
+            }
+            let mut deck:Vec<usize> = Vec::with_capacity(52);
+            for a in 0..HAND_CLASS_NUM {
+                for b in (a + 1)..HAND_CLASS_NUM {
+                    let mut score = 0.0;
+                    for _ in 0..samples {
+                        let (hero, villain) = loop {
+                            let hero = classes[a][rng.gen_range(0..classes[a].len())];
+                            let villain = classes[b][rng.gen_range(0..classes[b].len())];
+                            if !villain.iter().any(|card| hero.contains(card)) {
+                                break (hero, villain);
+                            }
+                        };
+                        // 从剩下的48张里抽5张公共牌
+                        let used = (1u64 << hero[0]) | (1u64 << hero[1]) | (1u64 << villain[0]) | (1u64 << villain[1]);
+                        let mut board = 0u64;
+                        deck.clear();
+                        while deck.len() < 5 {
+                            let card = rng.gen_range(0..52);
+                            if (used | board) >> card & 1 == 0 {
+                                board |= 1 << card;
+                                deck.push(card);
+                            }
+                        }
+                        let hero_value = get_seven_cards_value(&[hero[0], hero[1], deck[0], deck[1], deck[2], deck[3], deck[4]]);
+                        let villain_value = get_seven_cards_value(&[villain[0], villain[1], deck[0], deck[1], deck[2], deck[3], deck[4]]);
+                        score += match hero_value.cmp(&villain_value) {
+                            std::cmp::Ordering::Greater => 1.0,
+                            std::cmp::Ordering::Equal => 0.5,
+                            std::cmp::Ordering::Less => 0.0,
+                        };
+                    }
+                    equities[a][b] = score / samples.max(1) as f64;
+                    equities[b][a] = 1.0 - equities[a][b];
+                }
+            }
+            EquityTable { equities, combos }
+        }
+        /// 对一个范围的胜率，range[类]是这一类在范围里的比例，范围是空的时候返回None
+        pub fn get_equity_against_range(&self, hero:usize, range:&Vec<f64>) -> Option<f64> {
+            let mut weight = 0.0;
+            let mut equity = 0.0;
+            for (villain, frequency) in range.iter().enumerate() {
+                let combos = self.combos[hero][villain] * frequency;
+                weight += combos;
+                equity += combos * self.equities[hero][villain];
+            }
+            if weight > 0.0 { Some(equity / weight) } else { None }
+        }
+        /// 拿着hero时对手的牌落在范围里的概率
+        pub fn get_range_probability(&self, hero:usize, range:&Vec<f64>) -> f64 {
+            let total:f64 = self.combos[hero].iter().sum();
+            let inside:f64 = self.combos[hero].iter().zip(range.iter()).map(|(combos, frequency)| combos * frequency).sum();
+            inside / total
+        }
+    }
+
+    // 检查是否是一对
+    fn is_pair(card1:&Card, card2:&Card) -> bool {
+        return if card1.value == card2.value {
+            true
+        } else {
+            false
+        }
+    }
+
+    // 检查花色是否一致
+    fn is_same_color(card1:&Card, card2:&Card) -> bool {
+        return if card1.color == card2.color {
+            true
+        } else {
+            false
+        }
+    }
+
+    // 检查是否相邻（card1大，card2小）
+    fn is_near(card1:&Card, card2:&Card) -> bool {
+        return if card1.value == card2.value + 1 {
+            true
+        } else {
+            false
+        }
+    }
+
+
+    /// 给卡实现Display特性
+    impl Display for Card {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self.color {
+                CardColor::HeiTao => write!(f, "黑桃{0}",self.name),
+                CardColor::HongTao => write!(f, "红桃{0}",self.name),
+                CardColor::MeiHua => write!(f, "梅花{0}",self.name),
+                CardColor::FangKuai => write!(f, "方块{0}",self.name)
+            }
+        }
+    }
+
+    /// 给五卡种类实现Display特性
+    impl Display for FiveCardsCategory {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                FiveCardsCategory::RoyalFlush => write!(f, "皇家同花顺"),
+                FiveCardsCategory::StraightFlush => write!(f, "同花顺"),
+                FiveCardsCategory::FourOfAKind => write!(f, "四条"),
+                FiveCardsCategory::FullHouse => write!(f, "葫芦"),
+                FiveCardsCategory::Flush => write!(f, "同花"),
+                FiveCardsCategory::Straight => write!(f, "顺子"),
+                FiveCardsCategory::ThreeOfAKind => write!(f, "三条"),
+                FiveCardsCategory::TwoPairs => write!(f, "两对"),
+                FiveCardsCategory::Pair => write!(f, "对子"),
+                FiveCardsCategory::HighCard => write!(f, "高牌"),
             }
         }
     }
@@ -1024,558 +1908,3952 @@ mod module_card {
     }
 
 }
-
-mod module_game {
-    use std::cmp::{min, Ordering};
-    use std::collections::HashMap;
-    use crate::CardPool;
-    use crate::module_card::{FiveCards, FiveCardsCategory};
-    use crate::module_player::{CashPool, Player, Role};
-    use crate::module_bank::*;
+///机器人的性格，可以从配置文件中读取
+mod module_bot {
+    use std::collections::VecDeque;
     use std::fmt;
-    use std::fmt::{Display, format};
+    use std::fmt::Display;
+    use std::fs;
+    use serde::{Deserialize, Serialize};
     use rand::prelude::*;
+    use crate::module_stats::PlayerStats;
+    use crate::module_view::PlayerView;
+    use crate::module_icm::get_icm_equities;
 
-    #[derive(Clone)]
-    pub struct Game {
-        pub players: Vec<Player>,
-        pub cash_pool: CashPool,
-        pub card_pool: CardPool,
-        pub five_cards: FiveCards,
-        pub game_status: GameStatus,
-        pub last_XiaoMang_ID: i32,
-        pub min_value_unit: i32,
-    }
-
-    pub enum MyEvent<'a> {
-        AddBot { num: usize },
-        ResetGame { assets: &'a Vec<(&'a StuffType, i32)> },
-        StartNextGame,
-        PickCards,
-        PlaceABet { bet: &'a Vec<(&'a StuffType, i32)> },
-        PlaceABet_Auto,
-        ConfirmBalance,
-        GiveUp,
+    /// 机器人性格
+    #[derive(Clone, Debug, PartialEq)]
+    #[derive(Serialize, Deserialize)]
+    pub struct BotProfile {
+        pub name: String,
+        /// 主动入池率：起手牌排在前vpip的才跟注或加注
+        pub vpip: f64,
+        /// 加注率：起手牌排在前pfr的才加注
+        pub pfr: f64,
+        /// 激进度AF：翻牌后下注加注与跟注的次数之比，继续打的牌按AF/(1+AF)的概率下注加注，否则跟注或过牌
+        pub aggression_factor: f64,
+        /// 大额加注与小额加注的次数之比，只决定加注的大小
+        pub big_raise_ratio: f64,
+        /// 诈唬频率：手牌不在范围内时仍然入池并加注的概率
+        pub bluff_frequency: f64,
     }
 
-    #[derive(Clone)]
-    pub enum GameStatus {
-        Setting,
-        CardsPicking,
-        BetPlacing1,
-        BetPlacing2,
-        BetPlacing3,
-        BetPlacing4,
-        Balancing,
+    /// 配置文件的格式，每个[[profile]]是一个性格
+    #[derive(Deserialize)]
+    struct BotProfileFile {
+        profile: Vec<BotProfile>,
     }
 
-    const MAX_PLAYER_NUM: usize = 10;
-    const PLAYER_NAME: [&str; MAX_PLAYER_NUM] = ["ME", "Alice", "Bob", "Cara", "David", "Ederson", "Ford", "Gavin", "Harry", "Ian"];
-
-    impl Game {
-        pub fn new() -> Game {
-            let mut game = Game {
-                players: Vec::new(),
-                cash_pool: CashPool::new(),
-                card_pool: CardPool::new(),
-                five_cards: FiveCards::new(),
-                game_status: GameStatus::Setting,
-                last_XiaoMang_ID: 0,
-                min_value_unit: 1,
+    impl BotProfile {
+        pub fn new(name: &str, vpip: f64, pfr: f64, aggression_factor: f64, big_raise_ratio: f64, bluff_frequency: f64) -> Result<BotProfile, String> {
+            let profile = BotProfile {
+                name: name.to_string(),
+                vpip,
+                pfr,
+                aggression_factor,
+                big_raise_ratio,
+                bluff_frequency,
             };
-            match game.add_a_player("ME") {
-                Ok(T) => println!("{}", T),
-                Err(E) => println!("{}", E),
+            profile.check()?;
+            return Ok(profile);
+        }
+        /// 默认性格，和原来写死的概率大致相同：20%弃牌，一半入池的牌加注，翻牌后下注加注和跟注一样多，加注中30%是大额加注
+        pub fn default_profile() -> BotProfile {
+            BotProfile {
+                name: "default".to_string(),
+                vpip: 0.8,
+                pfr: 0.4,
+                aggression_factor: 1.0,
+                big_raise_ratio: 3.0 / 7.0,
+                bluff_frequency: 0.0,
             }
-            return game;
         }
-        pub fn get_min_value_unit(&mut self) {
-            let mut value_unit = -1;
-            for player in self.players.iter() {
-                for item in player.owned_bank.get_basket_vec().iter() {
-                    if value_unit < 0 || item.0.get_value() < value_unit {
-                        value_unit = item.0.get_value();
-                    }
-                }
-                for item in player.bet_bank.get_basket_vec().iter() {
-                    if value_unit < 0 || item.0.get_value() < value_unit {
-                        value_unit = item.0.get_value();
-                    }
-                }
+        /// 紧弱型
+        pub fn tight_passive() -> BotProfile {
+            BotProfile {
+                name: "tight-passive".to_string(),
+                vpip: 0.15,
+                pfr: 0.05,
+                aggression_factor: 0.5,
+                big_raise_ratio: 0.5,
+                bluff_frequency: 0.02,
             }
-            self.min_value_unit = value_unit;
         }
-        /// 根据玩家数量、总金额计算小盲金额
-        pub fn get_XiaoMang_value(&self) -> i32 {
-            let mut player_num_left = 0;
-            for player in self.players.iter() {
-                if let Role::PlaceBet|Role::GiveUp|Role::Normal|Role::XiaoMang(..)|Role::DaMang(..) = player.role {
-                    player_num_left += 1;
+        /// 松凶型
+        pub fn loose_aggressive() -> BotProfile {
+            BotProfile {
+                name: "loose-aggressive".to_string(),
+                vpip: 0.35,
+                pfr: 0.28,
+                aggression_factor: 3.0,
+                big_raise_ratio: 3.0,
+                bluff_frequency: 0.15,
+            }
+        }
+        /// 疯子型
+        pub fn maniac() -> BotProfile {
+            BotProfile {
+                name: "maniac".to_string(),
+                vpip: 0.8,
+                pfr: 0.6,
+                aggression_factor: 5.0,
+                big_raise_ratio: 6.0,
+                bluff_frequency: 0.4,
+            }
+        }
+        /// 检查各项参数是否合法
+        pub fn check(&self) -> Result<(), String> {
+            for (key, value) in [("vpip", self.vpip), ("pfr", self.pfr), ("bluff_frequency", self.bluff_frequency)] {
+                if !(0.0..=1.0).contains(&value) {
+                    return Err(format!("Profile \"{0}\" has an invalid {1} of {2}!", self.name, key, value));
                 }
             }
-            let mut value_of_all = 0;
-            let value_unit = self.min_value_unit;
-            for player in self.players.iter() {
-                value_of_all += player.owned_bank.get_values_of_bank() + player.bet_bank.get_values_of_bank();
+            if self.pfr > self.vpip {
+                return Err(format!("Profile \"{0}\" cannot have a pfr of {1} larger than its vpip of {2}!", self.name, self.pfr, self.vpip));
             }
-            return if (value_of_all / player_num_left / 30) % value_unit != 0 {
-                value_of_all / player_num_left / 30 + (value_unit - (value_of_all / player_num_left / 30) % value_unit)
-            } else {
-                value_of_all / player_num_left / 30
+            for (key, value) in [("aggression_factor", self.aggression_factor), ("big_raise_ratio", self.big_raise_ratio)] {
+                if !(value >= 0.0 && value.is_finite()) {
+                    return Err(format!("Profile \"{0}\" has an invalid {1} of {2}!", self.name, key, value));
+                }
             }
+            Ok(())
         }
-        /// 根据玩家数量、总金额计算大盲金额
-        pub fn get_DaMang_value(&self) -> i32 {
-            let mut player_num_left = 0;
-            for player in self.players.iter() {
-                if let Role::PlaceBet|Role::GiveUp|Role::Normal|Role::XiaoMang(..)|Role::DaMang(..) = player.role {
-                    player_num_left += 1;
-                }
+        /// 翻牌后继续打的牌里下注加注的比例
+        pub fn get_aggressive_rate(&self) -> f64 {
+            self.aggression_factor / (1.0 + self.aggression_factor)
+        }
+        /// 大额加注在所有加注中的比例
+        pub fn get_big_raise_rate(&self) -> f64 {
+            self.big_raise_ratio / (1.0 + self.big_raise_ratio)
+        }
+        /// 根据底牌强度决定是否入池、是否加注：None表示弃牌，Some(true)表示下注或加注，不用跟注时不会弃牌；
+        /// 诈唬总是下注加注，范围内的牌翻前按pfr加注，翻牌后按激进度在下注加注和跟注过牌之间选
+        pub fn choose<R: Rng>(&self, rng: &mut R, context: &DecisionContext) -> Option<bool> {
+            let bluffing = rng.gen_bool(self.bluff_frequency);
+            let in_range = context.strength >= 1.0 - self.vpip;
+            if !in_range && !bluffing && context.need_to_call() {
+                return None;
             }
-            let mut value_of_all = 0;
-            let value_unit = self.min_value_unit;
-            for player in self.players.iter() {
-                value_of_all += player.owned_bank.get_values_of_bank() + player.bet_bank.get_values_of_bank();
+            if bluffing || !in_range {
+                return Some(bluffing);
             }
-            return if (value_of_all / player_num_left / 30) % value_unit != 0 {
-                (value_of_all / player_num_left / 30 + (value_unit - (value_of_all / player_num_left / 30) % value_unit))*2
+            if context.street == 0 {
+                Some(context.strength >= 1.0 - self.pfr)
             } else {
-                (value_of_all / player_num_left / 30)*2
+                Some(rng.gen_bool(self.get_aggressive_rate()))
             }
         }
-        /// 根据玩家数量、总金额计算最大下注金额
-        pub fn get_max_bet_value(&self) -> i32 {
-            let mut player_num_left = 0;
-            for player in self.players.iter() {
-                if let Role::PlaceBet|Role::GiveUp|Role::Normal|Role::XiaoMang(..)|Role::DaMang(..) = player.role {
-                    player_num_left += 1;
+        /// 按性格决定行动，加注额限制在下注结构允许的范围内，不能加注时改为跟注
+        pub fn decide<R: Rng>(&self, rng: &mut R, context: &DecisionContext) -> BotAction {
+            let call = if context.need_to_call() { BotAction::Call } else { BotAction::Check };
+            match self.choose(rng, context) {
+                None => BotAction::Fold,
+                Some(false) => call,
+                Some(true) => {
+                    let value = context.last_player_value + self.get_raise_value(rng, context.max_bet_value, context.min_value_unit);
+                    match context.raise_range {
+                        Some((min, max)) => BotAction::Raise(value.clamp(min, max)),
+                        None => call,
+                    }
                 }
             }
-            let mut value_of_all = 0;
-            let value_unit = self.min_value_unit;
-            for player in self.players.iter() {
-                value_of_all += player.owned_bank.get_values_of_bank() + player.bet_bank.get_values_of_bank();
-            }
-            return if (value_of_all / player_num_left / 3) % value_unit != 0 {
-                value_of_all/player_num_left/3 + (value_unit - (value_of_all/player_num_left/3)%value_unit)
+        }
+        /// 抽取一次加注的额度：小额加注为最大下注的10%，大额加注为20%到50%
+        pub fn get_raise_value<R: Rng>(&self, rng: &mut R, max_bet_value: i32, min_value_unit: i32) -> i32 {
+            let mut d_value = if !rng.gen_bool(self.get_big_raise_rate()) {
+                max_bet_value / 10
             } else {
-                value_of_all/player_num_left/3
+                max_bet_value / 10 * rng.gen_range(2..=5)
+            };
+            if d_value % min_value_unit != 0 {
+                d_value += min_value_unit - d_value % min_value_unit;
             }
+            d_value
         }
-        /// 添加玩家
-        pub fn add_a_player(&mut self, name: &str) -> Result<String, String> {
-            return if self.players.len() < MAX_PLAYER_NUM {
-                let mut player = Player::new(name);
-                self.players.push(player);
-                Ok(format!("Succeed to add a new player called \"{0}\"!", name))
-            } else {
-                Err(format!("Fail to add a new player because there are too many players!"))
+        /// 从TOML文本中读取性格列表
+        pub fn parse_profiles(text: &str) -> Result<Vec<BotProfile>, String> {
+            let file: BotProfileFile = match toml::from_str(text) {
+                Ok(file) => file,
+                Err(E) => return Err(format!("Fail to parse bot profiles: {0}", E)),
+            };
+            for profile in file.profile.iter() {
+                profile.check()?;
             }
+            Ok(file.profile)
         }
-        /// 游戏初始化
-        pub fn init_game(&mut self, initial: &Vec<(&StuffType, i32)>) -> Result<String, String> {
-            let mut te_stack = String::new();
-            match self.five_cards.clear_five_cards() {
-                Ok(T) => te_stack.push_str(&(T)),
-                Err(E) => te_stack.push_str(&(E)),
+        /// 从TOML文件中读取性格列表
+        pub fn load_profiles(path: &str) -> Result<Vec<BotProfile>, String> {
+            match fs::read_to_string(path) {
+                Ok(text) => BotProfile::parse_profiles(&text),
+                Err(E) => Err(format!("Fail to read bot profiles from \"{0}\": {1}", path, E)),
             }
-            match self.cash_pool.clear_cash_pool() {
-                Ok(T) => te_stack.push_str(&(T)),
-                Err(E) => te_stack.push_str(&(E)),
+        }
+        /// 根据对手的统计数据调整入池范围和诈唬频率，样本太少时不调整
+        pub fn adjust_against(&self, stats: &PlayerStats) -> BotProfile {
+            let mut profile = self.clone();
+            if stats.hands < 10 {
+                return profile;
             }
-            self.card_pool = self.card_pool.reset_card_pool();
+            // 对手面对持续下注经常弃牌，就多诈唬；对手跟注站，就少诈唬
+            if stats.cbets_faced >= 5 && stats.fold_to_cbet() > 0.6 {
+                profile.bluff_frequency = (profile.bluff_frequency * 1.5 + 0.05).min(1.0);
+            }
+            if stats.vpip() > 0.5 && stats.aggression_factor() < 1.0 {
+                profile.bluff_frequency *= 0.5;
+            }
+            // 对手很松或者很凶，就用更宽的范围跟他玩；对手很紧，就收紧范围
+            if stats.vpip() > 0.5 || stats.aggression_factor() > 2.0 {
+                profile.vpip = (profile.vpip + 0.1).min(1.0);
+            } else if stats.vpip() < 0.2 {
+                profile.vpip = (profile.vpip - 0.05).max(profile.pfr);
+            }
+            return profile;
+        }
+        /// 按名字查找性格
+        pub fn find<'a>(profiles: &'a Vec<BotProfile>, name: &str) -> Option<&'a BotProfile> {
+            profiles.iter().find(|profile| profile.name == name)
+        }
+    }
 
-            // 随机选择小盲角色
-            let length = self.players.len();
-            let mut rng = thread_rng();
-            let rand_num = rng.gen_range(0..length);
+    /// 给性格实现Display特性
+    impl Display for BotProfile {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{0}（VPIP {1:.0}%，PFR {2:.0}%，AF {3:.1}，大小加注比 {4:.1}，诈唬 {5:.0}%）", self.name, self.vpip * 100.0, self.pfr * 100.0, self.aggression_factor, self.big_raise_ratio, self.bluff_frequency * 100.0)
+        }
+    }
 
-            for (ID, player) in self.players.iter_mut().enumerate() {
-                match player.clear_my_bet_bank() {
-                    Ok(T) => te_stack.push_str(&(T)),
-                    Err(E) => te_stack.push_str(&(E)),
+    /// 机器人的行动，Raise带的是加注后自己的总下注额
+    #[derive(Clone, Debug, PartialEq)]
+    #[derive(Serialize, Deserialize)]
+    #[serde(tag = "action", content = "value", rename_all = "lowercase")]
+    pub enum BotAction {
+        Fold,
+        Check,
+        Call,
+        Raise(i32),
+    }
+
+    /// 合法的行动，Call带的是跟注后的总下注额，Raise带的是加注后总下注额的范围
+    #[derive(Clone, Debug, PartialEq)]
+    #[derive(Serialize, Deserialize)]
+    #[serde(tag = "action", rename_all = "lowercase")]
+    pub enum LegalAction {
+        Fold,
+        Check,
+        Call { value: i32 },
+        Raise { min: i32, max: i32 },
+    }
+
+    /// 机器人做决策时能看到的信息
+    #[derive(Clone)]
+    #[derive(Serialize)]
+    pub struct DecisionContext {
+        pub name: String,
+        pub hole_cards: Vec<String>,
+        /// 下注轮次：0翻前，1翻牌，2转牌，3河牌
+        pub street: usize,
+        /// 底牌强度百分位
+        pub strength: f64,
+        /// 需要跟到的下注额
+        pub last_player_value: i32,
+        /// 自己已经下注的
+        pub bet_value: i32,
+        /// 自己手里还剩的
+        pub owned_value: i32,
+        /// 钱池和所有人下注的总和
+        pub pot_value: i32,
+        pub max_bet_value: i32,
+        pub min_value_unit: i32,
+        /// 按下注结构能加注到的最小和最大总下注额，不能加注时为None
+        pub raise_range: Option<(i32, i32)>,
+        /// 上一个下注者以及对他的统计
+        pub opponent: Option<String>,
+        pub opponent_stats: Option<PlayerStats>,
+        /// 自己能看到的牌桌
+        pub view: PlayerView,
+        /// 锦标赛里还没发出去的奖金，从第一名往下排，现金局是空的
+        pub prizes: Vec<i32>,
+    }
+
+    impl DecisionContext {
+        /// 按每个座位手里和面前的筹码算出的ICM奖金期望，按座位排，现金局是None
+        pub fn get_icm_equities(&self) -> Option<Vec<f64>> {
+            if self.prizes.is_empty() {
+                return None;
+            }
+            let stacks: Vec<i32> = self.view.table.seats.iter().map(|seat| seat.stack + seat.bet).collect();
+            get_icm_equities(&stacks, &self.prizes).ok()
+        }
+        /// 是否需要补钱才能继续
+        pub fn need_to_call(&self) -> bool {
+            self.last_player_value > self.bet_value
+        }
+        /// 当前所有合法的行动
+        pub fn get_legal_actions(&self) -> Vec<LegalAction> {
+            let mut actions = Vec::new();
+            if self.need_to_call() {
+                actions.push(LegalAction::Fold);
+                actions.push(LegalAction::Call { value: self.last_player_value });
+            } else {
+                actions.push(LegalAction::Check);
+            }
+            if let Some((min, max)) = self.raise_range {
+                actions.push(LegalAction::Raise { min, max });
+            }
+            actions
+        }
+        /// 检查行动是否合法
+        pub fn check_action(&self, action: &BotAction) -> Result<(), String> {
+            let legal_actions = self.get_legal_actions();
+            let legal = legal_actions.iter().any(|legal| match (legal, action) {
+                (LegalAction::Fold, BotAction::Fold) => true,
+                (LegalAction::Check, BotAction::Check) => true,
+                (LegalAction::Call { .. }, BotAction::Call) => true,
+                (LegalAction::Raise { min, max }, BotAction::Raise(value)) => min <= value && value <= max,
+                _ => false,
+            });
+            if legal {
+                Ok(())
+            } else {
+                Err(format!("{0} cannot do {1:?}, legal actions are {2:?}!", self.name, action, legal_actions))
+            }
+        }
+        /// 不做选择时的默认行动：能过牌就过牌，否则弃牌
+        pub fn get_default_action(&self) -> BotAction {
+            if self.need_to_call() { BotAction::Fold } else { BotAction::Check }
+        }
+    }
+
+    /// 机器人策略特性，Game里按玩家名字挂上策略后由策略来决定机器人的行动
+    pub trait Strategy {
+        fn decide(&mut self, context: &DecisionContext) -> BotAction;
+    }
+
+    /// 按性格打牌的策略，用自己的随机数种子，可以复现
+    pub struct ProfileStrategy {
+        pub profile: BotProfile,
+        rng: StdRng,
+    }
+
+    impl ProfileStrategy {
+        pub fn new(profile: BotProfile, seed: u64) -> ProfileStrategy {
+            ProfileStrategy {
+                profile,
+                rng: StdRng::seed_from_u64(seed),
+            }
+        }
+    }
+
+    impl Strategy for ProfileStrategy {
+        fn decide(&mut self, context: &DecisionContext) -> BotAction {
+            let profile = match &context.opponent_stats {
+                Some(stats) => self.profile.adjust_against(stats),
+                None => self.profile.clone(),
+            };
+            profile.decide(&mut self.rng, context)
+        }
+    }
+
+    /// 按预先写好的脚本行动的策略，用于测试；脚本和合法行动对不上时直接panic
+    pub struct ScriptedStrategy {
+        pub script: VecDeque<BotAction>,
+    }
+
+    impl ScriptedStrategy {
+        pub fn new(script: Vec<BotAction>) -> ScriptedStrategy {
+            ScriptedStrategy {
+                script: script.into_iter().collect(),
+            }
+        }
+        /// 解析脚本，f弃牌，x过牌，c跟注，r60加注到60，用空格或逗号分隔
+        pub fn parse(text: &str) -> Result<ScriptedStrategy, String> {
+            let mut script = Vec::new();
+            for token in text.split(|c: char| c == ',' || c.is_whitespace()).filter(|token| !token.is_empty()) {
+                let action = match token {
+                    "f" => BotAction::Fold,
+                    "x" => BotAction::Check,
+                    "c" => BotAction::Call,
+                    _ if token.starts_with('r') => match token[1..].parse::<i32>() {
+                        Ok(value) => BotAction::Raise(value),
+                        Err(_) => return Err(format!("Fail to parse the raise \"{0}\" in the script!", token)),
+                    },
+                    _ => return Err(format!("Fail to parse the action \"{0}\" in the script!", token)),
+                };
+                script.push(action);
+            }
+            Ok(ScriptedStrategy::new(script))
+        }
+        /// 脚本里还没用到的行动数
+        pub fn remaining(&self) -> usize {
+            self.script.len()
+        }
+    }
+
+    impl Strategy for ScriptedStrategy {
+        fn decide(&mut self, context: &DecisionContext) -> BotAction {
+            let action = match self.script.pop_front() {
+                Some(action) => action,
+                None => panic!("The script of {0} has run out, legal actions are {1:?}!", context.name, context.get_legal_actions()),
+            };
+            if let Err(E) = context.check_action(&action) {
+                panic!("The script diverges: {0}", E);
+            }
+            action
+        }
+    }
+}
+
+///根据游戏中的行动统计每个对手的打法（VPIP、PFR、AF、持续下注弃牌率、摊牌率）
+mod module_stats {
+    use std::collections::HashMap;
+    use std::fmt;
+    use std::fmt::Display;
+    use serde::{Deserialize, Serialize};
+
+    /// 统计用的行动类型
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum ActionType {
+        Fold,
+        Check,
+        Call,
+        Raise,
+    }
+
+    /// 单个玩家的累计数据
+    #[derive(Clone, Default, Debug)]
+    #[derive(Serialize, Deserialize)]
+    pub struct PlayerStats {
+        pub hands: u32,
+        pub vpip_hands: u32,
+        pub pfr_hands: u32,
+        /// 翻牌后（翻牌、转牌、河牌圈）的下注和加注次数，翻前的不算，用来算AF
+        pub bets_and_raises: u32,
+        /// 翻牌后的跟注次数，翻前的不算，用来算AF
+        pub calls: u32,
+        pub cbets_faced: u32,
+        pub folds_to_cbet: u32,
+        pub flops_seen: u32,
+        pub showdowns: u32,
+    }
+
+    /// 当前这一手里玩家已经做过的事，避免一手里重复计数
+    #[derive(Clone, Default)]
+    #[derive(Serialize, Deserialize)]
+    struct HandFlags {
+        vpip: bool,
+        pfr: bool,
+        saw_flop: bool,
+        faced_cbet: bool,
+    }
+
+    /// 对手数据统计器
+    #[derive(Clone, Default)]
+    #[derive(Serialize, Deserialize)]
+    pub struct OpponentStats {
+        stats: HashMap<String, PlayerStats>,
+        hand: HashMap<String, HandFlags>,
+        preflop_aggressor: Option<String>,
+        flop_bet_made: bool,
+        cbet_made: bool,
+    }
+
+    impl PlayerStats {
+        /// 主动入池率
+        pub fn vpip(&self) -> f64 {
+            ratio(self.vpip_hands, self.hands)
+        }
+        /// 翻前加注率
+        pub fn pfr(&self) -> f64 {
+            ratio(self.pfr_hands, self.hands)
+        }
+        /// 激进度AF：翻牌后下注和加注次数除以跟注次数
+        pub fn aggression_factor(&self) -> f64 {
+            if self.calls == 0 {
+                self.bets_and_raises as f64
+            } else {
+                self.bets_and_raises as f64 / self.calls as f64
+            }
+        }
+        /// 面对持续下注时的弃牌率
+        pub fn fold_to_cbet(&self) -> f64 {
+            ratio(self.folds_to_cbet, self.cbets_faced)
+        }
+        /// 看到翻牌后走到摊牌的比例
+        pub fn showdown_frequency(&self) -> f64 {
+            ratio(self.showdowns, self.flops_seen)
+        }
+    }
+
+    fn ratio(part: u32, total: u32) -> f64 {
+        if total == 0 { 0.0 } else { part as f64 / total as f64 }
+    }
+
+    impl OpponentStats {
+        pub fn new() -> OpponentStats {
+            OpponentStats::default()
+        }
+        /// 查询某个玩家的数据
+        pub fn get(&self, name: &str) -> Option<&PlayerStats> {
+            self.stats.get(name)
+        }
+        /// 新的一手开始，names是参与这一手的玩家
+        pub fn start_hand(&mut self, names: &Vec<String>) {
+            self.hand.clear();
+            self.preflop_aggressor = None;
+            self.flop_bet_made = false;
+            self.cbet_made = false;
+            for name in names.iter() {
+                self.stats.entry(name.clone()).or_default().hands += 1;
+                self.hand.insert(name.clone(), HandFlags::default());
+            }
+        }
+        /// 记录一次行动，street从0开始：0翻前，1翻牌，2转牌，3河牌
+        pub fn record_action(&mut self, name: &str, street: usize, action: ActionType) {
+            let flags = self.hand.entry(name.to_string()).or_default();
+            let stats = self.stats.entry(name.to_string()).or_default();
+            if street >= 1 && !flags.saw_flop {
+                flags.saw_flop = true;
+                stats.flops_seen += 1;
+            }
+            if street >= 1 {
+                match action {
+                    ActionType::Raise => stats.bets_and_raises += 1,
+                    ActionType::Call => stats.calls += 1,
+                    _ => {}
                 }
-                match player.clear_my_cards() {
-                    Ok(T) => te_stack.push_str(&(T)),
-                    Err(E) => te_stack.push_str(&(E)),
+            }
+            if street == 0 {
+                if let ActionType::Call | ActionType::Raise = action {
+                    if !flags.vpip {
+                        flags.vpip = true;
+                        stats.vpip_hands += 1;
+                    }
                 }
-                match player.initial_my_owned_bank(initial) {
-                    Ok(T) => te_stack.push_str(&(T)),
-                    Err(E) => te_stack.push_str(&(E)),
+                if action == ActionType::Raise {
+                    if !flags.pfr {
+                        flags.pfr = true;
+                        stats.pfr_hands += 1;
+                    }
+                    self.preflop_aggressor = Some(name.to_string());
+                }
+            } else if street == 1 {
+                let is_aggressor = self.preflop_aggressor.as_deref() == Some(name);
+                if self.cbet_made && !is_aggressor && !flags.faced_cbet {
+                    // 面对翻牌前加注者在翻牌圈的持续下注
+                    flags.faced_cbet = true;
+                    stats.cbets_faced += 1;
+                    if action == ActionType::Fold {
+                        stats.folds_to_cbet += 1;
+                    }
+                }
+                if action == ActionType::Raise {
+                    if is_aggressor && !self.flop_bet_made {
+                        self.cbet_made = true;
+                    }
+                    self.flop_bet_made = true;
                 }
             }
-            self.get_min_value_unit();
-            self.players[rand_num].role = Role::XiaoMang(self.get_XiaoMang_value());
-            self.last_XiaoMang_ID = rand_num as i32;
-            if length - 1 == rand_num {
-                self.players[0].role = Role::DaMang(self.get_DaMang_value());
+        }
+        /// 记录走到摊牌的玩家
+        pub fn record_showdown(&mut self, names: &Vec<String>) {
+            for name in names.iter() {
+                let flags = self.hand.entry(name.clone()).or_default();
+                let stats = self.stats.entry(name.clone()).or_default();
+                if !flags.saw_flop {
+                    flags.saw_flop = true;
+                    stats.flops_seen += 1;
+                }
+                stats.showdowns += 1;
+            }
+        }
+    }
+
+    /// 给玩家数据实现Display特性
+    impl Display for PlayerStats {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{0}手，VPIP {1:.0}%，PFR {2:.0}%，AF {3:.1}，持续下注弃牌 {4:.0}%，摊牌 {5:.0}%",
+                   self.hands, self.vpip() * 100.0, self.pfr() * 100.0, self.aggression_factor(), self.fold_to_cbet() * 100.0, self.showdown_frequency() * 100.0)
+        }
+    }
+}
+
+///牌局记录：记下每一手的座位、筹码、盲注、底牌、各轮行动、公共牌、摊牌和分池，可以导出成PokerStars的文本格式，也能从JSON和PokerStars文本读回来
+mod module_history {
+    use std::fmt::Write;
+    use std::time::{SystemTime, UNIX_EPOCH};
+    use serde::{Deserialize, Serialize};
+    use crate::module_card::{Card, FiveCardsCategory};
+
+    /// 牌局记录里的一次行动，金额都是这一手里的累计下注
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    pub enum HistoryAction {
+        /// 前注直接进底池，不算在跟注的金额里
+        Ante(i32),
+        SmallBlind(i32),
+        BigBlind(i32),
+        /// 抓头，相当于翻前多了一个两倍的大盲
+        Straddle(i32),
+        Fold,
+        Check,
+        /// 补了多少
+        Call(i32),
+        /// 加注到多少（这一手累计）
+        Raise(i32),
+    }
+
+    impl HistoryAction {
+        /// 是不是发牌前强制下的注
+        pub fn is_forced(&self) -> bool {
+            matches!(self, HistoryAction::Ante(..) | HistoryAction::SmallBlind(..) | HistoryAction::BigBlind(..) | HistoryAction::Straddle(..))
+        }
+    }
+
+    /// 某个玩家在某一轮的行动
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct HistoryEntry {
+        pub name: String,
+        /// 下注轮次：0翻前，1翻牌，2转牌，3河牌
+        pub street: usize,
+        pub action: HistoryAction,
+        pub all_in: bool,
+    }
+
+    /// 座位信息，seat从0开始
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct SeatInfo {
+        pub seat: usize,
+        pub name: String,
+        pub stack: i32,
+        pub hole_cards: Vec<String>,
+    }
+
+    fn get_default_limit() -> String {
+        "No Limit".to_string()
+    }
+
+    /// 一手牌的完整记录，牌都用“As”“Td”这样的简写
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct HandHistory {
+        pub hand_id: u64,
+        pub table_name: String,
+        pub max_seats: usize,
+        /// 开始时间，Unix秒数
+        pub started_at: u64,
+        /// 下注结构，“No Limit”“Pot Limit”或“Limit”
+        #[serde(default = "get_default_limit")]
+        pub limit: String,
+        pub button: usize,
+        pub small_blind: i32,
+        pub big_blind: i32,
+        pub seats: Vec<SeatInfo>,
+        pub board: Vec<String>,
+        pub actions: Vec<HistoryEntry>,
+        /// 摊牌的玩家和牌型
+        pub showdown: Vec<(String, String)>,
+        /// 赢得的筹码，已经扣掉抽水
+        pub awards: Vec<(String, i32)>,
+        /// 抽水
+        #[serde(default)]
+        pub rake: i32,
+    }
+
+    const STREET_NAMES: [&str; 4] = ["Preflop", "Flop", "Turn", "River"];
+
+    /// 牌型的英文名字
+    pub fn get_category_name(category: &FiveCardsCategory) -> String {
+        match category {
+            FiveCardsCategory::RoyalFlush => "a Royal Flush",
+            FiveCardsCategory::StraightFlush => "a straight flush",
+            FiveCardsCategory::FourOfAKind => "four of a kind",
+            FiveCardsCategory::FullHouse => "a full house",
+            FiveCardsCategory::Flush => "a flush",
+            FiveCardsCategory::Straight => "a straight",
+            FiveCardsCategory::ThreeOfAKind => "three of a kind",
+            FiveCardsCategory::TwoPairs => "two pair",
+            FiveCardsCategory::Pair => "a pair",
+            FiveCardsCategory::HighCard => "high card",
+        }.to_string()
+    }
+
+    /// 把Unix秒数转成“2026/10/19 12:00:00”
+    fn format_time(seconds: u64) -> String {
+        let days = (seconds / 86400) as i64;
+        let rest = seconds % 86400;
+        // 公历日期换算
+        let z = days + 719468;
+        let era = z.div_euclid(146097);
+        let doe = z - era * 146097;
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+        format!("{0}/{1:02}/{2:02} {3:02}:{4:02}:{5:02}", year, month, day, rest / 3600, rest % 3600 / 60, rest % 60)
+    }
+
+    /// 取出两个分隔符之间的文字
+    fn get_between<'a>(text: &'a str, open: &str, close: &str) -> Option<&'a str> {
+        let start = text.find(open)? + open.len();
+        let end = text[start..].find(close)? + start;
+        Some(&text[start..end])
+    }
+
+    /// 解析金额，带货币符号的按分计
+    fn parse_amount(text: &str, cents: bool) -> Result<i32, String> {
+        let number: String = text.chars().filter(|c| c.is_ascii_digit() || *c == '.').collect();
+        match number.parse::<f64>() {
+            Ok(T) => Ok(if cents { (T * 100.0).round() as i32 } else { T.round() as i32 }),
+            Err(_) => Err(format!("Fail to parse the amount \"{0}\"!", text)),
+        }
+    }
+
+    /// 统一成“As”“Td”这样的简写
+    fn get_short_names(cards: &Vec<&str>) -> Result<Vec<String>, String> {
+        cards.iter().map(|card| Card::parse(card).map(|card| card.get_short_name())).collect()
+    }
+
+    /// 把含有多手牌的PokerStars文本拆开逐手解析
+    pub fn parse_pokerstars_file(text: &str) -> Result<Vec<HandHistory>, String> {
+        let mut hands = Vec::new();
+        let mut current = String::new();
+        for line in text.lines() {
+            if line.trim_start().starts_with("PokerStars ") && !current.trim().is_empty() {
+                hands.push(HandHistory::parse_pokerstars(&current)?);
+                current.clear();
+            }
+            current.push_str(line);
+            current.push('\n');
+        }
+        if !current.trim().is_empty() {
+            hands.push(HandHistory::parse_pokerstars(&current)?);
+        }
+        Ok(hands)
+    }
+
+    impl HandHistory {
+        pub fn new(hand_id: u64, button: usize, small_blind: i32, big_blind: i32, seats: Vec<SeatInfo>) -> HandHistory {
+            let started_at = match SystemTime::now().duration_since(UNIX_EPOCH) {
+                Ok(T) => T.as_secs(),
+                Err(_) => 0,
+            };
+            HandHistory {
+                hand_id,
+                table_name: "TexasPoker".to_string(),
+                max_seats: 10,
+                started_at,
+                limit: get_default_limit(),
+                button,
+                small_blind,
+                big_blind,
+                seats,
+                board: Vec::new(),
+                actions: Vec::new(),
+                showdown: Vec::new(),
+                awards: Vec::new(),
+                rake: 0,
+            }
+        }
+        /// 记录一次行动
+        pub fn record(&mut self, name: &str, street: usize, action: HistoryAction, all_in: bool) {
+            self.actions.push(HistoryEntry { name: name.to_string(), street, action, all_in });
+        }
+        /// 所有人下注的总和，包括抽水
+        pub fn get_total_pot(&self) -> i32 {
+            self.awards.iter().map(|award| award.1).sum::<i32>() + self.rake
+        }
+        /// 按行动算出每个人这一手一共下了多少，包括前注
+        pub fn get_committed(&self) -> Vec<(String, i32)> {
+            let mut committed: Vec<(String, i32)> = self.seats.iter().map(|seat| (seat.name.clone(), 0)).collect();
+            let mut antes: Vec<i32> = vec![0; committed.len()];
+            for entry in self.actions.iter() {
+                if let Some(index) = committed.iter().position(|item| item.0 == entry.name) {
+                    match entry.action {
+                        HistoryAction::Ante(value) => antes[index] += value,
+                        HistoryAction::SmallBlind(value) | HistoryAction::BigBlind(value) | HistoryAction::Straddle(value) | HistoryAction::Call(value) => committed[index].1 += value,
+                        HistoryAction::Raise(to) => committed[index].1 = to,
+                        _ => {}
+                    }
+                }
+            }
+            for (item, ante) in committed.iter_mut().zip(antes.iter()) {
+                item.1 += ante;
+            }
+            committed
+        }
+        /// 导出成JSON
+        pub fn to_json(&self) -> Result<String, String> {
+            match serde_json::to_string(self) {
+                Ok(T) => Ok(T),
+                Err(E) => Err(format!("Fail to encode the hand history: {0}", E)),
+            }
+        }
+        /// 从JSON读回来
+        pub fn from_json(text: &str) -> Result<HandHistory, String> {
+            match serde_json::from_str(text) {
+                Ok(T) => Ok(T),
+                Err(E) => Err(format!("Fail to decode the hand history: {0}", E)),
+            }
+        }
+        /// 某个玩家最后一次弃牌的轮次
+        fn get_fold_street(&self, name: &str) -> Option<usize> {
+            self.actions.iter().find(|entry| entry.name == name && entry.action == HistoryAction::Fold).map(|entry| entry.street)
+        }
+        /// 一手里最后有行动的轮次
+        fn get_last_street(&self) -> usize {
+            let street = self.actions.iter().map(|entry| entry.street).max().unwrap_or(0);
+            if self.showdown.len() >= 2 { 3 } else { street }
+        }
+
+        /// 以hero的视角导出成PokerStars的文本格式，只有hero的底牌写在Dealt to里，别人的牌只在摊牌和总结里出现；hero为None时是旁观者的视角
+        pub fn to_pokerstars(&self, hero: Option<&str>) -> String {
+            let mut text = String::new();
+            let _ = writeln!(text, "PokerStars Hand #{0}: Hold'em {4} ({1}/{2}) - {3} UTC",
+                             self.hand_id, self.small_blind, self.big_blind, format_time(self.started_at), self.limit);
+            let _ = writeln!(text, "Table '{0}' {1}-max Seat #{2} is the button", self.table_name, self.max_seats, self.button + 1);
+            for seat in self.seats.iter() {
+                let _ = writeln!(text, "Seat {0}: {1} ({2} in chips)", seat.seat + 1, seat.name, seat.stack);
+            }
+            // 每个玩家已经下的注和当前这一轮的最高注，用来把累计金额换算成PokerStars里每一轮的金额
+            let mut committed: Vec<(String, i32)> = self.seats.iter().map(|seat| (seat.name.clone(), 0)).collect();
+            let mut level = 0;
+            let mut street_base = 0;
+            let mut street = 0;
+            for entry in self.actions.iter().filter(|entry| entry.action.is_forced()) {
+                self.write_action(&mut text, entry, &mut committed, &mut level, street_base);
+            }
+            let _ = writeln!(text, "*** HOLE CARDS ***");
+            for seat in self.seats.iter().filter(|seat| Some(seat.name.as_str()) == hero && !seat.hole_cards.is_empty()) {
+                let _ = writeln!(text, "Dealt to {0} [{1}]", seat.name, seat.hole_cards.join(" "));
+            }
+            for entry in self.actions.iter().filter(|entry| !entry.action.is_forced()) {
+                while street < entry.street {
+                    street += 1;
+                    self.write_street(&mut text, street);
+                    street_base = level;
+                }
+                self.write_action(&mut text, entry, &mut committed, &mut level, street_base);
+            }
+            if self.showdown.len() >= 2 {
+                while street < 3 {
+                    street += 1;
+                    self.write_street(&mut text, street);
+                }
+                let _ = writeln!(text, "*** SHOW DOWN ***");
+                for (name, category) in self.showdown.iter() {
+                    let _ = writeln!(text, "{0}: shows [{1}] ({2})", name, self.get_hole_cards(name), category);
+                }
+            }
+            for (name, value) in self.awards.iter() {
+                let _ = writeln!(text, "{0} collected {1} from pot", name, value);
+            }
+            let _ = writeln!(text, "*** SUMMARY ***");
+            let _ = writeln!(text, "Total pot {0} | Rake {1}", self.get_total_pot(), self.rake);
+            let shown = (self.get_last_street() + 2).min(self.board.len());
+            if shown >= 3 {
+                let _ = writeln!(text, "Board [{0}]", self.board[..shown].join(" "));
+            }
+            for seat in self.seats.iter() {
+                let _ = writeln!(text, "Seat {0}: {1}{2} {3}", seat.seat + 1, seat.name, self.get_position_name(seat), self.get_result(&seat.name));
+            }
+            text
+        }
+
+        fn write_street(&self, text: &mut String, street: usize) {
+            let header = ["", "FLOP", "TURN", "RIVER"][street];
+            let shown = street + 2;
+            if self.board.len() < shown {
+                let _ = writeln!(text, "*** {0} ***", header);
+            } else if street == 1 {
+                let _ = writeln!(text, "*** FLOP *** [{0}]", self.board[..3].join(" "));
             } else {
-                self.players[rand_num+1].role = Role::DaMang(self.get_DaMang_value());
+                let _ = writeln!(text, "*** {0} *** [{1}] [{2}]", header, self.board[..shown - 1].join(" "), self.board[shown - 1]);
             }
-            Ok(te_stack)
         }
 
-        /// 玩家事件响应
-        pub fn receive_my_event(&mut self, event: MyEvent) -> Result<String, String> {
-            let game_status = self.game_status.clone();
-            match event {
-                MyEvent::AddBot { num } => {
-                    match game_status {
-                        GameStatus::Setting => {
-                            self.game_status = GameStatus::Setting;
-                            let players_temp = self.players.clone();
-                            let mut te_stack = String::new();
-                            for i in 0..num {
-                                match self.add_a_player(PLAYER_NAME[players_temp.len() + i]) {
-                                    Ok(T) => te_stack.push_str(&T),
-                                    Err(E) => te_stack.push_str(&E),
-                                }
-                            }
-                            return Ok(te_stack);
-                        },
-                        GameStatus::CardsPicking => {
-                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
-                        },
-                        GameStatus::BetPlacing1 => {
-                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
-                        },
-                        GameStatus::BetPlacing2 => {
-                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
-                        },
-                        GameStatus::BetPlacing3 => {
-                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
-                        },
-                        GameStatus::BetPlacing4=> {
-                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
-                        },
-                        GameStatus::Balancing => {
-                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
-                        },
+        fn write_action(&self, text: &mut String, entry: &HistoryEntry, committed: &mut Vec<(String, i32)>, level: &mut i32, street_base: i32) {
+            let index = match committed.iter().position(|item| item.0 == entry.name) {
+                Some(T) => T,
+                None => {
+                    committed.push((entry.name.clone(), 0));
+                    committed.len() - 1
+                }
+            };
+            let all_in = if entry.all_in { " and is all-in" } else { "" };
+            let line = match entry.action {
+                HistoryAction::Ante(value) => format!("posts the ante {0}{1}", value, all_in),
+                HistoryAction::SmallBlind(value) => format!("posts small blind {0}", value),
+                HistoryAction::BigBlind(value) => format!("posts big blind {0}", value),
+                HistoryAction::Straddle(value) => format!("posts straddle {0}{1}", value, all_in),
+                HistoryAction::Fold => "folds".to_string(),
+                HistoryAction::Check => "checks".to_string(),
+                HistoryAction::Call(value) => format!("calls {0}{1}", value, all_in),
+                HistoryAction::Raise(to) => {
+                    if *level > street_base {
+                        format!("raises {0} to {1}{2}", to - *level, to - street_base, all_in)
+                    } else {
+                        format!("bets {0}{1}", to - street_base, all_in)
                     }
-                },
-                MyEvent::ResetGame { assets } => {
-                    match game_status {
-                        GameStatus::Setting => {
-                            self.game_status = GameStatus::Setting;
-                            return self.init_game(assets);
-                        },
-                        GameStatus::CardsPicking => {
-                            self.game_status = GameStatus::Setting;
-                            return self.init_game(assets);
-                        },
-                        GameStatus::BetPlacing1 => {
-                            self.game_status = GameStatus::Setting;
-                            return self.init_game(assets);
-                        },
-                        GameStatus::BetPlacing2 => {
-                            self.game_status = GameStatus::Setting;
-                            return self.init_game(assets);
-                        },
-                        GameStatus::BetPlacing3 => {
-                            self.game_status = GameStatus::Setting;
-                            return self.init_game(assets);
-                        },
-                        GameStatus::BetPlacing4 => {
-                            self.game_status = GameStatus::Setting;
-                            return self.init_game(assets);
-                        },
-                        GameStatus::Balancing => {
-                            self.game_status = GameStatus::Setting;
-                            return self.init_game(assets);
-                        },
+                }
+            };
+            match entry.action {
+                HistoryAction::SmallBlind(value) | HistoryAction::BigBlind(value) | HistoryAction::Straddle(value) | HistoryAction::Call(value) => committed[index].1 += value,
+                HistoryAction::Raise(to) => committed[index].1 = to,
+                _ => {}
+            }
+            *level = (*level).max(committed[index].1);
+            let _ = writeln!(text, "{0}: {1}", entry.name, line);
+        }
+
+        /// 解析一手PokerStars文本，金额带货币符号时按分计
+        pub fn parse_pokerstars(text: &str) -> Result<HandHistory, String> {
+            let mut lines = text.lines().map(|line| line.trim()).filter(|line| !line.is_empty());
+            let header = lines.next().ok_or("The hand history is empty!".to_string())?;
+            if !header.starts_with("PokerStars ") || !header.contains("Hold'em") {
+                return Err(format!("Fail to parse the header \"{0}\"!", header));
+            }
+            let cents = header.contains('$') || header.contains('€') || header.contains('£');
+            let hand_id = match header.split('#').nth(1).and_then(|rest| rest.split(':').next()) {
+                Some(T) => T.trim().parse::<u64>().map_err(|_| format!("Fail to parse the hand id in \"{0}\"!", header))?,
+                None => return Err(format!("Fail to parse the hand id in \"{0}\"!", header)),
+            };
+            let blinds = get_between(header, "(", ")").ok_or(format!("Fail to parse the blinds in \"{0}\"!", header))?;
+            let (small_blind, big_blind) = match blinds.split_once('/') {
+                Some((small, big)) => (parse_amount(small, cents)?, parse_amount(big.split_whitespace().next().unwrap_or(""), cents)?),
+                None => return Err(format!("Fail to parse the blinds in \"{0}\"!", header)),
+            };
+            let mut history = HandHistory::new(hand_id, 0, small_blind, big_blind, Vec::new());
+            if let Some(limit) = get_between(header, "Hold'em ", " (") {
+                history.limit = limit.trim().to_string();
+            }
+            let mut button_number = 0;
+            // 每个人已经下的注、当前的最高注、这一轮开始时的最高注
+            let mut committed: Vec<i32> = Vec::new();
+            let mut level = 0;
+            let mut street_base = 0;
+            let mut street = 0;
+            let mut in_seats = true;
+            // 最后一次加注：(记录里的位置, 座位, 加注前的最高注, 加注前自己下的注)
+            let mut last_raise: Option<(usize, usize, i32, i32)> = None;
+            for line in lines.by_ref() {
+                if line.starts_with("*** SUMMARY") {
+                    break;
+                }
+                if line.starts_with("Table '") {
+                    if let Some(rest) = line.rsplit('\'').next() {
+                        let words: Vec<&str> = rest.split_whitespace().collect();
+                        if let Some(max) = words.first().and_then(|word| word.strip_suffix("-max")) {
+                            history.max_seats = max.parse().unwrap_or(10);
+                        }
+                        if let Some(seat) = words.iter().find(|word| word.starts_with('#')) {
+                            button_number = seat[1..].parse().unwrap_or(0);
+                        }
                     }
-                },
-                MyEvent::StartNextGame => {
-                    match game_status {
-                        GameStatus::Setting => {
-                            self.game_status = GameStatus::CardsPicking;
-                            return Ok("游戏开始！".to_string());
-                        },
-                        GameStatus::CardsPicking => {
-                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
-                        },
-                        GameStatus::BetPlacing1 => {
-                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
-                        },
-                        GameStatus::BetPlacing2 => {
-                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
-                        },
-                        GameStatus::BetPlacing3 => {
-                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
-                        },
-                        GameStatus::BetPlacing4 => {
-                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
-                        },
-                        GameStatus::Balancing => {
-                            self.game_status = GameStatus::CardsPicking;
-                            // 剩余玩家重新分配大小盲
-                            let player_num = self.players.len();
-                            let mut player_num_left = 0;
-                            let mut ID:usize = self.last_XiaoMang_ID as usize;
-                            for (ID, player) in self.players.iter_mut().enumerate() {
-                                if let Role::PlaceBet|Role::GiveUp|Role::Normal|Role::XiaoMang(..)|Role::DaMang(..) = player.role {
-                                    player_num_left += 1;
-                                }
-                            }
-                            if player_num_left <= 1 {
-                                self.game_status = GameStatus::Balancing;
-                                return Ok(format!("游戏已经结束！只有{0}人留在场上！",player_num_left));
-                            }
-                            loop {
-                                if ID == player_num - 1 {
-                                    ID = 0;
-                                } else {
-                                    ID += 1;
-                                }
-                                if let Role::PlaceBet|Role::GiveUp|Role::Normal|Role::XiaoMang(..)|Role::DaMang(..) = self.players[ID].role {
-                                    self.last_XiaoMang_ID = ID as i32;
-                                    break;
-                                }
-                            }
-                            let mut player_num_before_xiaomang = 0;
-                            for (ID, player) in self.players.iter_mut().enumerate() {
-                                if let Role::PlaceBet|Role::GiveUp|Role::Normal|Role::XiaoMang(..)|Role::DaMang(..) = player.role {
-                                    if ID == self.last_XiaoMang_ID as usize {
-                                        break;
-                                    }
-                                    player_num_before_xiaomang += 1;
-                                }
-                            }
-                            let mut stack = Vec::new();
-                            stack.push(Role::XiaoMang(self.get_XiaoMang_value()));
-                            stack.push(Role::DaMang(self.get_DaMang_value()));
-                            for i in 0..player_num_left-2 {
-                                    stack.push(Role::Normal);
-                            }
-                            for i in 0..player_num_before_xiaomang {
-                                if let Some(T) = stack.pop() {
-                                    stack.reverse();
-                                    stack.push(T);
-                                    stack.reverse();
-                                }
-                            }
-                            stack.reverse();
-                            for (ID, player) in self.players.iter_mut().enumerate() {
-                                if let Role::PlaceBet|Role::GiveUp|Role::Normal|Role::XiaoMang(..)|Role::DaMang(..) = player.role {
-                                    if let Some(T) = stack.pop() {
-                                        player.role = T;
-                                    }
-                                }
-                            }
-                            return Ok(format!("游戏开始！还有{0}人留在场上！",player_num_left));
-                        },
+                    history.table_name = get_between(line, "'", "'").unwrap_or("").to_string();
+                    continue;
+                }
+                if in_seats && line.starts_with("Seat ") {
+                    let (number, rest) = line[5..].split_once(": ").ok_or(format!("Fail to parse the seat \"{0}\"!", line))?;
+                    let number: usize = number.parse().map_err(|_| format!("Fail to parse the seat \"{0}\"!", line))?;
+                    let open = rest.rfind(" (").ok_or(format!("Fail to parse the seat \"{0}\"!", line))?;
+                    let stack = get_between(&rest[open..], "(", " in chips").ok_or(format!("Fail to parse the seat \"{0}\"!", line))?;
+                    history.seats.push(SeatInfo { seat: number - 1, name: rest[..open].to_string(), stack: parse_amount(stack, cents)?, hole_cards: Vec::new() });
+                    committed.push(0);
+                    continue;
+                }
+                if line.starts_with("*** ") {
+                    in_seats = false;
+                    let board = match line.find('[') {
+                        Some(T) => line[T..].replace(['[', ']'], " "),
+                        None => String::new(),
+                    };
+                    let cards: Vec<&str> = board.split_whitespace().collect();
+                    let next = if line.starts_with("*** FLOP") { 1 } else if line.starts_with("*** TURN") { 2 } else if line.starts_with("*** RIVER") { 3 } else { street };
+                    if next != street {
+                        street = next;
+                        street_base = level;
+                        history.board = get_short_names(&cards)?;
                     }
-                },
-                MyEvent::PickCards => {
-                    match game_status {
-                        GameStatus::Setting => {
-                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
-                        },
-                        GameStatus::CardsPicking => {
-                            self.game_status = GameStatus::BetPlacing1;
-                            let player_num = self.players.len();
-                            let mut te_stack = String::new();
-                            // 每人抽两张卡
-                            for player in self.players.iter_mut() {
-                                match player.get_two_cards(&mut self.card_pool) {
-                                    Ok(T) => te_stack.push_str(&T),
-                                    Err(E) => te_stack.push_str(&E),
-                                }
-                            }
-                            // 抽五张卡
-                            match self.five_cards.get_five_cards(&mut self.card_pool) {
-                                Ok(T) => te_stack.push_str(&T),
-                                Err(E) => te_stack.push_str(&E),
-                            }
-                            // 从小盲开始出钱
-                            let mut game_clone = self.clone();
-                            'outer:for (ID, player) in game_clone.players.iter().enumerate() {
-                                match self.players[ID].role {
-                                    Role::XiaoMang(value) => {
-                                        // 检查是不是我
-                                        if player.name == "ME" {
-                                            return Ok(te_stack + &format!("It's time for you!"));
-                                        }
-                                        // 下注指定的资金
-                                        match self.players[ID].place_a_bet_with_value(value) {
-                                            Ok(T) => te_stack.push_str(&T),
-                                            Err(E) => te_stack.push_str(&E),
-                                        }
-                                    }
-                                    Role::DaMang(value) => {
-                                        // 检查是否其他人都放弃或者退出了
-                                        let mut last_player_ID = ID;
-                                        loop {
-                                            if last_player_ID == 0 {
-                                                last_player_ID = player_num - 1;
-                                            } else {
-                                                last_player_ID -= 1;
-                                            }
-                                            if last_player_ID == ID {
-                                                self.game_status = GameStatus::Balancing;
-                                                return Ok(te_stack + &format!("{0} win the game because nobody place a bet!", self.players[ID].name));
-                                            }
-                                            if let Role::PlaceBet|Role::DaMang(..)|Role::XiaoMang(..)|Role::Normal = self.players[last_player_ID].role {
-                                                break;
-                                            }
-                                        }
-                                        // 检查前面第一个不是旁观的角色是不是下注或放弃，如果是就可以下注，否则不能下注
-                                        let mut last_player_ID = ID;
-                                        loop {
-                                            if last_player_ID == 0 {
-                                                last_player_ID = player_num - 1;
-                                            } else {
-                                                last_player_ID -= 1;
-                                            }
-                                            if let Role::PlaceBet|Role::Normal|Role::GiveUp|Role::XiaoMang(..)|Role::DaMang(..) = self.players[last_player_ID].role {
-                                                if let Role::PlaceBet|Role::GiveUp = self.players[last_player_ID].role {
-                                                    break;
-                                                } else {
-                                                    continue 'outer;
-                                                }
-                                            }
-                                        }
-                                        // 检查是不是我
-                                        if player.name == "ME" {
-                                            return Ok(te_stack + &format!("It's time for you!"));
-                                        }
-                                        // 下注指定的资金
-                                        match self.players[ID].place_a_bet_with_value(value) {
-                                            Ok(T) => te_stack.push_str(&T),
-                                            Err(E) => te_stack.push_str(&E),
-                                        }
-                                    }
-                                    Role::Normal => {
-                                        // 检查是否其他人都放弃或者退出了
-                                        let mut last_player_ID = ID;
-                                        loop {
-                                            if last_player_ID == 0 {
-                                                last_player_ID = player_num - 1;
-                                            } else {
-                                                last_player_ID -= 1;
-                                            }
-                                            if last_player_ID == ID {
-                                                self.game_status = GameStatus::Balancing;
-                                                return Ok(te_stack + &format!("{0} win the game because nobody place a bet!", self.players[ID].name));
-                                            }
-                                            if let Role::PlaceBet|Role::DaMang(..)|Role::XiaoMang(..)|Role::Normal = self.players[last_player_ID].role {
-                                                break;
-                                            }
-                                        }
-                                        // 检查前面第一个不是旁观的角色是不是下注或放弃，如果是就可以下注，否则不能下注
-                                        let mut last_player_ID = ID;
-                                        loop {
-                                            if last_player_ID == 0 {
-                                                last_player_ID = player_num - 1;
-                                            } else {
-                                                last_player_ID -= 1;
-                                            }
-                                            if let Role::PlaceBet|Role::Normal|Role::GiveUp|Role::XiaoMang(..)|Role::DaMang(..) = self.players[last_player_ID].role {
-                                                if let Role::PlaceBet|Role::GiveUp = self.players[last_player_ID].role {
-                                                    break;
-                                                } else {
-                                                    continue 'outer;
-                                                }
-                                            }
-                                        }
-                                        // 检查是不是我
-                                        if player.name == "ME" {
-                                            return Ok(te_stack + &format!("It's time for you!"));
-                                        }
-                                        // 获取需要用于比较的value
-                                        let mut value = 0;
-                                        if let Role::PlaceBet = self.players[last_player_ID].role {
-                                            let players_clone = self.players.clone();
-                                            value = players_clone[last_player_ID].bet_bank.get_values_of_bank();
-                                        } else {
-                                            loop {
-                                                if last_player_ID == 0 {
-                                                    last_player_ID = player_num - 1;
-                                                } else {
-                                                    last_player_ID -= 1;
-                                                }
-                                                if last_player_ID == ID {
-                                                    value = 0;
-                                                    break;
-                                                }
-                                                if let Role::PlaceBet = self.players[last_player_ID].role {
-                                                    let players_clone = self.players.clone();
-                                                    value = players_clone[last_player_ID].bet_bank.get_values_of_bank();
-                                                    break;
-                                                }
-                                            }
-                                        }
-                                        // 根据上一者下注的资金来下注
-                                        let game_clone = self.clone();
-                                        match self.players[ID].place_a_bet_with_last_value(value,game_clone.get_max_bet_value(),game_clone.min_value_unit) {
-                                            Ok(T) => te_stack.push_str(&T),
-                                            Err(E) => te_stack.push_str(&E),
-                                        }
-                                    }
-                                    _ => {}
-                                }
-                            }
-                            return Ok(te_stack);
-                        },
-                        GameStatus::BetPlacing1 => {
-                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
-                        },
-                        GameStatus::BetPlacing2 => {
-                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
-                        },
-                        GameStatus::BetPlacing3 => {
-                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
-                        },
-                        GameStatus::BetPlacing4 => {
-                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
-                        },
-                        GameStatus::Balancing => {
-                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
-                        },
+                    continue;
+                }
+                if let Some(rest) = line.strip_prefix("Dealt to ") {
+                    let open = rest.find(" [").ok_or(format!("Fail to parse the hole cards \"{0}\"!", line))?;
+                    let cards = get_between(&rest[open..], "[", "]").unwrap_or("");
+                    let name = &rest[..open];
+                    let seat = history.seats.iter_mut().find(|seat| seat.name == name).ok_or(format!("Fail to find the player {0}!", name))?;
+                    seat.hole_cards = get_short_names(&cards.split_whitespace().collect())?;
+                    continue;
+                }
+                if let Some(rest) = line.strip_prefix("Uncalled bet ") {
+                    // 没人跟的部分退回去了，把最后一次加注减掉这部分；减完不再是加注的就改成跟注或过牌
+                    let value = parse_amount(get_between(rest, "(", ")").ok_or(format!("Fail to parse \"{0}\"!", line))?, cents)?;
+                    let name = rest.split(" returned to ").nth(1).ok_or(format!("Fail to parse \"{0}\"!", line))?;
+                    let (position, index, level_before, committed_before) = match last_raise {
+                        Some(T) if history.seats[T.1].name == name => T,
+                        _ => return Err(format!("Fail to find the bet of {0} returned in \"{1}\"!", name, line)),
+                    };
+                    let to = committed[index] - value;
+                    let entry = &mut history.actions[position];
+                    entry.action = if to > level_before {
+                        HistoryAction::Raise(to)
+                    } else if to > committed_before {
+                        HistoryAction::Call(to - committed_before)
+                    } else {
+                        HistoryAction::Check
+                    };
+                    entry.all_in = false;
+                    committed[index] = to;
+                    level = committed.iter().copied().max().unwrap_or(0);
+                    last_raise = None;
+                    continue;
+                }
+                if let Some(index) = history.seats.iter().position(|seat| line.starts_with(&format!("{0} collected ", seat.name))) {
+                    let name = history.seats[index].name.clone();
+                    let value = line[name.len() + 11..].split_whitespace().next().unwrap_or("");
+                    history.awards.push((name, parse_amount(value, cents)?));
+                    continue;
+                }
+                // 剩下的是“名字: 行动”，名字里可能有空格，所以按座位上的名字匹配
+                let index = match history.seats.iter().position(|seat| line.starts_with(&format!("{0}: ", seat.name))) {
+                    Some(T) => T,
+                    None => continue,
+                };
+                let name = history.seats[index].name.clone();
+                let action_text = &line[name.len() + 2..];
+                let all_in = action_text.ends_with("and is all-in");
+                let words: Vec<&str> = action_text.split_whitespace().collect();
+                let action = match words.as_slice() {
+                    ["posts", "the", "ante", value, ..] => HistoryAction::Ante(parse_amount(value, cents)?),
+                    ["posts", "straddle", value, ..] => HistoryAction::Straddle(parse_amount(value, cents)?),
+                    ["posts", "small", "blind", value, ..] => HistoryAction::SmallBlind(parse_amount(value, cents)?),
+                    ["posts", "big", "blind", value, ..] => HistoryAction::BigBlind(parse_amount(value, cents)?),
+                    ["folds", ..] => HistoryAction::Fold,
+                    ["checks", ..] => HistoryAction::Check,
+                    ["calls", value, ..] => HistoryAction::Call(parse_amount(value, cents)?),
+                    ["bets", value, ..] => HistoryAction::Raise(street_base + parse_amount(value, cents)?),
+                    ["raises", _, "to", value, ..] => HistoryAction::Raise(street_base + parse_amount(value, cents)?),
+                    ["shows", ..] => {
+                        let category = get_between(action_text, "(", ")").unwrap_or("").to_string();
+                        let cards = get_between(action_text, "[", "]").unwrap_or("");
+                        history.seats[index].hole_cards = get_short_names(&cards.split_whitespace().collect())?;
+                        history.showdown.push((name, category));
+                        continue;
                     }
-                },
-                MyEvent::PlaceABet { .. }|MyEvent::PlaceABet_Auto => {
-                    match game_status {
-                        GameStatus::Setting => {
-                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
-                        },
-                        GameStatus::CardsPicking => {
-                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
-                        },
-                        GameStatus::BetPlacing1|GameStatus::BetPlacing2|GameStatus::BetPlacing3|GameStatus::BetPlacing4 => {
-                            match game_status {
-                                GameStatus::BetPlacing1 => self.game_status = GameStatus::BetPlacing2,
-                                GameStatus::BetPlacing2 => self.game_status = GameStatus::BetPlacing3,
-                                GameStatus::BetPlacing3 => self.game_status = GameStatus::BetPlacing4,
-                                GameStatus::BetPlacing4 => self.game_status = GameStatus::Balancing,
-                                _ => {}
-                            }
+                    ["posts", ..] => return Err(format!("Fail to replay \"{0}\" because this kind of forced bet is not supported!", line)),
+                    _ => continue,
+                };
+                match action {
+                    HistoryAction::SmallBlind(value) | HistoryAction::BigBlind(value) | HistoryAction::Straddle(value) | HistoryAction::Call(value) => committed[index] += value,
+                    HistoryAction::Raise(to) => {
+                        last_raise = Some((history.actions.len(), index, level, committed[index]));
+                        committed[index] = to;
+                    }
+                    _ => {}
+                }
+                level = level.max(committed[index]);
+                history.record(&name, street, action, all_in);
+            }
+            if history.seats.is_empty() {
+                return Err("Fail to find any seat in the hand history!".to_string());
+            }
+            // 总结里的抽水和没有亮出来的底牌
+            for line in lines {
+                if line.starts_with("Total pot") {
+                    if let Some(rake) = line.split("| Rake ").nth(1) {
+                        history.rake = parse_amount(rake.split_whitespace().next().unwrap_or(""), cents)?;
+                    }
+                    continue;
+                }
+                if let Some(cards) = line.split(" mucked [").nth(1) {
+                    let number: usize = line.strip_prefix("Seat ").and_then(|rest| rest.split(':').next()).and_then(|number| number.parse().ok()).unwrap_or(0);
+                    if let Some(seat) = history.seats.iter_mut().find(|seat| seat.seat + 1 == number) {
+                        seat.hole_cards = get_short_names(&cards.trim_end_matches(']').split_whitespace().collect())?;
+                    }
+                }
+            }
+            history.button = button_number.max(1) - 1;
+            Ok(history)
+        }
 
-                            let player_num = self.players.len();
-                            let mut te_stack = String::new();
-                            let ID = 0;
+        fn get_hole_cards(&self, name: &str) -> String {
+            match self.seats.iter().find(|seat| seat.name == name) {
+                Some(seat) => seat.hole_cards.join(" "),
+                None => String::new(),
+            }
+        }
 
-                            let players_clone = self.players.clone();
-                            let bet_bank_backup = players_clone[ID].bet_bank.get_basket_vec();
-                            if let MyEvent::PlaceABet {bet} = event {
-                                // 我自己下注，有成功和失败的可能
-                                match self.players[ID].role {
-                                    Role::XiaoMang(value) => {
-                                        // 下指定的注
+        fn get_position_name(&self, seat: &SeatInfo) -> &'static str {
+            let mut name = if seat.seat == self.button { " (button)" } else { "" };
+            for entry in self.actions.iter().filter(|entry| entry.name == seat.name) {
+                match entry.action {
+                    HistoryAction::SmallBlind(..) => name = " (small blind)",
+                    HistoryAction::BigBlind(..) => name = " (big blind)",
+                    _ => {}
+                }
+            }
+            name
+        }
+
+        fn get_result(&self, name: &str) -> String {
+            let won = self.awards.iter().find(|award| award.0 == name).map(|award| award.1);
+            let shown = self.showdown.iter().find(|item| item.0 == name);
+            match (shown, won) {
+                (Some((_, category)), Some(value)) => format!("showed [{0}] and won ({1}) with {2}", self.get_hole_cards(name), value, category),
+                (Some((_, category)), None) => format!("showed [{0}] and lost with {1}", self.get_hole_cards(name), category),
+                (None, Some(value)) => format!("collected ({0})", value),
+                (None, None) => match self.get_fold_street(name) {
+                    Some(0) => "folded before Flop".to_string(),
+                    Some(street) => format!("folded on the {0}", STREET_NAMES[street]),
+                    None if self.get_hole_cards(name).is_empty() => "mucked".to_string(),
+                    None => format!("mucked [{0}]", self.get_hole_cards(name)),
+                },
+            }
+        }
+    }
+}
+
+///游戏引擎发出的结构化事件，界面、日志、牌局记录和统计可以订阅，不用再解析返回的字符串
+mod module_event {
+    use std::sync::mpsc::Sender;
+    use serde::Serialize;
+    use crate::module_history::HistoryAction;
+
+    /// 引擎事件，牌都用“As”“Td”这样的简写
+    #[derive(Clone, Debug, PartialEq, Serialize)]
+    #[serde(tag = "type")]
+    pub enum GameEvent {
+        /// 新的一手开始，seats是(座位, 名字, 筹码)
+        HandStarted { hand_id: u64, button: usize, seats: Vec<(usize, String, i32)> },
+        BlindPosted { name: String, value: i32, is_big: bool },
+        AntePosted { name: String, value: i32 },
+        StraddlePosted { name: String, value: i32 },
+        /// 发了底牌，所有人都能看到，牌本身不公开
+        CardsDealt { name: String },
+        /// 某个玩家自己的底牌，只发给以这个玩家身份订阅的人
+        HoleCards { name: String, cards: Vec<String> },
+        PlayerActed { name: String, street: usize, action: HistoryAction, all_in: bool },
+        /// 进入新的一轮，board是这一轮能看到的公共牌
+        StreetAdvanced { street: usize, board: Vec<String> },
+        Showdown { name: String, cards: Vec<String>, category: String },
+        PotAwarded { name: String, value: i32 },
+        PlayerBusted { name: String },
+        /// 现金局有人坐到座位上
+        PlayerSeated { name: String, seat: usize },
+        /// 从收银台买入、重新买入或补码
+        ChipsBought { name: String, value: i32 },
+        PlayerSatOut { name: String },
+        PlayerSatIn { name: String },
+        /// 坐出去的玩家交的死盲注，直接进钱池
+        DeadBlindPosted { name: String, value: i32 },
+        /// 派奖前从钱池里抽的水
+        RakeTaken { value: i32 },
+        /// 筹码还给收银台离开座位
+        PlayerCashedOut { name: String, value: i32 },
+        /// 锦标赛拿掉最小面额的筹码，removed是交出去的，added是换回来的
+        ChipsColoredUp { name: String, removed: i32, added: i32 },
+        /// 锦标赛升盲，level从1开始
+        BlindLevelChanged { level: usize, small_blind: i32, big_blind: i32, ante: i32 },
+    }
+
+    impl GameEvent {
+        /// 只能给某个玩家看的事件，返回这个玩家的名字
+        pub fn get_recipient(&self) -> Option<&str> {
+            match self {
+                GameEvent::HoleCards { name, .. } => Some(name),
+                _ => None,
+            }
+        }
+    }
+
+    /// 事件订阅者特性
+    pub trait GameObserver {
+        fn on_event(&mut self, event: &GameEvent);
+    }
+
+    /// 通过通道把事件发给别的线程，接收端关掉后的事件直接丢掉
+    impl GameObserver for Sender<GameEvent> {
+        fn on_event(&mut self, event: &GameEvent) {
+            let _ = self.send(event.clone());
+        }
+    }
+
+    /// 把事件存起来的订阅者
+    #[derive(Default)]
+    pub struct EventLog {
+        pub events: Vec<GameEvent>,
+    }
+
+    impl GameObserver for EventLog {
+        fn on_event(&mut self, event: &GameEvent) {
+            self.events.push(event.clone());
+        }
+    }
+}
+
+///牌桌规则配置：盲注、前注、下注结构、座位数、起始筹码、行动时限和玩法，可以从TOML文件读取，建Game时检查
+mod module_config {
+    use std::fs;
+    use std::time::Duration;
+    use serde::{Deserialize, Serialize};
+    use crate::module_bank::{ChipSet, StuffType};
+    use crate::module_tournament::BlindSchedule;
+
+    /// 最多座位数
+    pub const MAX_SEAT_NUM: usize = 10;
+
+    /// 玩法
+    #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub enum Variant {
+        TexasHoldem,
+    }
+
+    /// 下注结构
+    #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub enum BettingStructure {
+        NoLimit,
+        PotLimit,
+        FixedLimit,
+    }
+
+    /// 可以自愿抓头的位置，抓头下两倍大盲
+    #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub enum Straddle {
+        /// 大盲下一位
+        UnderTheGun,
+        Button,
+    }
+
+    /// 现金局的抽水：底池抽percent%，按筹码面值往下取整，不超过cap，也不超过max_per_hand
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(default)]
+    pub struct RakeRule {
+        pub percent: f64,
+        pub cap: Option<i32>,
+        pub max_per_hand: Option<i32>,
+        /// 没发翻牌的手不抽水
+        pub no_flop_no_drop: bool,
+    }
+
+    impl Default for RakeRule {
+        fn default() -> RakeRule {
+            RakeRule {
+                percent: 0.0,
+                cap: None,
+                max_per_hand: None,
+                no_flop_no_drop: true,
+            }
+        }
+    }
+
+    impl RakeRule {
+        pub fn new(percent: f64, cap: Option<i32>, max_per_hand: Option<i32>, no_flop_no_drop: bool) -> RakeRule {
+            RakeRule { percent, cap, max_per_hand, no_flop_no_drop }
+        }
+        pub fn check(&self, chip_value: i32) -> Result<(), String> {
+            if !(self.percent >= 0.0 && self.percent <= 100.0) {
+                return Err(format!("The rake percent {0} must be between 0 and 100!", self.percent));
+            }
+            if let Some(cap) = self.cap {
+                check_value("rake cap", cap, chip_value)?;
+            }
+            if let Some(max_per_hand) = self.max_per_hand {
+                check_value("max rake per hand", max_per_hand, chip_value)?;
+            }
+            Ok(())
+        }
+        /// 一手牌的抽水，引擎每手只结算一个底池，没有边池
+        pub fn get_rake(&self, pot: i32, saw_flop: bool, chip_value: i32) -> i32 {
+            if self.no_flop_no_drop && !saw_flop {
+                return 0;
+            }
+            let mut rake = (pot as f64 * self.percent / 100.0).floor() as i32;
+            rake -= rake % chip_value.max(1);
+            rake.min(self.cap.unwrap_or(i32::MAX)).min(self.max_per_hand.unwrap_or(i32::MAX)).min(pot)
+        }
+    }
+
+    /// 牌桌规则，没给盲注和最大下注时按筹码总额和人数算
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(default)]
+    pub struct GameConfig {
+        pub variant: Variant,
+        pub betting_structure: BettingStructure,
+        pub max_players: usize,
+        /// 筹码面值，也是最小下注单位
+        pub chip_value: i32,
+        /// 每人的起始筹码，给了就在建Game时发好筹码
+        pub starting_stack: Option<i32>,
+        pub small_blind: Option<i32>,
+        pub big_blind: Option<i32>,
+        /// 每人的前注
+        pub ante: i32,
+        /// 大盲前注：由大盲一个人替全桌下，金额就是ante
+        pub big_blind_ante: bool,
+        /// 允许抓头的位置，空的就是不许抓头
+        pub straddles: Vec<Straddle>,
+        pub max_bet: Option<i32>,
+        /// 每次行动的时限，外部机器人超过时限按默认行动处理
+        pub action_timeout_ms: Option<u64>,
+        /// 练习模式可以悔棋
+        pub practice_mode: bool,
+        /// 锦标赛的盲注级别表，给了就是锦标赛模式，盲注和前注按级别走
+        pub blind_schedule: Option<BlindSchedule>,
+        /// 现金局入座和重新买入的最少筹码，不给就是一个大盲
+        pub min_buy_in: Option<i32>,
+        /// 现金局买入和补码的上限，不给就不能补码
+        pub max_buy_in: Option<i32>,
+        /// 现金局的抽水，不给就不抽水
+        pub rake: Option<RakeRule>,
+        /// 筹码的面值，最小的就是chip_value；给了就按面值发筹码，庄家的筹码架负责换零，锦标赛自动进位
+        pub chip_set: Option<ChipSet>,
+    }
+
+    impl Default for GameConfig {
+        fn default() -> GameConfig {
+            GameConfig {
+                variant: Variant::TexasHoldem,
+                betting_structure: BettingStructure::NoLimit,
+                max_players: MAX_SEAT_NUM,
+                chip_value: 5,
+                starting_stack: None,
+                small_blind: None,
+                big_blind: None,
+                ante: 0,
+                big_blind_ante: false,
+                straddles: Vec::new(),
+                max_bet: None,
+                action_timeout_ms: None,
+                practice_mode: false,
+                blind_schedule: None,
+                min_buy_in: None,
+                max_buy_in: None,
+                rake: None,
+                chip_set: None,
+            }
+        }
+    }
+
+    /// 检查金额是正的筹码面值的整数倍
+    fn check_value(name: &str, value: i32, chip_value: i32) -> Result<(), String> {
+        if value <= 0 || value % chip_value != 0 {
+            return Err(format!("The {0} {1} must be a positive multiple of the chip value {2}!", name, value, chip_value));
+        }
+        Ok(())
+    }
+
+    impl GameConfig {
+        pub fn builder() -> GameConfigBuilder {
+            GameConfigBuilder { config: GameConfig::default() }
+        }
+        /// 检查规则是否合理
+        pub fn check(&self) -> Result<(), String> {
+            if self.max_players < 2 || self.max_players > MAX_SEAT_NUM {
+                return Err(format!("The seat count {0} must be between 2 and {1}!", self.max_players, MAX_SEAT_NUM));
+            }
+            if self.chip_value <= 0 {
+                return Err(format!("The chip value {0} must be positive!", self.chip_value));
+            }
+            match (self.small_blind, self.big_blind) {
+                (Some(small_blind), Some(big_blind)) => {
+                    check_value("small blind", small_blind, self.chip_value)?;
+                    check_value("big blind", big_blind, self.chip_value)?;
+                    if big_blind < small_blind {
+                        return Err(format!("The big blind {0} is less than the small blind {1}!", big_blind, small_blind));
+                    }
+                }
+                (None, None) => {
+                    if self.betting_structure == BettingStructure::FixedLimit && self.blind_schedule.is_none() {
+                        return Err("Fixed-limit games need explicit blinds!".to_string());
+                    }
+                }
+                _ => return Err("The small blind and the big blind must be given together!".to_string()),
+            }
+            if self.ante != 0 {
+                check_value("ante", self.ante, self.chip_value)?;
+            }
+            if self.big_blind_ante && self.ante == 0 {
+                return Err("The big blind ante needs a positive ante!".to_string());
+            }
+            if let Some(starting_stack) = self.starting_stack {
+                check_value("starting stack", starting_stack, self.chip_value)?;
+                if starting_stack < self.big_blind.unwrap_or(0) + self.ante {
+                    return Err(format!("The starting stack {0} cannot cover the big blind and the ante!", starting_stack));
+                }
+            }
+            if let Some(max_bet) = self.max_bet {
+                check_value("max bet", max_bet, self.chip_value)?;
+                if max_bet < self.big_blind.unwrap_or(0) {
+                    return Err(format!("The max bet {0} is less than the big blind!", max_bet));
+                }
+            }
+            if self.action_timeout_ms == Some(0) {
+                return Err("The action time limit must be positive!".to_string());
+            }
+            if let Some(schedule) = &self.blind_schedule {
+                schedule.check(self.chip_value)?;
+            }
+            if let Some(min_buy_in) = self.min_buy_in {
+                check_value("min buy-in", min_buy_in, self.chip_value)?;
+                if min_buy_in < self.big_blind.unwrap_or(0) + self.ante {
+                    return Err(format!("The min buy-in {0} cannot cover the big blind and the ante!", min_buy_in));
+                }
+            }
+            if let Some(max_buy_in) = self.max_buy_in {
+                check_value("max buy-in", max_buy_in, self.chip_value)?;
+                if max_buy_in < self.min_buy_in.unwrap_or(0) {
+                    return Err(format!("The max buy-in {0} is less than the min buy-in!", max_buy_in));
+                }
+            }
+            if let Some(rake) = &self.rake {
+                if self.blind_schedule.is_some() {
+                    return Err("Tournaments take no rake from the pots!".to_string());
+                }
+                rake.check(self.chip_value)?;
+            }
+            if let Some(chip_set) = &self.chip_set {
+                chip_set.check()?;
+                if chip_set.denominations[0].value != self.chip_value {
+                    return Err(format!("The smallest chip {0} must be the chip value {1}!", chip_set.denominations[0].value, self.chip_value));
+                }
+            }
+            Ok(())
+        }
+        /// 起始筹码对应的资产
+        pub fn get_starting_assets(&self) -> Option<Vec<(StuffType, i32)>> {
+            if let (Some(chip_set), Some(stack)) = (&self.chip_set, self.starting_stack) {
+                return chip_set.get_chips(stack, 0).ok();
+            }
+            self.starting_stack.map(|stack| vec![(StuffType::OwnedType("筹码".to_string(), self.chip_value), stack / self.chip_value)])
+        }
+        pub fn get_action_timeout(&self) -> Option<Duration> {
+            self.action_timeout_ms.map(Duration::from_millis)
+        }
+        /// 从TOML文本中读取规则
+        pub fn from_toml(text: &str) -> Result<GameConfig, String> {
+            let config: GameConfig = match toml::from_str(text) {
+                Ok(T) => T,
+                Err(E) => return Err(format!("Fail to parse the game config: {0}", E)),
+            };
+            config.check()?;
+            Ok(config)
+        }
+        /// 从TOML文件中读取规则
+        pub fn load(path: &str) -> Result<GameConfig, String> {
+            match fs::read_to_string(path) {
+                Ok(text) => GameConfig::from_toml(&text),
+                Err(E) => Err(format!("Fail to read the game config from \"{0}\": {1}", path, E)),
+            }
+        }
+    }
+
+    /// 规则构造器，build时检查
+    pub struct GameConfigBuilder {
+        config: GameConfig,
+    }
+
+    impl GameConfigBuilder {
+        pub fn variant(mut self, variant: Variant) -> GameConfigBuilder {
+            self.config.variant = variant;
+            self
+        }
+        pub fn betting_structure(mut self, betting_structure: BettingStructure) -> GameConfigBuilder {
+            self.config.betting_structure = betting_structure;
+            self
+        }
+        pub fn max_players(mut self, max_players: usize) -> GameConfigBuilder {
+            self.config.max_players = max_players;
+            self
+        }
+        pub fn chip_value(mut self, chip_value: i32) -> GameConfigBuilder {
+            self.config.chip_value = chip_value;
+            self
+        }
+        pub fn starting_stack(mut self, starting_stack: i32) -> GameConfigBuilder {
+            self.config.starting_stack = Some(starting_stack);
+            self
+        }
+        pub fn blinds(mut self, small_blind: i32, big_blind: i32) -> GameConfigBuilder {
+            self.config.small_blind = Some(small_blind);
+            self.config.big_blind = Some(big_blind);
+            self
+        }
+        pub fn ante(mut self, ante: i32) -> GameConfigBuilder {
+            self.config.ante = ante;
+            self
+        }
+        pub fn big_blind_ante(mut self, big_blind_ante: bool) -> GameConfigBuilder {
+            self.config.big_blind_ante = big_blind_ante;
+            self
+        }
+        pub fn straddles(mut self, straddles: Vec<Straddle>) -> GameConfigBuilder {
+            self.config.straddles = straddles;
+            self
+        }
+        pub fn max_bet(mut self, max_bet: i32) -> GameConfigBuilder {
+            self.config.max_bet = Some(max_bet);
+            self
+        }
+        pub fn action_timeout_ms(mut self, action_timeout_ms: u64) -> GameConfigBuilder {
+            self.config.action_timeout_ms = Some(action_timeout_ms);
+            self
+        }
+        pub fn practice_mode(mut self, practice_mode: bool) -> GameConfigBuilder {
+            self.config.practice_mode = practice_mode;
+            self
+        }
+        pub fn blind_schedule(mut self, blind_schedule: BlindSchedule) -> GameConfigBuilder {
+            self.config.blind_schedule = Some(blind_schedule);
+            self
+        }
+        pub fn buy_in(mut self, min_buy_in: i32, max_buy_in: i32) -> GameConfigBuilder {
+            self.config.min_buy_in = Some(min_buy_in);
+            self.config.max_buy_in = Some(max_buy_in);
+            self
+        }
+        pub fn rake(mut self, rake: RakeRule) -> GameConfigBuilder {
+            self.config.rake = Some(rake);
+            self
+        }
+        /// 同时把chip_value设成最小的面值
+        pub fn chip_set(mut self, chip_set: ChipSet) -> GameConfigBuilder {
+            if let Some(denomination) = chip_set.denominations.first() {
+                self.config.chip_value = denomination.value;
+            }
+            self.config.chip_set = Some(chip_set);
+            self
+        }
+        pub fn build(self) -> Result<GameConfig, String> {
+            self.config.check()?;
+            Ok(self.config)
+        }
+    }
+}
+
+///锦标赛的盲注级别表：每一级的大小盲、前注和持续的手数或分钟数，靠可以替换的时钟在两手之间自动升级
+mod module_tournament {
+    use std::cell::Cell;
+    use std::fs;
+    use std::rc::Rc;
+    use std::time::{SystemTime, UNIX_EPOCH};
+    use serde::{Deserialize, Serialize};
+
+    /// 时钟特性，测试里用MockClock手动拨时间
+    pub trait Clock {
+        /// 当前时间，毫秒
+        fn now_ms(&self) -> u64;
+    }
+
+    /// 系统时钟
+    #[derive(Clone, Copy, Default)]
+    pub struct SystemClock;
+
+    impl Clock for SystemClock {
+        fn now_ms(&self) -> u64 {
+            match SystemTime::now().duration_since(UNIX_EPOCH) {
+                Ok(T) => T.as_millis() as u64,
+                Err(_) => 0,
+            }
+        }
+    }
+
+    /// 手动拨动的时钟，克隆出来的共用同一个时间
+    #[derive(Clone, Default)]
+    pub struct MockClock {
+        now_ms: Rc<Cell<u64>>,
+    }
+
+    impl MockClock {
+        pub fn new() -> MockClock {
+            MockClock::default()
+        }
+        pub fn advance_ms(&self, ms: u64) {
+            self.now_ms.set(self.now_ms.get() + ms);
+        }
+        pub fn advance_minutes(&self, minutes: u64) {
+            self.advance_ms(minutes * 60_000);
+        }
+    }
+
+    impl Clock for MockClock {
+        fn now_ms(&self) -> u64 {
+            self.now_ms.get()
+        }
+    }
+
+    /// 一个级别持续多久
+    #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub enum LevelDuration {
+        Hands(u32),
+        Minutes(u32),
+    }
+
+    /// 一个盲注级别
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    pub struct BlindLevel {
+        pub small_blind: i32,
+        pub big_blind: i32,
+        #[serde(default)]
+        pub ante: i32,
+        #[serde(flatten)]
+        pub duration: LevelDuration,
+    }
+
+    impl BlindLevel {
+        pub fn new(small_blind: i32, big_blind: i32, ante: i32, duration: LevelDuration) -> BlindLevel {
+            BlindLevel { small_blind, big_blind, ante, duration }
+        }
+    }
+
+    /// 盲注级别表，最后一级打完后一直停在最后一级
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    pub struct BlindSchedule {
+        pub levels: Vec<BlindLevel>,
+    }
+
+    impl BlindSchedule {
+        pub fn new(levels: Vec<BlindLevel>) -> BlindSchedule {
+            BlindSchedule { levels }
+        }
+        /// 检查每一级的金额都是筹码面值的整数倍，并且持续时间不为零
+        pub fn check(&self, chip_value: i32) -> Result<(), String> {
+            if self.levels.is_empty() {
+                return Err("The blind schedule has no level!".to_string());
+            }
+            for (index, level) in self.levels.iter().enumerate() {
+                if level.small_blind <= 0 || level.big_blind < level.small_blind || level.ante < 0 {
+                    return Err(format!("The blinds {0}/{1} with ante {2} of level {3} are not valid!", level.small_blind, level.big_blind, level.ante, index + 1));
+                }
+                if level.small_blind % chip_value != 0 || level.big_blind % chip_value != 0 || level.ante % chip_value != 0 {
+                    return Err(format!("The blinds of level {0} must be multiples of the chip value {1}!", index + 1, chip_value));
+                }
+                if let LevelDuration::Hands(0) | LevelDuration::Minutes(0) = level.duration {
+                    return Err(format!("The level {0} must last at least one hand or one minute!", index + 1));
+                }
+            }
+            Ok(())
+        }
+        /// 第index级，超过最后一级时是最后一级
+        pub fn get_level(&self, index: usize) -> &BlindLevel {
+            &self.levels[index.min(self.levels.len() - 1)]
+        }
+        /// 从TOML文本中读取级别表
+        pub fn from_toml(text: &str) -> Result<BlindSchedule, String> {
+            match toml::from_str(text) {
+                Ok(T) => Ok(T),
+                Err(E) => Err(format!("Fail to parse the blind schedule: {0}", E)),
+            }
+        }
+        /// 从TOML文件中读取级别表
+        pub fn load(path: &str) -> Result<BlindSchedule, String> {
+            match fs::read_to_string(path) {
+                Ok(text) => BlindSchedule::from_toml(&text),
+                Err(E) => Err(format!("Fail to read the blind schedule from \"{0}\": {1}", path, E)),
+            }
+        }
+    }
+
+    fn get_system_clock() -> Rc<dyn Clock> {
+        Rc::new(SystemClock)
+    }
+
+    /// 按级别表走的盲注计时器，时钟不存档，读档后用系统时钟
+    #[derive(Clone, Serialize, Deserialize)]
+    pub struct BlindTimer {
+        pub schedule: BlindSchedule,
+        /// 当前级别，从0开始
+        pub level: usize,
+        /// 这一级已经打完的手数
+        pub hands_in_level: u32,
+        /// 这一级开始的时间，第一手开始前是None
+        pub level_started_ms: Option<u64>,
+        #[serde(skip, default = "get_system_clock")]
+        clock: Rc<dyn Clock>,
+    }
+
+    impl BlindTimer {
+        pub fn new(schedule: BlindSchedule, clock: Rc<dyn Clock>) -> BlindTimer {
+            BlindTimer { schedule, level: 0, hands_in_level: 0, level_started_ms: None, clock }
+        }
+        pub fn set_clock(&mut self, clock: Rc<dyn Clock>) {
+            self.clock = clock;
+        }
+        pub fn get_level(&self) -> &BlindLevel {
+            self.schedule.get_level(self.level)
+        }
+        /// 是不是已经到了最后一级
+        pub fn is_last_level(&self) -> bool {
+            self.level + 1 >= self.schedule.levels.len()
+        }
+        /// 每一手开始前调用：第一手开始计时，之后记上打完的一手，到时间或手数就升级，返回是否升级
+        pub fn on_new_hand(&mut self) -> bool {
+            let now = self.clock.now_ms();
+            let mut started = match self.level_started_ms {
+                Some(T) => T,
+                None => {
+                    self.level_started_ms = Some(now);
+                    return false;
+                }
+            };
+            self.hands_in_level += 1;
+            let level_before = self.level;
+            while !self.is_last_level() {
+                match self.get_level().duration {
+                    LevelDuration::Hands(hands) if self.hands_in_level >= hands => {
+                        started = now;
+                    }
+                    // 按分钟的级别从上一级结束的时间算起，停得久时可能连升几级
+                    LevelDuration::Minutes(minutes) if now >= started + minutes as u64 * 60_000 => {
+                        started += minutes as u64 * 60_000;
+                    }
+                    _ => break,
+                }
+                self.level += 1;
+                self.hands_in_level = 0;
+            }
+            self.level_started_ms = Some(started);
+            self.level != level_before
+        }
+        /// 这一级还剩多少毫秒，按手数的级别返回None
+        pub fn get_time_left_ms(&self) -> Option<u64> {
+            match self.get_level().duration {
+                LevelDuration::Minutes(minutes) => {
+                    let started = self.level_started_ms.unwrap_or(self.clock.now_ms());
+                    Some((started + minutes as u64 * 60_000).saturating_sub(self.clock.now_ms()))
+                }
+                LevelDuration::Hands(_) => None,
+            }
+        }
+    }
+}
+
+
+mod module_game {
+    use std::cmp::{min, Ordering};
+    use std::collections::HashMap;
+    use crate::CardPool;
+    use crate::module_card::{FiveCards, FiveCardsCategory};
+    use crate::module_player::{CashPool, Cashier, House, Player, RakeReport, Role};
+    use crate::module_bank::*;
+    use crate::module_bot::{BotAction, BotProfile, DecisionContext, Strategy};
+    use crate::module_stats::{ActionType, OpponentStats};
+    use crate::module_history::{get_category_name, HandHistory, HistoryAction, HistoryEntry, SeatInfo};
+    use crate::module_event::{GameEvent, GameObserver};
+    use crate::module_config::{BettingStructure, GameConfig, Straddle};
+    use crate::module_tournament::{BlindSchedule, BlindTimer, Clock, SystemClock};
+    use crate::module_protocol::{ExternalStrategy, DEFAULT_ACTION_TIMEOUT_MS};
+    use serde::{Deserialize, Serialize};
+    use std::sync::mpsc;
+    use std::sync::mpsc::Receiver;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::time::Duration;
+    use std::fmt;
+    use std::fmt::{Display, format};
+    use rand::prelude::*;
+
+    #[derive(Clone)]
+    #[derive(Serialize, Deserialize)]
+    pub struct Game {
+        pub players: Vec<Player>,
+        pub cash_pool: CashPool,
+        pub card_pool: CardPool,
+        pub five_cards: FiveCards,
+        pub game_status: GameStatus,
+        pub last_XiaoMang_ID: i32,
+        /// 庄家的座位，死庄时是已经出局的座位
+        #[serde(default)]
+        pub button_ID: i32,
+        /// 上一手大盲的座位，轮换盲注时大盲从这里往下走
+        #[serde(default)]
+        pub last_DaMang_ID: i32,
+        /// 这一手说好要抓头的座位，发牌时下
+        #[serde(default)]
+        pub straddle_ID: Option<usize>,
+        /// 这一手已经走到的轮次，0翻前，1翻牌，2转牌，3河牌；全下后直接摊牌也算发到了河牌
+        #[serde(default)]
+        pub street: usize,
+        pub min_value_unit: i32,
+        pub opponent_stats: OpponentStats,
+        /// 按玩家名字挂上的策略，没有挂策略的机器人按性格随机下注；策略不存档，读档后要重新挂上
+        #[serde(skip)]
+        pub strategies: HashMap<String, Rc<RefCell<dyn Strategy>>>,
+        /// 已经开始的每一手牌的记录
+        pub histories: Vec<HandHistory>,
+        /// 练习模式才能悔棋，正式游戏里关掉
+        pub practice_mode: bool,
+        /// 最多能悔几步
+        pub undo_limit: usize,
+        /// 每个事件之前的状态快照，不存档
+        #[serde(skip)]
+        undo_stack: Vec<GameSnapshot>,
+        /// 事件订阅者和他们以谁的身份订阅，不存档
+        #[serde(skip)]
+        observers: Vec<(Option<String>, Rc<RefCell<dyn GameObserver>>)>,
+        /// 牌桌规则
+        #[serde(default)]
+        pub config: GameConfig,
+        /// 锦标赛的盲注计时器，有了它盲注和前注就按级别走
+        #[serde(default)]
+        pub blind_timer: Option<BlindTimer>,
+        /// 锦标赛里还没发出去的奖金，从第一名往下排，由锦标赛在两手之间更新，机器人按它算ICM
+        #[serde(default)]
+        pub prizes: Vec<i32>,
+        /// 现金局的收银台
+        #[serde(default)]
+        pub cashier: Cashier,
+        /// 抽水进庄家
+        #[serde(default)]
+        pub house: House,
+        /// 庄家的筹码架，给玩家换零和进位
+        #[serde(default)]
+        pub chip_bank: MyBank,
+        /// 锦标赛里已经进位拿掉的最小面额种数
+        #[serde(default)]
+        pub colored_up: usize,
+    }
+
+    /// 悔棋用的状态快照：银行、角色、钱池、卡组、发出去的牌和统计
+    #[derive(Clone)]
+    struct GameSnapshot {
+        players: Vec<Player>,
+        cash_pool: CashPool,
+        card_pool: CardPool,
+        five_cards: FiveCards,
+        game_status: GameStatus,
+        last_XiaoMang_ID: i32,
+        button_ID: i32,
+        last_DaMang_ID: i32,
+        straddle_ID: Option<usize>,
+        street: usize,
+        min_value_unit: i32,
+        opponent_stats: OpponentStats,
+        histories: Vec<HandHistory>,
+        blind_timer: Option<BlindTimer>,
+        cashier: Cashier,
+        house: House,
+        chip_bank: MyBank,
+        colored_up: usize,
+    }
+
+    pub enum MyEvent<'a> {
+        AddBot { num: usize, profiles: Option<&'a Vec<BotProfile>> },
+        ResetGame { assets: &'a Vec<(&'a StuffType, i32)> },
+        StartNextGame,
+        PickCards,
+        PlaceABet { bet: &'a Vec<(&'a StuffType, i32)> },
+        PlaceABet_Auto,
+        ConfirmBalance,
+        GiveUp,
+        /// 练习模式下撤销最近的steps个事件
+        Undo { steps: usize },
+        /// 发牌前某个座位自愿抓头
+        Straddle { seat: usize },
+        /// 现金局两手之间从收银台买入筹码坐到空座位上
+        JoinSeat { name: &'a str, buy_in: i32 },
+        /// 坐出去不打牌，post_blinds时盲注轮到自己照样交死钱，否则坐回来时补上错过的盲注
+        SitOut { seat: usize, post_blinds: bool },
+        SitIn { seat: usize },
+        /// 输光后从收银台重新买入
+        Rebuy { seat: usize, value: i32 },
+        /// 从收银台补码到最多买入
+        TopUp { seat: usize },
+        /// 筹码全部还给收银台，离开座位
+        CashOut { seat: usize },
+    }
+
+    #[derive(Clone)]
+    #[derive(Serialize, Deserialize)]
+    pub enum GameStatus {
+        Setting,
+        CardsPicking,
+        BetPlacing1,
+        BetPlacing2,
+        BetPlacing3,
+        BetPlacing4,
+        Balancing,
+    }
+
+    const MAX_PLAYER_NUM: usize = 10;
+    /// 空座位上占位玩家的名字前缀
+    pub const EMPTY_SEAT_NAME: &str = "空座";
+    const PLAYER_NAME: [&str; MAX_PLAYER_NUM] = ["ME", "Alice", "Bob", "Cara", "David", "Ederson", "Ford", "Gavin", "Harry", "Ian"];
+
+    impl Game {
+        pub fn new() -> Game {
+            let mut game = Game {
+                players: Vec::new(),
+                cash_pool: CashPool::new(),
+                card_pool: CardPool::new(),
+                five_cards: FiveCards::new(),
+                game_status: GameStatus::Setting,
+                last_XiaoMang_ID: 0,
+                button_ID: 0,
+                last_DaMang_ID: 0,
+                straddle_ID: None,
+                street: 0,
+                min_value_unit: 1,
+                opponent_stats: OpponentStats::new(),
+                strategies: HashMap::new(),
+                histories: Vec::new(),
+                practice_mode: false,
+                undo_limit: 50,
+                undo_stack: Vec::new(),
+                observers: Vec::new(),
+                config: GameConfig::default(),
+                blind_timer: None,
+                prizes: Vec::new(),
+                cashier: Cashier::new(),
+                house: House::new(),
+                chip_bank: MyBank::new(),
+                colored_up: 0,
+            };
+            match game.add_a_player("ME") {
+                Ok(T) => println!("{}", T),
+                Err(E) => println!("{}", E),
+            }
+            return game;
+        }
+        /// 只有机器人的游戏，不添加ME也不打印，用于无界面地批量运行
+        pub fn new_with_players(names: &Vec<String>) -> Result<Game, String> {
+            Game::new_with_config(GameConfig::default(), names)
+        }
+        /// 按规则建一个只有机器人的游戏，规则里给了起始筹码时直接发好筹码
+        pub fn new_with_config(config: GameConfig, names: &Vec<String>) -> Result<Game, String> {
+            config.check()?;
+            let mut game = Game {
+                players: Vec::new(),
+                cash_pool: CashPool::new(),
+                card_pool: CardPool::new(),
+                five_cards: FiveCards::new(),
+                game_status: GameStatus::Setting,
+                last_XiaoMang_ID: 0,
+                button_ID: 0,
+                last_DaMang_ID: 0,
+                straddle_ID: None,
+                street: 0,
+                min_value_unit: 1,
+                opponent_stats: OpponentStats::new(),
+                strategies: HashMap::new(),
+                histories: Vec::new(),
+                practice_mode: config.practice_mode,
+                undo_limit: 50,
+                undo_stack: Vec::new(),
+                observers: Vec::new(),
+                blind_timer: None,
+                prizes: Vec::new(),
+                cashier: Cashier::new(),
+                house: House::new(),
+                chip_bank: MyBank::new(),
+                colored_up: 0,
+                config,
+            };
+            if let Some(schedule) = game.config.blind_schedule.clone() {
+                game.blind_timer = Some(BlindTimer::new(schedule, Rc::new(SystemClock)));
+            }
+            for name in names.iter() {
+                game.add_a_player(name)?;
+            }
+            if let Some(assets) = game.config.get_starting_assets() {
+                let assets: Vec<(&StuffType, i32)> = assets.iter().map(|item| (&item.0, item.1)).collect();
+                game.init_game(&assets)?;
+            }
+            // 筹码架上每种面额都备够坐满时的筹码总额：有起始筹码按起始筹码算，从收银台入座的按最大买入算
+            if game.config.chip_set.is_some() {
+                if let Some(stack) = game.config.starting_stack.or(game.config.max_buy_in) {
+                    game.stock_chip_bank(stack * game.config.max_players as i32)?;
+                }
+            }
+            return Ok(game);
+        }
+        pub fn get_min_value_unit(&mut self) {
+            let mut value_unit = -1;
+            for player in self.players.iter() {
+                for item in player.owned_bank.get_basket_vec().iter() {
+                    if value_unit < 0 || item.0.get_value() < value_unit {
+                        value_unit = item.0.get_value();
+                    }
+                }
+                for item in player.bet_bank.get_basket_vec().iter() {
+                    if value_unit < 0 || item.0.get_value() < value_unit {
+                        value_unit = item.0.get_value();
+                    }
+                }
+            }
+            self.min_value_unit = value_unit;
+            // 有整套筹码时可以换零，最小单位就是还在用的最小面额
+            if let Some(chip_set) = &self.config.chip_set {
+                self.min_value_unit = chip_set.denominations[self.colored_up.min(chip_set.denominations.len() - 1)].value;
+            }
+        }
+        /// 规则里给了就按规则，否则根据玩家数量、总金额计算小盲金额
+        pub fn get_XiaoMang_value(&self) -> i32 {
+            if let Some(timer) = &self.blind_timer {
+                return timer.get_level().small_blind;
+            }
+            if let Some(value) = self.config.small_blind {
+                return value;
+            }
+            let mut player_num_left = 0;
+            for player in self.players.iter() {
+                if let Role::PlaceBet|Role::GiveUp|Role::Normal|Role::XiaoMang(..)|Role::DaMang(..) = player.role {
+                    player_num_left += 1;
+                }
+            }
+            let mut value_of_all = 0;
+            let value_unit = self.min_value_unit;
+            for player in self.players.iter() {
+                value_of_all += player.owned_bank.get_values_of_bank() + player.bet_bank.get_values_of_bank();
+            }
+            return if (value_of_all / player_num_left / 30) % value_unit != 0 {
+                value_of_all / player_num_left / 30 + (value_unit - (value_of_all / player_num_left / 30) % value_unit)
+            } else {
+                value_of_all / player_num_left / 30
+            }
+        }
+        /// 规则里给了就按规则，否则根据玩家数量、总金额计算大盲金额
+        pub fn get_DaMang_value(&self) -> i32 {
+            if let Some(timer) = &self.blind_timer {
+                return timer.get_level().big_blind;
+            }
+            if let Some(value) = self.config.big_blind {
+                return value;
+            }
+            let mut player_num_left = 0;
+            for player in self.players.iter() {
+                if let Role::PlaceBet|Role::GiveUp|Role::Normal|Role::XiaoMang(..)|Role::DaMang(..) = player.role {
+                    player_num_left += 1;
+                }
+            }
+            let mut value_of_all = 0;
+            let value_unit = self.min_value_unit;
+            for player in self.players.iter() {
+                value_of_all += player.owned_bank.get_values_of_bank() + player.bet_bank.get_values_of_bank();
+            }
+            return if (value_of_all / player_num_left / 30) % value_unit != 0 {
+                (value_of_all / player_num_left / 30 + (value_unit - (value_of_all / player_num_left / 30) % value_unit))*2
+            } else {
+                (value_of_all / player_num_left / 30)*2
+            }
+        }
+        /// 规则里给了就按规则，否则根据玩家数量、总金额计算最大下注金额
+        pub fn get_max_bet_value(&self) -> i32 {
+            if let Some(value) = self.config.max_bet {
+                return value;
+            }
+            let mut player_num_left = 0;
+            for player in self.players.iter() {
+                if let Role::PlaceBet|Role::GiveUp|Role::Normal|Role::XiaoMang(..)|Role::DaMang(..) = player.role {
+                    player_num_left += 1;
+                }
+            }
+            let mut value_of_all = 0;
+            let value_unit = self.min_value_unit;
+            for player in self.players.iter() {
+                value_of_all += player.owned_bank.get_values_of_bank() + player.bet_bank.get_values_of_bank();
+            }
+            return if (value_of_all / player_num_left / 3) % value_unit != 0 {
+                value_of_all/player_num_left/3 + (value_unit - (value_of_all/player_num_left/3)%value_unit)
+            } else {
+                value_of_all/player_num_left/3
+            }
+        }
+        /// 添加玩家
+        pub fn add_a_player(&mut self, name: &str) -> Result<String, String> {
+            return if self.players.len() < MAX_PLAYER_NUM.min(self.config.max_players) {
+                let mut player = Player::new(name);
+                self.players.push(player);
+                Ok(format!("Succeed to add a new player called \"{0}\"!", name))
+            } else {
+                Err(format!("Fail to add a new player because there are too many players!"))
+            }
+        }
+        /// 两手之间把一个玩家连同筹码和策略带离座位，座位留给出局的占位玩家
+        pub fn take_a_player(&mut self, ID: usize) -> Result<(Player, Option<Rc<RefCell<dyn Strategy>>>), String> {
+            if !self.is_between_hands() {
+                return Err(format!("Fail to take a player away while status of game is {0}!", self.game_status));
+            }
+            if ID >= self.players.len() || (matches!(self.players[ID].role, Role::Quit) && !self.players[ID].sitting_out) {
+                return Err(format!("Fail to take a player away because seat {0} is empty!", ID + 1));
+            }
+            let empty = Player::new_placeholder(&format!("{0}{1}", EMPTY_SEAT_NAME, ID + 1));
+            let player = std::mem::replace(&mut self.players[ID], empty);
+            let strategy = self.strategies.remove(&player.name);
+            Ok((player, strategy))
+        }
+        /// 两手之间让一个玩家坐到空座位上，没有空座位时加一个座位，返回座位号
+        pub fn seat_a_player(&mut self, mut player: Player, strategy: Option<Rc<RefCell<dyn Strategy>>>) -> Result<usize, String> {
+            if !self.is_between_hands() {
+                return Err(format!("Fail to seat \"{0}\" while status of game is {1}!", player.name, self.game_status));
+            }
+            if self.players.iter().any(|other| other.name == player.name) {
+                return Err(format!("Fail to seat \"{0}\" because the name is used!", player.name));
+            }
+            // 只坐占位玩家的座位，输光了还没买入或兑现的玩家座位还是他们的
+            let empty = self.players.iter().position(|other| other.is_placeholder);
+            let ID = match empty {
+                Some(T) => T,
+                None => {
+                    self.add_a_player(&player.name)?;
+                    self.players.len() - 1
+                }
+            };
+            player.role = Role::Normal;
+            if let Some(strategy) = strategy {
+                self.strategies.insert(player.name.clone(), strategy);
+            }
+            self.players[ID] = player;
+            self.get_min_value_unit();
+            Ok(ID)
+        }
+        /// 两手之间：还没开始，或者这一手已经结算完
+        pub fn is_between_hands(&self) -> bool {
+            match self.game_status {
+                GameStatus::Setting => true,
+                GameStatus::Balancing => self.five_cards.five_cards.get_basket_vec().len() == 0,
+                _ => false,
+            }
+        }
+        /// 现金局买入的范围，没给最少买入时是一个大盲
+        pub fn get_buy_in_range(&self) -> (i32, Option<i32>) {
+            let min_buy_in = match self.config.min_buy_in {
+                Some(T) => T,
+                None if self.get_player_num_left() > 0 => self.get_DaMang_value(),
+                None => 0,
+            };
+            (min_buy_in, self.config.max_buy_in)
+        }
+        fn check_buy_in(&self, value: i32) -> Result<(), String> {
+            let (min_buy_in, max_buy_in) = self.get_buy_in_range();
+            if value < min_buy_in.max(1) {
+                return Err(format!("The buy-in {0} is less than the min buy-in {1}!", value, min_buy_in));
+            }
+            if let Some(max_buy_in) = max_buy_in {
+                if value > max_buy_in {
+                    return Err(format!("The buy-in {0} is more than the max buy-in {1}!", value, max_buy_in));
+                }
+            }
+            Ok(())
+        }
+        /// 找到有人坐着的座位，空座位上的占位玩家不算
+        fn get_seated_player(&self, ID: usize) -> Result<&Player, String> {
+            match self.players.get(ID) {
+                Some(player) if !player.is_placeholder => Ok(player),
+                _ => Err(format!("Seat {0} is empty!", ID + 1)),
+            }
+        }
+        /// 从收银台给座位上的玩家买筹码，输光的玩家买了就回到场上
+        fn buy_chips(&mut self, ID: usize, value: i32) -> Result<String, String> {
+            let name = self.players[ID].name.clone();
+            let te_stack = self.cashier.sell(&name, value, &mut self.players[ID].owned_bank)?;
+            if !self.players[ID].sitting_out {
+                self.players[ID].role = Role::Normal;
+            }
+            self.get_min_value_unit();
+            self.emit(GameEvent::ChipsBought { name, value });
+            Ok(te_stack)
+        }
+        /// 带着从收银台买的筹码坐到空座位上
+        pub fn join_seat(&mut self, name: &str, buy_in: i32) -> Result<String, String> {
+            self.check_buy_in(buy_in)?;
+            let mut player = Player::new(name);
+            let te_stack = self.cashier.sell(name, buy_in, &mut player.owned_bank)?;
+            match self.seat_a_player(player.clone(), None) {
+                Ok(ID) => {
+                    self.emit(GameEvent::PlayerSeated { name: name.to_string(), seat: ID });
+                    self.emit(GameEvent::ChipsBought { name: name.to_string(), value: buy_in });
+                    Ok(format!("Succeed to seat \"{0}\" at seat {1} with {2}!", name, ID + 1, buy_in) + &te_stack)
+                },
+                Err(E) => {
+                    let _ = self.cashier.redeem(name, &mut player.owned_bank);
+                    Err(E)
+                },
+            }
+        }
+        /// 坐出去，筹码留在桌上
+        pub fn sit_out(&mut self, ID: usize, post_blinds: bool) -> Result<String, String> {
+            let player = self.get_seated_player(ID)?;
+            if player.sitting_out || matches!(player.role, Role::Quit) {
+                return Err(format!("Fail to sit out because \"{0}\" is not playing!", player.name));
+            }
+            let name = player.name.clone();
+            self.players[ID].sitting_out = true;
+            self.players[ID].posting_blinds = post_blinds;
+            self.players[ID].role = Role::Quit;
+            self.emit(GameEvent::PlayerSatOut { name: name.clone() });
+            Ok(format!("Succeed to sit \"{0}\" out!", name))
+        }
+        /// 坐回来，先把错过的盲注作为死钱交到钱池里
+        pub fn sit_in(&mut self, ID: usize) -> Result<String, String> {
+            let player = self.get_seated_player(ID)?;
+            if !player.sitting_out {
+                return Err(format!("Fail to sit in because \"{0}\" is not sitting out!", player.name));
+            }
+            let name = player.name.clone();
+            let stack = player.owned_bank.get_values_of_bank();
+            if stack == 0 {
+                return Err(format!("Fail to sit in because \"{0}\" has no chips left!", name));
+            }
+            let mut te_stack = String::new();
+            let owed = player.missed_blinds.min(stack);
+            if owed > 0 {
+                te_stack.push_str(&self.make_change(ID, owed));
+                te_stack.push_str(&self.players[ID].place_an_ante(owed, &mut self.cash_pool)?);
+                self.emit(GameEvent::DeadBlindPosted { name: name.clone(), value: owed });
+            }
+            let player = &mut self.players[ID];
+            player.sitting_out = false;
+            player.posting_blinds = false;
+            player.missed_blinds = 0;
+            player.role = Role::Normal;
+            self.emit(GameEvent::PlayerSatIn { name: name.clone() });
+            Ok(format!("Succeed to sit \"{0}\" in!", name) + &te_stack)
+        }
+        /// 输光的玩家重新买入
+        pub fn rebuy(&mut self, ID: usize, value: i32) -> Result<String, String> {
+            let player = self.get_seated_player(ID)?;
+            if player.owned_bank.get_values_of_bank() > 0 {
+                return Err(format!("Fail to rebuy because \"{0}\" still has chips!", player.name));
+            }
+            self.check_buy_in(value)?;
+            self.buy_chips(ID, value)
+        }
+        /// 补码到最多买入
+        pub fn top_up(&mut self, ID: usize) -> Result<String, String> {
+            let player = self.get_seated_player(ID)?;
+            let max_buy_in = match self.config.max_buy_in {
+                Some(T) => T,
+                None => return Err("Fail to top up because there is no max buy-in!".to_string()),
+            };
+            let value = max_buy_in - player.owned_bank.get_values_of_bank();
+            if value <= 0 {
+                return Err(format!("Fail to top up because \"{0}\" already has the max buy-in!", player.name));
+            }
+            self.buy_chips(ID, value)
+        }
+        /// 把筹码全部还给收银台，座位留给占位玩家，错过的盲注不用再补
+        pub fn cash_out(&mut self, ID: usize) -> Result<String, String> {
+            let name = self.get_seated_player(ID)?.name.clone();
+            let value = self.cashier.redeem(&name, &mut self.players[ID].owned_bank)?;
+            let empty = Player::new_placeholder(&format!("{0}{1}", EMPTY_SEAT_NAME, ID + 1));
+            self.players[ID] = empty;
+            self.strategies.remove(&name);
+            self.get_min_value_unit();
+            self.emit(GameEvent::PlayerCashedOut { name: name.clone(), value });
+            Ok(format!("Succeed to cash out {0} for \"{1}\"!", value, name))
+        }
+        /// 锦标赛两手之间让输光出局的玩家离开，座位留给占位玩家，换桌时才能坐人；返回离开的玩家
+        pub fn remove_busted_players(&mut self) -> Vec<String> {
+            let mut names = Vec::new();
+            for ID in 0..self.players.len() {
+                let player = &self.players[ID];
+                if player.is_placeholder || player.sitting_out || player.owned_bank.get_values_of_bank() + player.bet_bank.get_values_of_bank() > 0 {
+                    continue;
+                }
+                let name = player.name.clone();
+                self.strategies.remove(&name);
+                self.players[ID] = Player::new_placeholder(&format!("{0}{1}", EMPTY_SEAT_NAME, ID + 1));
+                names.push(name);
+            }
+            names
+        }
+        /// 轮换盲注后，大盲跳过的和轮到死小盲的坐出去的玩家错过了盲注：
+        /// 选了照交盲注的直接交死钱，否则记下来，最多记一个大盲加一个小盲
+        fn charge_sitting_out(&mut self, last_DaMang_ID: usize) -> String {
+            let mut te_stack = String::new();
+            let player_num = self.players.len();
+            let DaMang_ID = self.last_DaMang_ID as usize;
+            let XiaoMang_ID = self.last_XiaoMang_ID as usize;
+            let DaMang_value = self.get_DaMang_value();
+            let XiaoMang_value = self.get_XiaoMang_value();
+            let mut missed = Vec::new();
+            let mut ID = (last_DaMang_ID + 1) % player_num;
+            while ID != DaMang_ID && ID != last_DaMang_ID {
+                missed.push((ID, DaMang_value));
+                ID = (ID + 1) % player_num;
+            }
+            if XiaoMang_ID != DaMang_ID {
+                missed.push((XiaoMang_ID, XiaoMang_value));
+            }
+            for (ID, value) in missed {
+                if !self.players[ID].sitting_out {
+                    continue;
+                }
+                if self.players[ID].posting_blinds {
+                    let value = value.min(self.players[ID].owned_bank.get_values_of_bank());
+                    if value <= 0 {
+                        continue;
+                    }
+                    te_stack.push_str(&self.make_change(ID, value));
+                    match self.players[ID].place_an_ante(value, &mut self.cash_pool) {
+                        Ok(T) => {
+                            te_stack.push_str(&T);
+                            self.emit(GameEvent::DeadBlindPosted { name: self.players[ID].name.clone(), value });
+                            continue;
+                        },
+                        Err(E) => te_stack.push_str(&E),
+                    }
+                }
+                let player = &mut self.players[ID];
+                player.missed_blinds = (player.missed_blinds + value).min(DaMang_value + XiaoMang_value);
+            }
+            te_stack
+        }
+        /// 结算时从钱池里抽水到庄家，翻牌发没发出来看这一手走到了哪一轮
+        fn take_rake(&mut self) -> i32 {
+            let rule = match &self.config.rake {
+                Some(T) => T.clone(),
+                None => return 0,
+            };
+            let pot = self.cash_pool.cash_pool.get_values_of_bank();
+            let rake = rule.get_rake(pot, self.street >= 1, self.min_value_unit);
+            let rake = self.house.take_rake(rake, self.min_value_unit, &mut self.cash_pool);
+            if rake > 0 {
+                self.emit(GameEvent::RakeTaken { value: rake });
+            }
+            if let Some(history) = self.histories.last_mut() {
+                history.rake = rake;
+            }
+            rake
+        }
+        /// 结束这一场，返回这一场的抽水统计
+        pub fn end_rake_session(&mut self) -> RakeReport {
+            self.house.end_session()
+        }
+        /// 筹码凑不出value时先到筹码架上换零，下注前调用；换不开就交给下注自己失败
+        fn make_change(&mut self, ID: usize, value: i32) -> String {
+            if self.chip_bank.get_values_of_bank() == 0 {
+                return String::new();
+            }
+            let min_value = self.min_value_unit;
+            let player = &mut self.players[ID];
+            let value = value.min(player.owned_bank.get_values_of_bank() + player.bet_bank.get_values_of_bank());
+            match player.owned_bank.make_change(value, &player.bet_bank, &mut self.chip_bank, min_value) {
+                Ok(T) => T,
+                Err(E) => E,
+            }
+        }
+        /// 往筹码架上每种面额都补上价值total的筹码，没有整套筹码时不能补
+        pub fn stock_chip_bank(&mut self, total: i32) -> Result<String, String> {
+            let chip_set = match &self.config.chip_set {
+                Some(T) => T.clone(),
+                None => return Err("Fail to stock the chip bank because the table has no chip set!".to_string()),
+            };
+            let mut te_stack = String::new();
+            for index in 0..chip_set.denominations.len() {
+                let rack = (chip_set.get_stuff_type(index), total / chip_set.denominations[index].value);
+                te_stack.push_str(&self.chip_bank.stuff_in(&vec![(&rack.0, rack.1)], true)?);
+            }
+            Ok(te_stack)
+        }
+        /// 锦标赛升盲后，盲注和前注都用不上最小面额时把它换成上一级面额：
+        /// 每人的零头满半个上一级筹码就进成一个，不满就拿掉，但至少留一个，差额由筹码架出
+        pub fn color_up(&mut self) -> String {
+            let chip_set = match &self.config.chip_set {
+                Some(T) => T.clone(),
+                None => return String::new(),
+            };
+            let mut te_stack = String::new();
+            while self.colored_up + 1 < chip_set.denominations.len() {
+                let next_value = chip_set.denominations[self.colored_up + 1].value;
+                let values = [self.get_XiaoMang_value(), self.get_DaMang_value(), self.get_ante_value()];
+                if values.iter().any(|value| value % next_value != 0) {
+                    break;
+                }
+                let low = chip_set.get_stuff_type(self.colored_up);
+                let high = chip_set.get_stuff_type(self.colored_up + 1);
+                for ID in 0..self.players.len() {
+                    let num = self.players[ID].owned_bank.get_num_of(&low);
+                    if num == 0 {
+                        continue;
+                    }
+                    let removed = num * low.get_value();
+                    let mut high_num = removed / next_value;
+                    if (removed % next_value) * 2 >= next_value {
+                        high_num += 1;
+                    }
+                    if high_num == 0 && self.players[ID].owned_bank.get_values_of_bank() == removed {
+                        high_num = 1;
+                    }
+                    if self.chip_bank.get_num_of(&high) < high_num {
+                        te_stack.push_str(&format!("Fail to color up for \"{0}\" because the chip bank runs out of {1}!", self.players[ID].name, high));
+                        continue;
+                    }
+                    match self.players[ID].owned_bank.stuff_transfer(&low, num, &mut self.chip_bank) {
+                        Ok(T) => te_stack.push_str(&T),
+                        Err(E) => {
+                            te_stack.push_str(&E);
+                            continue;
+                        },
+                    }
+                    if high_num > 0 {
+                        match self.chip_bank.stuff_transfer(&high, high_num, &mut self.players[ID].owned_bank) {
+                            Ok(T) => te_stack.push_str(&T),
+                            Err(E) => te_stack.push_str(&E),
+                        }
+                    }
+                    self.emit(GameEvent::ChipsColoredUp { name: self.players[ID].name.clone(), removed, added: high_num * next_value });
+                }
+                self.colored_up += 1;
+            }
+            self.get_min_value_unit();
+            te_stack
+        }
+        /// 游戏初始化
+        pub fn init_game(&mut self, initial: &Vec<(&StuffType, i32)>) -> Result<String, String> {
+            let mut te_stack = String::new();
+            match self.five_cards.clear_five_cards() {
+                Ok(T) => te_stack.push_str(&(T)),
+                Err(E) => te_stack.push_str(&(E)),
+            }
+            match self.cash_pool.clear_cash_pool() {
+                Ok(T) => te_stack.push_str(&(T)),
+                Err(E) => te_stack.push_str(&(E)),
+            }
+            self.card_pool = self.card_pool.reset_card_pool();
+
+            // 随机选择小盲角色
+            let length = self.players.len();
+            let mut rng = thread_rng();
+            let rand_num = rng.gen_range(0..length);
+
+            for (ID, player) in self.players.iter_mut().enumerate() {
+                match player.clear_my_bet_bank() {
+                    Ok(T) => te_stack.push_str(&(T)),
+                    Err(E) => te_stack.push_str(&(E)),
+                }
+                match player.clear_my_cards() {
+                    Ok(T) => te_stack.push_str(&(T)),
+                    Err(E) => te_stack.push_str(&(E)),
+                }
+                match player.initial_my_owned_bank(initial) {
+                    Ok(T) => te_stack.push_str(&(T)),
+                    Err(E) => te_stack.push_str(&(E)),
+                }
+            }
+            self.get_min_value_unit();
+            self.assign_blinds(rand_num);
+            self.straddle_ID = None;
+            Ok(te_stack)
+        }
+        /// 指定小盲的位置，下一位是大盲，其余玩家都是普通角色；两人时小盲就是庄家，否则小盲前一位是庄家
+        pub fn assign_blinds(&mut self, XiaoMang_ID: usize) {
+            for player in self.players.iter_mut() {
+                player.role = if player.sitting_out { Role::Quit } else { Role::Normal };
+            }
+            let DaMang_ID = self.get_next_seat(XiaoMang_ID);
+            self.button_ID = if self.get_player_num_left() == 2 { XiaoMang_ID } else { self.get_previous_seat(XiaoMang_ID) } as i32;
+            self.set_blinds(XiaoMang_ID, DaMang_ID);
+        }
+        /// 按死庄规则轮换庄家和盲注：大盲走到上一手大盲之后的下一个玩家，上一手的大盲下小盲，上一手的小盲做庄，
+        /// 这两个人出局了就是死小盲和死庄；只剩两人时庄家下小盲，另一个人下大盲
+        pub fn move_button(&mut self) {
+            let last_DaMang_ID = self.last_DaMang_ID as usize;
+            let DaMang_ID = self.get_next_seat(last_DaMang_ID);
+            if self.get_player_num_left() == 2 {
+                let XiaoMang_ID = self.get_next_seat(DaMang_ID);
+                self.button_ID = XiaoMang_ID as i32;
+                self.set_blinds(XiaoMang_ID, DaMang_ID);
+            } else {
+                self.button_ID = self.last_XiaoMang_ID;
+                self.set_blinds(last_DaMang_ID, DaMang_ID);
+            }
+        }
+        /// 直接摆好庄家和大小盲的座位
+        pub fn set_positions(&mut self, button_ID: usize, XiaoMang_ID: usize, DaMang_ID: usize) {
+            self.button_ID = button_ID as i32;
+            self.set_blinds(XiaoMang_ID, DaMang_ID);
+        }
+        /// 设好大小盲的角色，小盲座位上的玩家已经出局时没人下小盲
+        fn set_blinds(&mut self, XiaoMang_ID: usize, DaMang_ID: usize) {
+            let XiaoMang_value = self.get_XiaoMang_value();
+            let DaMang_value = self.get_DaMang_value();
+            for player in self.players.iter_mut() {
+                if !matches!(player.role, Role::Quit) {
+                    player.role = Role::Normal;
+                }
+            }
+            if !matches!(self.players[XiaoMang_ID].role, Role::Quit) {
+                self.players[XiaoMang_ID].role = Role::XiaoMang(XiaoMang_value);
+            }
+            self.players[DaMang_ID].role = Role::DaMang(DaMang_value);
+            self.last_XiaoMang_ID = XiaoMang_ID as i32;
+            self.last_DaMang_ID = DaMang_ID as i32;
+        }
+        /// 这一手是不是死小盲
+        pub fn is_XiaoMang_dead(&self) -> bool {
+            match self.players.get(self.last_XiaoMang_ID as usize) {
+                Some(player) => matches!(player.role, Role::Quit),
+                None => false,
+            }
+        }
+        /// 还留在场上的玩家数量
+        pub fn get_player_num_left(&self) -> usize {
+            self.players.iter().filter(|player| !matches!(player.role, Role::Quit)).count()
+        }
+        /// 往后数下一个还留在场上的座位
+        pub fn get_next_seat(&self, ID: usize) -> usize {
+            let player_num = self.players.len();
+            for i in 1..=player_num {
+                let next = (ID + i) % player_num;
+                if !matches!(self.players[next].role, Role::Quit) {
+                    return next;
+                }
+            }
+            ID
+        }
+        /// 往前数上一个还留在场上的座位
+        pub fn get_previous_seat(&self, ID: usize) -> usize {
+            let player_num = self.players.len();
+            for i in 1..=player_num {
+                let previous = (ID + player_num * 2 - i) % player_num;
+                if !matches!(self.players[previous].role, Role::Quit) {
+                    return previous;
+                }
+            }
+            ID
+        }
+        /// 按位置排好一轮下注的座位顺序：翻前从小盲开始，翻后从庄家下一位开始，
+        /// 所以两人时庄家翻前先说话、翻后后说话
+        pub fn get_action_order(&self, street: usize) -> Vec<usize> {
+            let player_num = self.players.len();
+            let first = if street == 0 { self.last_XiaoMang_ID as usize } else { self.button_ID as usize + 1 };
+            (0..player_num).map(|i| (first + i) % player_num).collect()
+        }
+
+        /// 把整个游戏状态存成JSON，挂上的策略不存
+        pub fn save_to_json(&self) -> Result<String, String> {
+            match serde_json::to_string(self) {
+                Ok(T) => Ok(T),
+                Err(E) => Err(format!("Fail to save the game: {0}", E)),
+            }
+        }
+        /// 从JSON读回游戏状态
+        pub fn load_from_json(text: &str) -> Result<Game, String> {
+            match serde_json::from_str(text) {
+                Ok(T) => Ok(T),
+                Err(E) => Err(format!("Fail to load the game: {0}", E)),
+            }
+        }
+        /// 存档到文件
+        pub fn save(&self, path: &str) -> Result<String, String> {
+            match std::fs::write(path, self.save_to_json()?) {
+                Ok(_) => Ok(format!("Succeed to save the game to {0}!", path)),
+                Err(E) => Err(format!("Fail to write {0}: {1}", path, E)),
+            }
+        }
+        /// 从文件读档
+        pub fn load(path: &str) -> Result<Game, String> {
+            match std::fs::read_to_string(path) {
+                Ok(T) => Game::load_from_json(&T),
+                Err(E) => Err(format!("Fail to read {0}: {1}", path, E)),
+            }
+        }
+        /// 打开或关闭练习模式，关闭时清掉悔棋记录
+        pub fn set_practice_mode(&mut self, practice_mode: bool) {
+            self.practice_mode = practice_mode;
+            if !practice_mode {
+                self.undo_stack.clear();
+            }
+        }
+        /// 还能悔几步
+        pub fn get_undo_steps(&self) -> usize {
+            self.undo_stack.len()
+        }
+        /// 在处理事件前拍下快照
+        fn take_snapshot(&self) -> GameSnapshot {
+            GameSnapshot {
+                players: self.players.clone(),
+                cash_pool: self.cash_pool.clone(),
+                card_pool: self.card_pool.clone(),
+                five_cards: self.five_cards.clone(),
+                game_status: self.game_status.clone(),
+                last_XiaoMang_ID: self.last_XiaoMang_ID,
+                button_ID: self.button_ID,
+                last_DaMang_ID: self.last_DaMang_ID,
+                straddle_ID: self.straddle_ID,
+                street: self.street,
+                min_value_unit: self.min_value_unit,
+                opponent_stats: self.opponent_stats.clone(),
+                histories: self.histories.clone(),
+                blind_timer: self.blind_timer.clone(),
+                cashier: self.cashier.clone(),
+                house: self.house.clone(),
+                chip_bank: self.chip_bank.clone(),
+                colored_up: self.colored_up,
+            }
+        }
+        /// 事件成功处理后才把快照存进悔棋记录
+        fn push_snapshot(&mut self, snapshot: GameSnapshot) {
+            if self.undo_limit == 0 {
+                return;
+            }
+            if self.undo_stack.len() >= self.undo_limit {
+                self.undo_stack.remove(0);
+            }
+            self.undo_stack.push(snapshot);
+        }
+        /// 撤销最近的steps个事件，挂上的策略不会跟着回退
+        fn undo(&mut self, steps: usize) -> Result<String, String> {
+            if !self.practice_mode {
+                return Err("Cannot undo while the game is not in practice mode!".to_string());
+            }
+            if steps == 0 || steps > self.undo_stack.len() {
+                return Err(format!("Fail to undo {0} steps because only {1} steps can be undone!", steps, self.undo_stack.len()));
+            }
+            let index = self.undo_stack.len() - steps;
+            let snapshot = self.undo_stack.split_off(index).swap_remove(0);
+            self.players = snapshot.players;
+            self.cash_pool = snapshot.cash_pool;
+            self.card_pool = snapshot.card_pool;
+            self.five_cards = snapshot.five_cards;
+            self.game_status = snapshot.game_status;
+            self.last_XiaoMang_ID = snapshot.last_XiaoMang_ID;
+            self.button_ID = snapshot.button_ID;
+            self.last_DaMang_ID = snapshot.last_DaMang_ID;
+            self.straddle_ID = snapshot.straddle_ID;
+            self.street = snapshot.street;
+            self.min_value_unit = snapshot.min_value_unit;
+            self.opponent_stats = snapshot.opponent_stats;
+            self.histories = snapshot.histories;
+            self.blind_timer = snapshot.blind_timer;
+            self.cashier = snapshot.cashier;
+            self.house = snapshot.house;
+            self.chip_bank = snapshot.chip_bank;
+            self.colored_up = snapshot.colored_up;
+            Ok(format!("Succeed to undo {0} steps!", steps))
+        }
+        /// 添加旁观的事件订阅者，看不到任何人的底牌
+        pub fn add_observer(&mut self, observer: Rc<RefCell<dyn GameObserver>>) {
+            self.observers.push((None, observer));
+        }
+        /// 以某个玩家的身份添加事件订阅者，能看到这个玩家自己的底牌
+        pub fn add_observer_for(&mut self, name: &str, observer: Rc<RefCell<dyn GameObserver>>) {
+            self.observers.push((Some(name.to_string()), observer));
+        }
+        /// 通过通道旁观事件
+        pub fn subscribe(&mut self) -> Receiver<GameEvent> {
+            let (sender, receiver) = mpsc::channel();
+            self.add_observer(Rc::new(RefCell::new(sender)));
+            receiver
+        }
+        /// 通过通道以某个玩家的身份订阅事件
+        pub fn subscribe_as(&mut self, name: &str) -> Receiver<GameEvent> {
+            let (sender, receiver) = mpsc::channel();
+            self.add_observer_for(name, Rc::new(RefCell::new(sender)));
+            receiver
+        }
+        /// 把事件发给订阅者，只给某个玩家看的事件只发给以他身份订阅的人
+        fn emit(&self, event: GameEvent) {
+            let recipient = event.get_recipient();
+            for (viewer, observer) in self.observers.iter() {
+                if recipient.is_none() || recipient == viewer.as_deref() {
+                    observer.borrow_mut().on_event(&event);
+                }
+            }
+        }
+        /// 行动或摊牌到了新的一轮时，记下走到的轮次，并把中间每一轮的开始发出去
+        fn advance_street(&mut self, street: usize) {
+            self.street = self.street.max(street.min(3));
+            let history = match self.histories.last() {
+                Some(T) => T,
+                None => return,
+            };
+            let current = history.actions.iter().map(|entry| entry.street).max().unwrap_or(0);
+            for next in (current + 1)..=street.min(3) {
+                let board = history.board.iter().take(next + 2).cloned().collect();
+                self.emit(GameEvent::StreetAdvanced { street: next, board });
+            }
+        }
+        /// 给某个玩家挂上策略
+        pub fn set_strategy(&mut self, name: &str, strategy: Rc<RefCell<dyn Strategy>>) {
+            self.strategies.insert(name.to_string(), strategy);
+        }
+        /// 启动外部机器人挂到某个玩家上，每次行动的时限用规则里的action_timeout_ms，没给就用默认时限
+        pub fn attach_external_bot(&mut self, name: &str, program: &str, args: &Vec<&str>) -> Result<Rc<RefCell<ExternalStrategy>>, String> {
+            if !self.players.iter().any(|player| player.name == name) {
+                return Err(format!("Fail to find the player {0}!", name));
+            }
+            let timeout = self.config.get_action_timeout().unwrap_or(Duration::from_millis(DEFAULT_ACTION_TIMEOUT_MS));
+            let strategy = Rc::new(RefCell::new(ExternalStrategy::spawn(program, args, timeout)?));
+            self.set_strategy(name, strategy.clone());
+            Ok(strategy)
+        }
+        /// 钱池和所有人下注的总和
+        pub fn get_pot_value(&self) -> i32 {
+            let mut value = self.cash_pool.cash_pool.get_values_of_bank();
+            for player in self.players.iter() {
+                value += player.bet_bank.get_values_of_bank();
+            }
+            return value;
+        }
+        /// 生成机器人决策用的信息
+        pub fn get_decision_context(&self, ID: usize, last_player_ID: usize, value: i32, street: usize) -> DecisionContext {
+            let player = &self.players[ID];
+            let mut opponent = None;
+            let mut opponent_stats = None;
+            if last_player_ID != ID {
+                let name = self.players[last_player_ID].name.clone();
+                opponent_stats = self.opponent_stats.get(&name).cloned();
+                opponent = Some(name);
+            }
+            DecisionContext {
+                name: player.name.clone(),
+                hole_cards: player.get_cards_names(),
+                street,
+                strength: player.get_two_cards_strength(),
+                last_player_value: value,
+                bet_value: player.bet_bank.get_values_of_bank(),
+                owned_value: player.owned_bank.get_values_of_bank(),
+                pot_value: self.get_pot_value(),
+                max_bet_value: self.get_max_bet_value(),
+                min_value_unit: self.min_value_unit,
+                raise_range: self.get_raise_range(ID, value, street),
+                opponent,
+                opponent_stats,
+                view: self.get_player_view(ID).unwrap(),
+                prizes: self.prizes.clone(),
+            }
+        }
+        /// 按下注结构算出能加注到的最小和最大总下注额，不能加注时返回None
+        ///
+        /// 无限注：至少加到上一次加注的幅度（最少一个大盲），最多全下；
+        /// 底池限注：最多加到跟注后底池的大小；
+        /// 固定限注：翻前和翻牌圈每次加一个大盲，转牌和河牌圈每次加两个大盲，每轮最多四次下注
+        pub fn get_raise_range(&self, ID: usize, value: i32, street: usize) -> Option<(i32, i32)> {
+            let player = &self.players[ID];
+            let bet_value = player.bet_bank.get_values_of_bank();
+            let all_in = bet_value + player.owned_bank.get_values_of_bank();
+            let big_blind = self.get_DaMang_value().max(self.min_value_unit);
+            // 从牌局记录里算出当前最高注、这一轮最大的加注幅度和下注次数
+            let mut level = value;
+            let mut last_raise = big_blind;
+            let mut raises = if street == 0 { 1 } else { 0 };
+            if let Some(history) = self.histories.last() {
+                let mut committed: HashMap<&str, i32> = HashMap::new();
+                let mut history_level = 0;
+                for entry in history.actions.iter() {
+                    let before_level = history_level;
+                    let total = committed.entry(entry.name.as_str()).or_insert(0);
+                    match entry.action {
+                        HistoryAction::SmallBlind(value) | HistoryAction::BigBlind(value) | HistoryAction::Straddle(value) | HistoryAction::Call(value) => *total += value,
+                        HistoryAction::Raise(to) => *total = to,
+                        _ => {}
+                    }
+                    history_level = history_level.max(*total);
+                    match entry.action {
+                        HistoryAction::Raise(to) if entry.street == street && to > before_level => {
+                            raises += 1;
+                            last_raise = last_raise.max(to - before_level);
+                        }
+                        // 抓头算一次加注，之后的最小加注按抓头的金额算
+                        HistoryAction::Straddle(value) if street == 0 => {
+                            raises += 1;
+                            last_raise = last_raise.max(value);
+                        }
+                        _ => {}
+                    }
+                }
+                level = level.max(history_level);
+            }
+            if all_in <= level {
+                return None;
+            }
+            let (min, mut max) = match self.config.betting_structure {
+                BettingStructure::NoLimit => (level + last_raise, all_in),
+                BettingStructure::PotLimit => (level + last_raise, level + self.get_pot_value() + (level - bet_value)),
+                BettingStructure::FixedLimit => {
+                    if raises >= 4 {
+                        return None;
+                    }
+                    let size = if street >= 2 { big_blind * 2 } else { big_blind };
+                    (level + size, level + size)
+                }
+            };
+            if let Some(max_bet) = self.config.max_bet {
+                max = max.min(max_bet);
+            }
+            max = max.min(all_in);
+            // 筹码不够最小加注时可以全下
+            let min = min.min(all_in);
+            if min > max || max <= level {
+                return None;
+            }
+            Some((min, max))
+        }
+        /// 检查下注到bet_value是否符合下注结构
+        pub fn check_raise(&self, ID: usize, value: i32, street: usize, bet_value: i32) -> Result<(), String> {
+            if bet_value <= value {
+                return Ok(());
+            }
+            match self.get_raise_range(ID, value, street) {
+                Some((min, max)) if min <= bet_value && bet_value <= max => Ok(()),
+                Some((min, max)) => Err(format!("Cannot raise to {0} in a {1:?} game, the raise must be between {2} and {3}!", bet_value, self.config.betting_structure, min, max)),
+                None => Err(format!("Cannot raise to {0} in a {1:?} game now!", bet_value, self.config.betting_structure)),
+            }
+        }
+        /// 执行策略给出的行动，加注失败就改为跟注，跟注失败就放弃
+        pub fn apply_bot_action(&mut self, ID: usize, value: i32, street: usize, action: BotAction) -> Result<String, String> {
+            let mut te_stack = String::new();
+            let players_clone = self.players.clone();
+            let bet_bank_backup = players_clone[ID].bet_bank.get_basket_vec();
+            let call_value = value.max(players_clone[ID].bet_bank.get_values_of_bank());
+            let mut values = Vec::new();
+            match action {
+                BotAction::Fold => {
+                    self.players[ID].role = Role::GiveUp;
+                    return Err(te_stack);
+                }
+                BotAction::Check | BotAction::Call => values.push(call_value),
+                BotAction::Raise(raise_value) => {
+                    if raise_value > call_value && self.check_raise(ID, value, street, raise_value).is_ok() {
+                        values.push(raise_value);
+                    } else {
+                        te_stack.push_str(&format!("Fail to raise to {0}!", raise_value));
+                    }
+                    values.push(call_value);
+                }
+            }
+            for bet_value in values {
+                te_stack.push_str(&self.make_change(ID, bet_value));
+                match self.players[ID].place_a_bet_with_value(bet_value) {
+                    Ok(T) => {
+                        te_stack.push_str(&T);
+                        return Ok(te_stack);
+                    }
+                    Err(E) => {
+                        let _ = self.players[ID].place_a_bet(&bet_bank_backup);
+                        te_stack.push_str(&E);
+                    }
+                }
+            }
+            self.players[ID].role = Role::GiveUp;
+            return Err(te_stack);
+        }
+        /// 机器人根据上一个下注者的下注和对他的统计数据来下注
+        pub fn bot_place_a_bet(&mut self, ID: usize, last_player_ID: usize, value: i32, street: usize) -> Result<String, String> {
+            let value = value.max(self.get_level_value());
+            if let Some(strategy) = self.strategies.get(&self.players[ID].name).cloned() {
+                let context = self.get_decision_context(ID, last_player_ID, value, street);
+                let before_value = context.bet_value;
+                let action = strategy.borrow_mut().decide(&context);
+                let result = self.apply_bot_action(ID, value, street, action);
+                self.record_action(ID, street, before_value, value);
+                return result;
+            }
+            let context = self.get_decision_context(ID, last_player_ID, value, street);
+            let before_value = context.bet_value;
+            let action = match &context.opponent_stats {
+                Some(stats) => self.players[ID].profile.adjust_against(stats).decide(&mut thread_rng(), &context),
+                None => self.players[ID].profile.decide(&mut thread_rng(), &context),
+            };
+            // 和策略一样走apply_bot_action，最后下的是多少就按多少换零
+            let result = self.apply_bot_action(ID, value, street, action);
+            self.record_action(ID, street, before_value, value);
+            return result;
+        }
+        /// 根据玩家行动前后的下注判断行动类型，并记录到对手统计中
+        pub fn record_action(&mut self, ID: usize, street: usize, before_value: i32, last_player_value: i32) {
+            let player = &self.players[ID];
+            let bet_value = player.bet_bank.get_values_of_bank();
+            let action = if let Role::GiveUp = player.role {
+                ActionType::Fold
+            } else if bet_value > last_player_value && bet_value > before_value {
+                ActionType::Raise
+            } else if bet_value > before_value {
+                ActionType::Call
+            } else {
+                ActionType::Check
+            };
+            let name = player.name.clone();
+            let all_in = player.owned_bank.get_values_of_bank() == 0;
+            let history_action = match action {
+                ActionType::Fold => HistoryAction::Fold,
+                ActionType::Check => HistoryAction::Check,
+                ActionType::Call => HistoryAction::Call(bet_value - before_value),
+                ActionType::Raise => HistoryAction::Raise(bet_value),
+            };
+            self.advance_street(street);
+            self.emit(GameEvent::PlayerActed { name: name.clone(), street, action: history_action.clone(), all_in });
+            if let Some(history) = self.histories.last_mut() {
+                history.record(&name, street, history_action, all_in);
+            }
+            self.opponent_stats.record_action(&name, street, action);
+        }
+        /// 下盲注，成功了就记下来
+        pub fn post_blind(&mut self, ID: usize, value: i32) -> Result<String, String> {
+            let is_DaMang = matches!(self.players[ID].role, Role::DaMang(..));
+            self.make_change(ID, value);
+            let result = self.players[ID].place_a_bet_with_value(value);
+            if result.is_ok() {
+                self.record_blind(ID, is_DaMang);
+            }
+            return result;
+        }
+        /// 前注：锦标赛按当前级别，否则是规则里的ante
+        pub fn get_ante_value(&self) -> i32 {
+            match &self.blind_timer {
+                Some(timer) => timer.get_level().ante,
+                None => self.config.ante,
+            }
+        }
+        /// 开始锦标赛模式，盲注和前注按级别表走，级别按给的时钟计时
+        pub fn start_tournament(&mut self, schedule: BlindSchedule, clock: Rc<dyn Clock>) -> Result<String, String> {
+            schedule.check(self.min_value_unit.max(1))?;
+            let level = schedule.get_level(0).clone();
+            self.config.blind_schedule = Some(schedule.clone());
+            self.blind_timer = Some(BlindTimer::new(schedule, clock));
+            Ok(format!("The tournament starts at blinds {0}/{1} with ante {2}!", level.small_blind, level.big_blind, level.ante))
+        }
+        /// 每一手开始前让盲注计时器走一步，升级了就发事件
+        fn advance_blind_level(&mut self) {
+            let changed = match &mut self.blind_timer {
+                Some(timer) => timer.on_new_hand(),
+                None => false,
+            };
+            if changed {
+                if let Some(timer) = &self.blind_timer {
+                    let level = timer.get_level();
+                    let event = GameEvent::BlindLevelChanged { level: timer.level + 1, small_blind: level.small_blind, big_blind: level.big_blind, ante: level.ante };
+                    self.emit(event);
+                }
+                self.color_up();
+            }
+        }
+        /// 抓头下两倍大盲
+        pub fn get_straddle_value(&self) -> i32 {
+            self.get_DaMang_value() * 2
+        }
+        /// 桌上最高的下注，抓头或者有人全下不够时要跟的就是它
+        pub fn get_level_value(&self) -> i32 {
+            self.players.iter().map(|player| player.bet_bank.get_values_of_bank()).max().unwrap_or(0)
+        }
+        /// 这一轮里还要不要轮到这个玩家：已经放弃或全下的不用，跟平了桌上最高的注并且在最后一次加注（或强制下注）之后主动行动过、或者别人都没法再下注的也不用
+        pub fn needs_to_act(&self, ID: usize, street: usize) -> bool {
+            let player = &self.players[ID];
+            if !matches!(player.role, Role::PlaceBet | Role::Normal | Role::XiaoMang(..) | Role::DaMang(..)) {
+                return false;
+            }
+            if player.owned_bank.get_values_of_bank() == 0 {
+                return false;
+            }
+            // 别人都全下或放弃了，跟平了就没什么可做的
+            let level = self.get_level_value();
+            let others_can_act = self.players.iter().enumerate().any(|(other, player)| other != ID && matches!(player.role, Role::PlaceBet | Role::Normal | Role::XiaoMang(..) | Role::DaMang(..)) && player.owned_bank.get_values_of_bank() > 0);
+            if !others_can_act && player.bet_bank.get_values_of_bank() == level {
+                return false;
+            }
+            let history = match self.histories.last() {
+                Some(history) => history,
+                None => return true,
+            };
+            let entries: Vec<&HistoryEntry> = history.actions.iter().filter(|entry| entry.street == street).collect();
+            let last_raise = entries.iter().rposition(|entry| matches!(entry.action, HistoryAction::Raise(..) | HistoryAction::BigBlind(..) | HistoryAction::Straddle(..)));
+            let acted_entries = match last_raise {
+                Some(index) => &entries[index..],
+                None => &entries[..],
+            };
+            player.bet_bank.get_values_of_bank() < level || !acted_entries.iter().any(|entry| entry.name == player.name && !entry.action.is_forced())
+        }
+        /// 发牌前说好由谁抓头，只有规则允许的枪口位或庄家可以
+        pub fn set_straddle(&mut self, ID: usize) -> Result<String, String> {
+            let player = self.players.get(ID).ok_or(format!("Fail to find the player at seat {0}!", ID))?;
+            let name = player.name.clone();
+            if !matches!(player.role, Role::Normal) {
+                return Err(format!("{0} cannot straddle because the player is in the blinds or out of the game!", name));
+            }
+            let is_UTG = ID == self.get_next_seat(self.last_DaMang_ID as usize);
+            let is_button = ID as i32 == self.button_ID;
+            if !(is_UTG && self.config.straddles.contains(&Straddle::UnderTheGun)) && !(is_button && self.config.straddles.contains(&Straddle::Button)) {
+                return Err(format!("{0} cannot straddle from seat {1}!", name, ID + 1));
+            }
+            let value = self.get_straddle_value();
+            let ante = if self.config.big_blind_ante { 0 } else { self.get_ante_value() };
+            if player.owned_bank.get_values_of_bank() < value + ante {
+                return Err(format!("{0} does not have enough to straddle {1}!", name, value));
+            }
+            self.straddle_ID = Some(ID);
+            Ok(format!("{0} will straddle {1}!", name, value))
+        }
+        /// 发牌后按位置下前注，前注直接进钱池；大盲前注时只有大盲替全桌下
+        fn post_antes(&mut self) -> String {
+            let mut te_stack = String::new();
+            let ante = self.get_ante_value();
+            if ante <= 0 {
+                return te_stack;
+            }
+            let payers: Vec<usize> = if self.config.big_blind_ante {
+                vec![self.last_DaMang_ID as usize]
+            } else {
+                self.get_action_order(0).into_iter().filter(|ID| !matches!(self.players[*ID].role, Role::Quit)).collect()
+            };
+            for ID in payers {
+                // 不够的就全下
+                let value = ante.min(self.players[ID].owned_bank.get_values_of_bank());
+                if value <= 0 {
+                    continue;
+                }
+                te_stack.push_str(&self.make_change(ID, value));
+                match self.players[ID].place_an_ante(value, &mut self.cash_pool) {
+                    Ok(T) => te_stack.push_str(&T),
+                    Err(E) => {
+                        te_stack.push_str(&E);
+                        continue;
+                    },
+                }
+                self.record_forced_bet(ID, HistoryAction::Ante(value));
+            }
+            te_stack
+        }
+        /// 下说好的抓头，抓头的人翻前第一圈不用行动
+        fn post_straddle(&mut self) -> String {
+            let ID = match self.straddle_ID.take() {
+                Some(T) => T,
+                None => return String::new(),
+            };
+            let value = self.get_straddle_value();
+            self.make_change(ID, value);
+            match self.players[ID].place_a_bet_with_value(value) {
+                Ok(T) => {
+                    self.record_forced_bet(ID, HistoryAction::Straddle(value));
+                    T
+                },
+                Err(E) => E,
+            }
+        }
+        /// 把前注或抓头记到牌局记录里
+        fn record_forced_bet(&mut self, ID: usize, action: HistoryAction) {
+            let player = &self.players[ID];
+            let all_in = player.owned_bank.get_values_of_bank() == 0;
+            let name = player.name.clone();
+            match action {
+                HistoryAction::Ante(value) => self.emit(GameEvent::AntePosted { name: name.clone(), value }),
+                HistoryAction::Straddle(value) => self.emit(GameEvent::StraddlePosted { name: name.clone(), value }),
+                _ => {}
+            }
+            if let Some(history) = self.histories.last_mut() {
+                history.record(&name, 0, action, all_in);
+            }
+        }
+        /// 把已经下好的盲注记到牌局记录里
+        pub fn record_blind(&mut self, ID: usize, is_DaMang: bool) {
+            let player = &self.players[ID];
+            let value = player.bet_bank.get_values_of_bank();
+            let action = if is_DaMang { HistoryAction::BigBlind(value) } else { HistoryAction::SmallBlind(value) };
+            let all_in = player.owned_bank.get_values_of_bank() == 0;
+            let name = player.name.clone();
+            self.emit(GameEvent::BlindPosted { name: name.clone(), value, is_big: is_DaMang });
+            if let Some(history) = self.histories.last_mut() {
+                history.record(&name, 0, action, all_in);
+            }
+        }
+        /// 发完牌后开始记录新的一手
+        pub fn start_history(&mut self) {
+            let mut seats = Vec::new();
+            for (ID, player) in self.players.iter().enumerate() {
+                let stack = player.owned_bank.get_values_of_bank() + player.bet_bank.get_values_of_bank();
+                if let Role::Quit = player.role {
+                    continue;
+                }
+                let hole_cards = player.get_cards().iter().map(|card| card.get_short_name()).collect();
+                seats.push(SeatInfo { seat: ID, name: player.name.clone(), stack, hole_cards });
+            }
+            let button = self.button_ID as usize;
+            let hand_id = self.histories.len() as u64 + 1;
+            let mut history = HandHistory::new(hand_id, button, self.get_XiaoMang_value(), self.get_DaMang_value(), seats);
+            history.board = self.five_cards.board_order.iter().map(|card| card.get_short_name()).collect();
+            history.limit = match self.config.betting_structure {
+                BettingStructure::NoLimit => "No Limit",
+                BettingStructure::PotLimit => "Pot Limit",
+                BettingStructure::FixedLimit => "Limit",
+            }.to_string();
+            let seats = history.seats.iter().map(|seat| (seat.seat, seat.name.clone(), seat.stack)).collect();
+            self.emit(GameEvent::HandStarted { hand_id, button, seats });
+            for seat in history.seats.iter() {
+                self.emit(GameEvent::CardsDealt { name: seat.name.clone() });
+                self.emit(GameEvent::HoleCards { name: seat.name.clone(), cards: seat.hole_cards.clone() });
+            }
+            self.histories.push(history);
+        }
+        /// 玩家事件响应，练习模式下只有成功的事件才能悔棋
+        pub fn receive_my_event(&mut self, event: MyEvent) -> Result<String, String> {
+            let snapshot = if self.practice_mode && !matches!(event, MyEvent::Undo { .. }) { Some(self.take_snapshot()) } else { None };
+            let result = self.handle_my_event(event);
+            if let (Some(snapshot), Ok(_)) = (snapshot, &result) {
+                self.push_snapshot(snapshot);
+            }
+            result
+        }
+        fn handle_my_event(&mut self, event: MyEvent) -> Result<String, String> {
+            let game_status = self.game_status.clone();
+            match event {
+                MyEvent::Undo { steps } => {
+                    return self.undo(steps);
+                },
+                MyEvent::Straddle { seat } => {
+                    match game_status {
+                        GameStatus::CardsPicking => {
+                            return self.set_straddle(seat);
+                        },
+                        _ => {
+                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
+                        },
+                    }
+                },
+                MyEvent::JoinSeat { .. } | MyEvent::SitOut { .. } | MyEvent::SitIn { .. } | MyEvent::Rebuy { .. } | MyEvent::TopUp { .. } | MyEvent::CashOut { .. } => {
+                    if !self.is_between_hands() {
+                        return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
+                    }
+                    return match event {
+                        MyEvent::JoinSeat { name, buy_in } => self.join_seat(name, buy_in),
+                        MyEvent::SitOut { seat, post_blinds } => self.sit_out(seat, post_blinds),
+                        MyEvent::SitIn { seat } => self.sit_in(seat),
+                        MyEvent::Rebuy { seat, value } => self.rebuy(seat, value),
+                        MyEvent::TopUp { seat } => self.top_up(seat),
+                        MyEvent::CashOut { seat } => self.cash_out(seat),
+                        _ => Err(format!("Cannot do {0}!", event)),
+                    };
+                },
+                MyEvent::AddBot { num, profiles } => {
+                    match game_status {
+                        GameStatus::Setting => {
+                            self.game_status = GameStatus::Setting;
+                            // 每个机器人对应一个性格
+                            if let Some(profiles) = profiles {
+                                if profiles.len() != num {
+                                    return Err(format!("Fail to add {0} bots with {1} profiles!", num, profiles.len()));
+                                }
+                            }
+                            let players_temp = self.players.clone();
+                            let mut te_stack = String::new();
+                            for i in 0..num {
+                                match self.add_a_player(PLAYER_NAME[players_temp.len() + i]) {
+                                    Ok(T) => {
+                                        te_stack.push_str(&T);
+                                        if let Some(profiles) = profiles {
+                                            if let Some(player) = self.players.last_mut() {
+                                                player.profile = profiles[i].clone();
+                                            }
+                                        }
+                                    },
+                                    Err(E) => te_stack.push_str(&E),
+                                }
+                            }
+                            return Ok(te_stack);
+                        },
+                        GameStatus::CardsPicking => {
+                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
+                        },
+                        GameStatus::BetPlacing1 => {
+                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
+                        },
+                        GameStatus::BetPlacing2 => {
+                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
+                        },
+                        GameStatus::BetPlacing3 => {
+                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
+                        },
+                        GameStatus::BetPlacing4=> {
+                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
+                        },
+                        GameStatus::Balancing => {
+                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
+                        },
+                    }
+                },
+                MyEvent::ResetGame { assets } => {
+                    match game_status {
+                        GameStatus::Setting => {
+                            self.game_status = GameStatus::Setting;
+                            return self.init_game(assets);
+                        },
+                        GameStatus::CardsPicking => {
+                            self.game_status = GameStatus::Setting;
+                            return self.init_game(assets);
+                        },
+                        GameStatus::BetPlacing1 => {
+                            self.game_status = GameStatus::Setting;
+                            return self.init_game(assets);
+                        },
+                        GameStatus::BetPlacing2 => {
+                            self.game_status = GameStatus::Setting;
+                            return self.init_game(assets);
+                        },
+                        GameStatus::BetPlacing3 => {
+                            self.game_status = GameStatus::Setting;
+                            return self.init_game(assets);
+                        },
+                        GameStatus::BetPlacing4 => {
+                            self.game_status = GameStatus::Setting;
+                            return self.init_game(assets);
+                        },
+                        GameStatus::Balancing => {
+                            self.game_status = GameStatus::Setting;
+                            return self.init_game(assets);
+                        },
+                    }
+                },
+                MyEvent::StartNextGame => {
+                    match game_status {
+                        GameStatus::Setting => {
+                            self.game_status = GameStatus::CardsPicking;
+                            self.advance_blind_level();
+                            // 锦标赛按第一级的盲注
+                            if self.blind_timer.is_some() {
+                                self.set_blinds(self.last_XiaoMang_ID as usize, self.last_DaMang_ID as usize);
+                            }
+                            return Ok("游戏开始！".to_string());
+                        },
+                        GameStatus::CardsPicking => {
+                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
+                        },
+                        GameStatus::BetPlacing1 => {
+                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
+                        },
+                        GameStatus::BetPlacing2 => {
+                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
+                        },
+                        GameStatus::BetPlacing3 => {
+                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
+                        },
+                        GameStatus::BetPlacing4 => {
+                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
+                        },
+                        GameStatus::Balancing => {
+                            self.game_status = GameStatus::CardsPicking;
+                            // 剩余玩家按死庄规则重新分配庄家和大小盲
+                            let player_num_left = self.get_player_num_left();
+                            if player_num_left <= 1 {
+                                self.game_status = GameStatus::Balancing;
+                                return Ok(format!("游戏已经结束！只有{0}人留在场上！",player_num_left));
+                            }
+                            self.advance_blind_level();
+                            let last_DaMang_ID = self.last_DaMang_ID as usize;
+                            self.move_button();
+                            let te_stack = self.charge_sitting_out(last_DaMang_ID);
+                            return Ok(format!("游戏开始！还有{0}人留在场上！",player_num_left) + &te_stack);
+                        },
+                    }
+                },
+                MyEvent::PickCards => {
+                    match game_status {
+                        GameStatus::Setting => {
+                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
+                        },
+                        GameStatus::CardsPicking => {
+                            self.game_status = GameStatus::BetPlacing1;
+                            self.street = 0;
+                            let player_num = self.players.len();
+                            let mut te_stack = String::new();
+                            // 按指定的牌排好发牌顺序
+                            match self.card_pool.arrange_pinned_deal(player_num) {
+                                Ok(T) => te_stack.push_str(&T),
+                                Err(E) => {
+                                    self.game_status = GameStatus::CardsPicking;
+                                    return Err(E);
+                                },
+                            }
+                            // 每人抽两张卡
+                            for player in self.players.iter_mut() {
+                                match player.get_two_cards(&mut self.card_pool) {
+                                    Ok(T) => te_stack.push_str(&T),
+                                    Err(E) => te_stack.push_str(&E),
+                                }
+                            }
+                            // 抽五张卡
+                            match self.five_cards.get_five_cards(&mut self.card_pool) {
+                                Ok(T) => te_stack.push_str(&T),
+                                Err(E) => te_stack.push_str(&E),
+                            }
+                            // 对手统计开始新的一手
+                            let mut names = Vec::new();
+                            for player in self.players.iter() {
+                                if let Role::PlaceBet|Role::GiveUp|Role::Normal|Role::XiaoMang(..)|Role::DaMang(..) = player.role {
+                                    names.push(player.name.clone());
+                                }
+                            }
+                            self.opponent_stats.start_hand(&names);
+                            self.start_history();
+                            // 先下前注和抓头
+                            te_stack.push_str(&self.post_antes());
+                            te_stack.push_str(&self.post_straddle());
+                            // 从小盲开始按位置出钱
+                            let order = self.get_action_order(0);
+                            let mut game_clone = self.clone();
+                            'outer:for &ID in order.iter() {
+                                let player = &game_clone.players[ID];
+                                match self.players[ID].role {
+                                    Role::XiaoMang(value) => {
+                                        // 检查是不是我
+                                        if player.name == "ME" {
+                                            return Ok(te_stack + &format!("It's time for you!"));
+                                        }
+                                        // 下注指定的资金
+                                        match self.post_blind(ID, value) {
+                                            Ok(T) => te_stack.push_str(&T),
+                                            Err(E) => te_stack.push_str(&E),
+                                        }
+                                    }
+                                    Role::DaMang(value) => {
+                                        // 检查是否其他人都放弃或者退出了
+                                        let mut last_player_ID = ID;
+                                        loop {
+                                            if last_player_ID == 0 {
+                                                last_player_ID = player_num - 1;
+                                            } else {
+                                                last_player_ID -= 1;
+                                            }
+                                            if last_player_ID == ID {
+                                                self.game_status = GameStatus::Balancing;
+                                                return Ok(te_stack + &format!("{0} win the game because nobody place a bet!", self.players[ID].name));
+                                            }
+                                            if let Role::PlaceBet|Role::DaMang(..)|Role::XiaoMang(..)|Role::Normal = self.players[last_player_ID].role {
+                                                break;
+                                            }
+                                        }
+                                        // 检查前面第一个不是旁观的角色是不是下注或放弃，如果是就可以下注，否则不能下注
+                                        let mut last_player_ID = ID;
+                                        loop {
+                                            if last_player_ID == 0 {
+                                                last_player_ID = player_num - 1;
+                                            } else {
+                                                last_player_ID -= 1;
+                                            }
+                                            if let Role::PlaceBet|Role::Normal|Role::GiveUp|Role::XiaoMang(..)|Role::DaMang(..) = self.players[last_player_ID].role {
+                                                if let Role::PlaceBet|Role::GiveUp = self.players[last_player_ID].role {
+                                                    break;
+                                                } else if self.is_XiaoMang_dead() {
+                                                    // 死小盲时大盲直接下
+                                                    break;
+                                                } else {
+                                                    continue 'outer;
+                                                }
+                                            }
+                                        }
+                                        // 检查是不是我
+                                        if player.name == "ME" {
+                                            return Ok(te_stack + &format!("It's time for you!"));
+                                        }
+                                        // 下注指定的资金
+                                        match self.post_blind(ID, value) {
+                                            Ok(T) => te_stack.push_str(&T),
+                                            Err(E) => te_stack.push_str(&E),
+                                        }
+                                    }
+                                    Role::Normal => {
+                                        // 已经跟平并且在最后一次加注后说过话的不再行动，行动回到最后加注的人（或大盲、抓头用过选择权）时这一轮就结束了
+                                        if !self.needs_to_act(ID, 0) {
+                                            continue 'outer;
+                                        }
+                                        // 检查是否其他人都放弃或者退出了
+                                        let mut last_player_ID = ID;
+                                        loop {
+                                            if last_player_ID == 0 {
+                                                last_player_ID = player_num - 1;
+                                            } else {
+                                                last_player_ID -= 1;
+                                            }
+                                            if last_player_ID == ID {
+                                                self.game_status = GameStatus::Balancing;
+                                                return Ok(te_stack + &format!("{0} win the game because nobody place a bet!", self.players[ID].name));
+                                            }
+                                            if let Role::PlaceBet|Role::DaMang(..)|Role::XiaoMang(..)|Role::Normal = self.players[last_player_ID].role {
+                                                break;
+                                            }
+                                        }
+                                        // 检查前面第一个不是旁观的角色是不是下注或放弃，如果是就可以下注，否则不能下注
+                                        let mut last_player_ID = ID;
+                                        loop {
+                                            if last_player_ID == 0 {
+                                                last_player_ID = player_num - 1;
+                                            } else {
+                                                last_player_ID -= 1;
+                                            }
+                                            if let Role::PlaceBet|Role::Normal|Role::GiveUp|Role::XiaoMang(..)|Role::DaMang(..) = self.players[last_player_ID].role {
+                                                if let Role::PlaceBet|Role::GiveUp = self.players[last_player_ID].role {
+                                                    break;
+                                                } else {
+                                                    continue 'outer;
+                                                }
+                                            }
+                                        }
+                                        // 检查是不是我
+                                        if player.name == "ME" {
+                                            return Ok(te_stack + &format!("It's time for you!"));
+                                        }
+                                        // 获取需要用于比较的value
+                                        let mut value = 0;
+                                        if let Role::PlaceBet = self.players[last_player_ID].role {
+                                            let players_clone = self.players.clone();
+                                            value = players_clone[last_player_ID].bet_bank.get_values_of_bank();
+                                        } else {
+                                            loop {
+                                                if last_player_ID == 0 {
+                                                    last_player_ID = player_num - 1;
+                                                } else {
+                                                    last_player_ID -= 1;
+                                                }
+                                                if last_player_ID == ID {
+                                                    value = 0;
+                                                    break;
+                                                }
+                                                if let Role::PlaceBet = self.players[last_player_ID].role {
+                                                    let players_clone = self.players.clone();
+                                                    value = players_clone[last_player_ID].bet_bank.get_values_of_bank();
+                                                    break;
+                                                }
+                                            }
+                                        }
+                                        // 根据上一者下注的资金来下注
+                                        match self.bot_place_a_bet(ID, last_player_ID, value, game_status.get_street()) {
+                                            Ok(T) => te_stack.push_str(&T),
+                                            Err(E) => te_stack.push_str(&E),
+                                        }
+                                    }
+                                    _ => {}
+                                }
+                            }
+                            return Ok(te_stack);
+                        },
+                        GameStatus::BetPlacing1 => {
+                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
+                        },
+                        GameStatus::BetPlacing2 => {
+                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
+                        },
+                        GameStatus::BetPlacing3 => {
+                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
+                        },
+                        GameStatus::BetPlacing4 => {
+                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
+                        },
+                        GameStatus::Balancing => {
+                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
+                        },
+                    }
+                },
+                MyEvent::PlaceABet { .. }|MyEvent::PlaceABet_Auto => {
+                    match game_status {
+                        GameStatus::Setting => {
+                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
+                        },
+                        GameStatus::CardsPicking => {
+                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
+                        },
+                        GameStatus::BetPlacing1|GameStatus::BetPlacing2|GameStatus::BetPlacing3|GameStatus::BetPlacing4 => {
+                            match game_status {
+                                GameStatus::BetPlacing1 => self.game_status = GameStatus::BetPlacing2,
+                                GameStatus::BetPlacing2 => self.game_status = GameStatus::BetPlacing3,
+                                GameStatus::BetPlacing3 => self.game_status = GameStatus::BetPlacing4,
+                                GameStatus::BetPlacing4 => self.game_status = GameStatus::Balancing,
+                                _ => {}
+                            }
+
+                            let player_num = self.players.len();
+                            let mut te_stack = String::new();
+                            let ID = 0;
+
+                            let players_clone = self.players.clone();
+                            let bet_bank_backup = players_clone[ID].bet_bank.get_basket_vec();
+                            if let MyEvent::PlaceABet {bet} = event {
+                                // 我自己下注，有成功和失败的可能
+                                match self.players[ID].role {
+                                    Role::XiaoMang(value) => {
+                                        // 下指定的注
+                                        match self.players[ID].place_a_bet_and_check_value(bet,Ordering::Equal,value) {
+                                            Ok(T) => {
+                                                self.record_blind(ID, false);
+                                                te_stack.push_str(&T);
+                                            },
+                                            Err(E) => {
+                                                self.players[ID].place_a_bet(&bet_bank_backup);
+                                                match game_status {
+                                                    GameStatus::BetPlacing1 => self.game_status = GameStatus::BetPlacing1,
+                                                    GameStatus::BetPlacing2 => self.game_status = GameStatus::BetPlacing2,
+                                                    GameStatus::BetPlacing3 => self.game_status = GameStatus::BetPlacing3,
+                                                    GameStatus::BetPlacing4 => self.game_status = GameStatus::BetPlacing4,
+                                                    _ => {}
+                                                }
+                                                te_stack.push_str(&E);
+                                                return Err(te_stack);
+                                            },
+                                        }
+                                    }
+                                    Role::DaMang(value) => {
+                                        // 检查是否其他人都放弃或者退出了
+                                        let mut last_player_ID = ID;
+                                        loop {
+                                            if last_player_ID == 0 {
+                                                last_player_ID = player_num - 1;
+                                            } else {
+                                                last_player_ID -= 1;
+                                            }
+                                            if last_player_ID == ID {
+                                                self.game_status = GameStatus::Balancing;
+                                                return Ok(te_stack + &format!("{0} win the game because nobody place a bet!", self.players[ID].name));
+                                            }
+                                            if let Role::PlaceBet|Role::DaMang(..)|Role::XiaoMang(..)|Role::Normal = self.players[last_player_ID].role {
+                                                break;
+                                            }
+                                        }
+                                        // 检查前面第一个不是旁观的角色是不是下注或放弃，如果是就可以下注，否则不能下注
+                                        let mut last_player_ID = ID;
+                                        loop {
+                                            if last_player_ID == 0 {
+                                                last_player_ID = player_num - 1;
+                                            } else {
+                                                last_player_ID -= 1;
+                                            }
+                                            if let Role::PlaceBet|Role::Normal|Role::GiveUp|Role::XiaoMang(..)|Role::DaMang(..) = self.players[last_player_ID].role {
+                                                if let Role::PlaceBet|Role::GiveUp = self.players[last_player_ID].role {
+                                                    break;
+                                                } else if self.is_XiaoMang_dead() {
+                                                    // 死小盲时大盲直接下
+                                                    break;
+                                                } else {
+                                                    match game_status {
+                                                        GameStatus::BetPlacing1 => self.game_status = GameStatus::BetPlacing1,
+                                                        GameStatus::BetPlacing2 => self.game_status = GameStatus::BetPlacing2,
+                                                        GameStatus::BetPlacing3 => self.game_status = GameStatus::BetPlacing3,
+                                                        GameStatus::BetPlacing4 => self.game_status = GameStatus::BetPlacing4,
+                                                        _ => {}
+                                                    }
+                                                    return Err(te_stack + &"It's not your turn!".to_string());
+                                                }
+                                            }
+                                        }
+                                        // 下指定的注
                                         match self.players[ID].place_a_bet_and_check_value(bet,Ordering::Equal,value) {
+                                            Ok(T) => {
+                                                self.record_blind(ID, true);
+                                                te_stack.push_str(&T);
+                                            },
+                                            Err(E) => {
+                                                self.players[ID].place_a_bet(&bet_bank_backup);
+                                                match game_status {
+                                                    GameStatus::BetPlacing1 => self.game_status = GameStatus::BetPlacing1,
+                                                    GameStatus::BetPlacing2 => self.game_status = GameStatus::BetPlacing2,
+                                                    GameStatus::BetPlacing3 => self.game_status = GameStatus::BetPlacing3,
+                                                    GameStatus::BetPlacing4 => self.game_status = GameStatus::BetPlacing4,
+                                                    _ => {}
+                                                }
+                                                te_stack.push_str(&E);
+                                                return Err(te_stack);
+                                            },
+                                        }
+                                    }
+                                    Role::Normal|Role::PlaceBet => {
+                                        // 检查是否其他人都放弃或者退出了
+                                        let mut last_player_ID = ID;
+                                        loop {
+                                            if last_player_ID == 0 {
+                                                last_player_ID = player_num - 1;
+                                            } else {
+                                                last_player_ID -= 1;
+                                            }
+                                            if last_player_ID == ID {
+                                                self.game_status = GameStatus::Balancing;
+                                                return Ok(te_stack + &format!("{0} win the game because nobody place a bet!", self.players[ID].name));
+                                            }
+                                            if let Role::PlaceBet|Role::DaMang(..)|Role::XiaoMang(..)|Role::Normal = self.players[last_player_ID].role {
+                                                break;
+                                            }
+                                        }
+                                        // 检查前面第一个不是旁观的角色是不是下注或放弃，如果是就可以下注，否则不能下注
+                                        let mut last_player_ID = ID;
+                                        loop {
+                                            if last_player_ID == 0 {
+                                                last_player_ID = player_num - 1;
+                                            } else {
+                                                last_player_ID -= 1;
+                                            }
+                                            if let Role::PlaceBet|Role::Normal|Role::GiveUp|Role::XiaoMang(..)|Role::DaMang(..) = self.players[last_player_ID].role {
+                                                if let Role::PlaceBet|Role::GiveUp = self.players[last_player_ID].role {
+                                                    break;
+                                                } else {
+                                                    match game_status {
+                                                        GameStatus::BetPlacing1 => self.game_status = GameStatus::BetPlacing1,
+                                                        GameStatus::BetPlacing2 => self.game_status = GameStatus::BetPlacing2,
+                                                        GameStatus::BetPlacing3 => self.game_status = GameStatus::BetPlacing3,
+                                                        GameStatus::BetPlacing4 => self.game_status = GameStatus::BetPlacing4,
+                                                        _ => {}
+                                                    }
+                                                    return Err(te_stack + &"It's not your turn!".to_string());
+                                                }
+                                            }
+                                        }
+                                        // 获取需要用于比较的value
+                                        let mut value = 0;
+                                        if let Role::PlaceBet = self.players[last_player_ID].role {
+                                            let players_clone = self.players.clone();
+                                            value = players_clone[last_player_ID].bet_bank.get_values_of_bank();
+                                        } else {
+                                            loop {
+                                                if last_player_ID == 0 {
+                                                    last_player_ID = player_num - 1;
+                                                } else {
+                                                    last_player_ID -= 1;
+                                                }
+                                                if last_player_ID == ID {
+                                                    value = 0;
+                                                    break;
+                                                }
+                                                if let Role::PlaceBet = self.players[last_player_ID].role {
+                                                    let players_clone = self.players.clone();
+                                                    value = players_clone[last_player_ID].bet_bank.get_values_of_bank();
+                                                    break;
+                                                }
+                                            }
+                                        }
+                                        // 下指定的注，要跟到桌上最高的注
+                                        let value = value.max(self.get_level_value());
+                                        let street = game_status.get_street();
+                                        match self.players[ID].place_a_bet_and_check_value(bet,Ordering::Greater,value).and_then(|T| {
+                                            let bet_value = self.players[ID].bet_bank.get_values_of_bank();
+                                            if let Err(E) = self.check_raise(ID, value, street, bet_value) {
+                                                let _ = self.players[ID].get_bets_back();
+                                                return Err(E);
+                                            }
+                                            Ok(T)
+                                        }) {
+                                            Ok(T) => {
+                                                te_stack.push_str(&T);
+                                                let before_value = players_clone[ID].bet_bank.get_values_of_bank();
+                                                self.record_action(ID, game_status.get_street(), before_value, value);
+                                            },
+                                            Err(E) => {
+                                                self.players[ID].place_a_bet(&bet_bank_backup);
+                                                match game_status {
+                                                    GameStatus::BetPlacing1 => self.game_status = GameStatus::BetPlacing1,
+                                                    GameStatus::BetPlacing2 => self.game_status = GameStatus::BetPlacing2,
+                                                    GameStatus::BetPlacing3 => self.game_status = GameStatus::BetPlacing3,
+                                                    GameStatus::BetPlacing4 => self.game_status = GameStatus::BetPlacing4,
+                                                    _ => {}
+                                                }
+                                                te_stack.push_str(&E);
+                                                return Err(te_stack);
+                                            },
+                                        }
+                                    }
+                                    _ => {}
+                                }
+                            }
+                            // 剩下的玩家按位置继续下注
+                            let order = self.get_action_order(game_status.get_street());
+                            let mut game_clone = self.clone();
+                            'outer:for &ID in order.iter() {
+                                let player = &game_clone.players[ID];
+                                if let MyEvent::PlaceABet {..} = event {
+                                    if player.name == "ME" {
+                                        continue;
+                                    }
+                                }
+                                if let GameStatus::Balancing = self.game_status {
+                                    if ID == order[0] {
+                                        return Ok(te_stack + &"Game End Normally!".to_string());
+                                    }
+                                }
+                                match self.players[ID].role {
+                                    Role::XiaoMang(value) => {
+                                        // 下注指定的资金
+                                        match self.post_blind(ID, value) {
+                                            Ok(T) => te_stack.push_str(&T),
+                                            Err(E) => te_stack.push_str(&E),
+                                        }
+                                    }
+                                    Role::DaMang(value) => {
+                                        // 检查是否其他人都放弃或者退出了
+                                        let mut last_player_ID = ID;
+                                        loop {
+                                            if last_player_ID == 0 {
+                                                last_player_ID = player_num - 1;
+                                            } else {
+                                                last_player_ID -= 1;
+                                            }
+                                            if last_player_ID == ID {
+                                                self.game_status = GameStatus::Balancing;
+                                                return Ok(te_stack + &format!("{0} win the game because nobody place a bet!", self.players[ID].name));
+                                            }
+                                            if let Role::PlaceBet|Role::DaMang(..)|Role::XiaoMang(..)|Role::Normal = self.players[last_player_ID].role {
+                                                break;
+                                            }
+                                        }
+                                        // 检查前面第一个不是旁观的角色是不是下注或放弃，如果是就可以下注，否则不能下注
+                                        let mut last_player_ID = ID;
+                                        loop {
+                                            if last_player_ID == 0 {
+                                                last_player_ID = player_num - 1;
+                                            } else {
+                                                last_player_ID -= 1;
+                                            }
+                                            if let Role::PlaceBet|Role::Normal|Role::GiveUp|Role::XiaoMang(..)|Role::DaMang(..) = self.players[last_player_ID].role {
+                                                if let Role::PlaceBet|Role::GiveUp = self.players[last_player_ID].role {
+                                                    break;
+                                                } else {
+                                                    continue 'outer;
+                                                }
+                                            }
+                                        }
+                                        // 下注指定的资金
+                                        match self.post_blind(ID, value) {
+                                            Ok(T) => te_stack.push_str(&T),
+                                            Err(E) => te_stack.push_str(&E),
+                                        }
+                                    }
+                                    Role::Normal|Role::PlaceBet => {
+                                        // 已经跟平并且在最后一次加注后说过话的不再行动，行动回到最后加注的人（或大盲、抓头用过选择权）时这一轮就结束了
+                                        if !self.needs_to_act(ID, game_status.get_street()) {
+                                            continue 'outer;
+                                        }
+                                        // 检查是否其他人都放弃或者退出了
+                                        let mut last_player_ID = ID;
+                                        loop {
+                                            if last_player_ID == 0 {
+                                                last_player_ID = player_num - 1;
+                                            } else {
+                                                last_player_ID -= 1;
+                                            }
+                                            if last_player_ID == ID {
+                                                self.game_status = GameStatus::Balancing;
+                                                return Ok(te_stack + &format!("{0} win the game because nobody place a bet!", self.players[ID].name));
+                                            }
+                                            if let Role::PlaceBet|Role::DaMang(..)|Role::XiaoMang(..)|Role::Normal = self.players[last_player_ID].role {
+                                                break;
+                                            }
+                                        }
+                                        // 检查前面第一个不是旁观的角色是不是下注或放弃，如果是就可以下注，否则不能下注
+                                        let mut last_player_ID = ID;
+                                        loop {
+                                            if last_player_ID == 0 {
+                                                last_player_ID = player_num - 1;
+                                            } else {
+                                                last_player_ID -= 1;
+                                            }
+                                            if let Role::PlaceBet|Role::Normal|Role::GiveUp|Role::XiaoMang(..)|Role::DaMang(..) = self.players[last_player_ID].role {
+                                                if let Role::PlaceBet|Role::GiveUp = self.players[last_player_ID].role {
+                                                    break;
+                                                } else {
+                                                    continue 'outer;
+                                                }
+                                            }
+                                        }
+                                        // 获取需要用于比较的value
+                                        let mut value = 0;
+                                        if let Role::PlaceBet = self.players[last_player_ID].role {
+                                            let players_clone = self.players.clone();
+                                            value = players_clone[last_player_ID].bet_bank.get_values_of_bank();
+                                        } else {
+                                            loop {
+                                                if last_player_ID == 0 {
+                                                    last_player_ID = player_num - 1;
+                                                } else {
+                                                    last_player_ID -= 1;
+                                                }
+                                                if last_player_ID == ID {
+                                                    value = 0;
+                                                    break;
+                                                }
+                                                if let Role::PlaceBet = self.players[last_player_ID].role {
+                                                    let players_clone = self.players.clone();
+                                                    value = players_clone[last_player_ID].bet_bank.get_values_of_bank();
+                                                    break;
+                                                }
+                                            }
+                                        }
+                                        // 根据上一者下注的资金来下注
+                                        match self.bot_place_a_bet(ID, last_player_ID, value, game_status.get_street()) {
+                                            Ok(T) => te_stack.push_str(&T),
+                                            Err(E) => te_stack.push_str(&E),
+                                        }
+                                    }
+                                    _ => {}
+                                }
+                            }
+                            return Ok(te_stack);
+                        },
+                        GameStatus::Balancing => {
+                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
+                        },
+                    }
+                },
+                MyEvent::ConfirmBalance => {
+                    match game_status {
+                        GameStatus::Setting => {
+                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
+                        },
+                        GameStatus::CardsPicking => {
+                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
+                        },
+                        GameStatus::BetPlacing1 => {
+                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
+                        },
+                        GameStatus::Balancing | GameStatus::BetPlacing2 | GameStatus::BetPlacing3 | GameStatus::BetPlacing4 => {
+                            match game_status {
+                                GameStatus::BetPlacing2 | GameStatus::BetPlacing3 | GameStatus::BetPlacing4 => {
+                                    let mut game_clone = self.clone();
+                                    let mut players_left = 0;
+                                    for (ID, player) in game_clone.players.iter().enumerate() {
+                                        if let Role::PlaceBet = player.role {
+                                            players_left += 1;
+                                        }
+                                    }
+                                    if players_left == 1 {
+                                        self.game_status = GameStatus::Balancing;
+                                    }
+                                }
+                                _ => {}
+                            }
+                            let mut te_stack = String::new();
+                            if let GameStatus::Balancing = self.game_status {
+                                if self.five_cards.five_cards.get_basket_vec().len() == 0 {
+                                    return Err("Balancing has been done before!".to_string());
+                                }
+                                // 计算所有还留在场上的玩家的卡牌value
+                                // 最大者赢
+                                let mut winner_ID = 0;
+                                let mut max_value = 0;
+                                let mut max_category = FiveCardsCategory::HighCard;
+
+                                let mut game_clone = self.clone();
+                                for (ID, player) in game_clone.players.iter().enumerate() {
+                                    if let Role::PlaceBet = player.role {
+                                        let (value, category) = player.get_cards_max_value_and_category(&self.five_cards);
+                                        if value > max_value {
+                                            max_value = value;
+                                            max_category = category;
+                                            winner_ID = ID;
+                                        }
+                                    }
+                                }
+                                // 别人都放弃时大盲没有下注，留在场上没放弃的那个人就是赢家
+                                if max_value == 0 {
+                                    if let Some(ID) = self.players.iter().position(|player| !matches!(player.role, Role::GiveUp | Role::Quit)) {
+                                        winner_ID = ID;
+                                    }
+                                }
+                                // 两人以上留到最后就是摊牌
+                                let mut names = Vec::new();
+                                for player in self.players.iter() {
+                                    if let Role::PlaceBet = player.role {
+                                        names.push(player.name.clone());
+                                    }
+                                }
+                                if names.len() >= 2 {
+                                    self.opponent_stats.record_showdown(&names);
+                                    self.advance_street(3);
+                                    let mut showdown = Vec::new();
+                                    for player in self.players.iter() {
+                                        if let Role::PlaceBet = player.role {
+                                            let (_, category) = player.get_cards_max_value_and_category(&self.five_cards);
+                                            let cards = player.get_cards().iter().map(|card| card.get_short_name()).collect();
+                                            showdown.push((player.name.clone(), cards, get_category_name(&category)));
+                                        }
+                                    }
+                                    for (name, cards, category) in showdown {
+                                        self.emit(GameEvent::Showdown { name: name.clone(), cards, category: category.clone() });
+                                        if let Some(history) = self.histories.last_mut() {
+                                            history.showdown.push((name, category));
+                                        }
+                                    }
+                                }
+                                // 将所有玩家的bet输入到cashpool，并还卡
+                                let mut game_clone = self.clone();
+                                for (ID, player) in game_clone.players.iter_mut().enumerate() {
+                                    if self.players[ID].owned_bank.get_values_of_bank() < self.get_XiaoMang_value() {
+                                        match self.players[ID].send_owned_to_pool(&mut self.cash_pool) {
                                             Ok(T) => te_stack.push_str(&T),
-                                            Err(E) => {
-                                                self.players[ID].place_a_bet(&bet_bank_backup);
-                                                match game_status {
-                                                    GameStatus::BetPlacing1 => self.game_status = GameStatus::BetPlacing1,
-                                                    GameStatus::BetPlacing2 => self.game_status = GameStatus::BetPlacing2,
-                                                    GameStatus::BetPlacing3 => self.game_status = GameStatus::BetPlacing3,
-                                                    GameStatus::BetPlacing4 => self.game_status = GameStatus::BetPlacing4,
-                                                    _ => {}
-                                                }
-                                                te_stack.push_str(&E);
-                                                return Err(te_stack);
-                                            },
+                                            Err(E) => te_stack.push_str(&E),
+                                        }
+                                    }
+                                    match self.players[ID].send_bets_to_pool(&mut self.cash_pool) {
+                                        Ok(T) => te_stack.push_str(&T),
+                                        Err(E) => te_stack.push_str(&E),
+                                    }
+                                    match self.players[ID].send_cards_back(&mut self.card_pool) {
+                                        Ok(T) => te_stack.push_str(&T),
+                                        Err(E) => te_stack.push_str(&E),
+                                    }
+                                }
+
+                                // 派奖前先抽水
+                                self.take_rake();
+                                // 将cashpool输入到赢家
+                                let award = (self.players[winner_ID].name.clone(), self.cash_pool.cash_pool.get_values_of_bank());
+                                self.emit(GameEvent::PotAwarded { name: award.0.clone(), value: award.1 });
+                                if let Some(history) = self.histories.last_mut() {
+                                    history.awards.push(award);
+                                }
+                                match self.players[winner_ID].get_bets_from_pool(&mut self.cash_pool) {
+                                    Ok(T) => te_stack.push_str(&T),
+                                    Err(E) => te_stack.push_str(&E),
+                                }
+                                // 把五卡还回去
+                                match self.five_cards.send_cards_back(&mut self.card_pool) {
+                                    Ok(T) => te_stack.push_str(&T),
+                                    Err(E) => te_stack.push_str(&E),
+                                }
+                                // owned_bank为空的玩家设置为quit
+                                let mut game_clone = self.clone();
+                                for (ID, player) in game_clone.players.iter().enumerate() {
+                                    if player.owned_bank.get_values_of_bank() == 0 {
+                                        if !matches!(player.role, Role::Quit) {
+                                            self.emit(GameEvent::PlayerBusted { name: player.name.clone() });
+                                        }
+                                        self.players[ID].role = Role::Quit;
+                                    }
+                                }
+                                return Ok(format!("{0} is the Winner of this game! He/She gets the cards group of {1}!", self.players[winner_ID].name, max_category) + &te_stack);
+                            }
+                            return Err(te_stack);
+                        },
+                    }
+                }
+                MyEvent::GiveUp => {
+                    match game_status {
+                        GameStatus::Setting => {
+                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
+                        },
+                        GameStatus::CardsPicking => {
+                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
+                        },
+                        GameStatus::BetPlacing1|GameStatus::BetPlacing2|GameStatus::BetPlacing3|GameStatus::BetPlacing4 => {
+                            match game_status {
+                                GameStatus::BetPlacing1 => self.game_status = GameStatus::BetPlacing2,
+                                GameStatus::BetPlacing2 => self.game_status = GameStatus::BetPlacing3,
+                                GameStatus::BetPlacing3 => self.game_status = GameStatus::BetPlacing4,
+                                GameStatus::BetPlacing4 => self.game_status = GameStatus::Balancing,
+                                _ => {}
+                            }
+                            let player_num = self.players.len();
+                            let mut te_stack = String::new();
+                            let ID = 0;
+
+                            // 我放弃
+                            self.players[ID].role = Role::GiveUp;
+                            self.record_action(ID, game_status.get_street(), 0, 0);
+                            // 剩下的玩家按位置继续下注
+                            let order = self.get_action_order(game_status.get_street());
+                            let mut game_clone = self.clone();
+                            'outer:for &ID in order.iter() {
+                                let player = &game_clone.players[ID];
+                                if let MyEvent::PlaceABet {..} = event {
+                                    if player.name == "ME" {
+                                        continue;
+                                    }
+                                }
+                                if let GameStatus::Balancing = self.game_status {
+                                    if ID == order[0] {
+                                        return Ok(te_stack + &"Game End Normally!".to_string());
+                                    }
+                                }
+                                match self.players[ID].role {
+                                    Role::XiaoMang(value) => {
+                                        // 下注指定的资金
+                                        match self.post_blind(ID, value) {
+                                            Ok(T) => te_stack.push_str(&T),
+                                            Err(E) => te_stack.push_str(&E),
                                         }
                                     }
                                     Role::DaMang(value) => {
@@ -1607,35 +5885,21 @@ mod module_game {
                                                 if let Role::PlaceBet|Role::GiveUp = self.players[last_player_ID].role {
                                                     break;
                                                 } else {
-                                                    match game_status {
-                                                        GameStatus::BetPlacing1 => self.game_status = GameStatus::BetPlacing1,
-                                                        GameStatus::BetPlacing2 => self.game_status = GameStatus::BetPlacing2,
-                                                        GameStatus::BetPlacing3 => self.game_status = GameStatus::BetPlacing3,
-                                                        GameStatus::BetPlacing4 => self.game_status = GameStatus::BetPlacing4,
-                                                        _ => {}
-                                                    }
-                                                    return Err(te_stack + &"It's not your turn!".to_string());
+                                                    continue 'outer;
                                                 }
                                             }
                                         }
-                                        // 下指定的注
-                                        match self.players[ID].place_a_bet_and_check_value(bet,Ordering::Equal,value) {
+                                        // 下注指定的资金
+                                        match self.post_blind(ID, value) {
                                             Ok(T) => te_stack.push_str(&T),
-                                            Err(E) => {
-                                                self.players[ID].place_a_bet(&bet_bank_backup);
-                                                match game_status {
-                                                    GameStatus::BetPlacing1 => self.game_status = GameStatus::BetPlacing1,
-                                                    GameStatus::BetPlacing2 => self.game_status = GameStatus::BetPlacing2,
-                                                    GameStatus::BetPlacing3 => self.game_status = GameStatus::BetPlacing3,
-                                                    GameStatus::BetPlacing4 => self.game_status = GameStatus::BetPlacing4,
-                                                    _ => {}
-                                                }
-                                                te_stack.push_str(&E);
-                                                return Err(te_stack);
-                                            },
+                                            Err(E) => te_stack.push_str(&E),
                                         }
                                     }
                                     Role::Normal|Role::PlaceBet => {
+                                        // 已经跟平并且在最后一次加注后说过话的不再行动，行动回到最后加注的人（或大盲、抓头用过选择权）时这一轮就结束了
+                                        if !self.needs_to_act(ID, game_status.get_street()) {
+                                            continue 'outer;
+                                        }
                                         // 检查是否其他人都放弃或者退出了
                                         let mut last_player_ID = ID;
                                         loop {
@@ -1664,14 +5928,7 @@ mod module_game {
                                                 if let Role::PlaceBet|Role::GiveUp = self.players[last_player_ID].role {
                                                     break;
                                                 } else {
-                                                    match game_status {
-                                                        GameStatus::BetPlacing1 => self.game_status = GameStatus::BetPlacing1,
-                                                        GameStatus::BetPlacing2 => self.game_status = GameStatus::BetPlacing2,
-                                                        GameStatus::BetPlacing3 => self.game_status = GameStatus::BetPlacing3,
-                                                        GameStatus::BetPlacing4 => self.game_status = GameStatus::BetPlacing4,
-                                                        _ => {}
-                                                    }
-                                                    return Err(te_stack + &"It's not your turn!".to_string());
+                                                    continue 'outer;
                                                 }
                                             }
                                         }
@@ -1698,526 +5955,3917 @@ mod module_game {
                                                 }
                                             }
                                         }
-                                        // 下指定的注
-                                        match self.players[ID].place_a_bet_and_check_value(bet,Ordering::Greater,value) {
+                                        // 根据上一者下注的资金来下注
+                                        match self.bot_place_a_bet(ID, last_player_ID, value, game_status.get_street()) {
                                             Ok(T) => te_stack.push_str(&T),
-                                            Err(E) => {
-                                                self.players[ID].place_a_bet(&bet_bank_backup);
-                                                match game_status {
-                                                    GameStatus::BetPlacing1 => self.game_status = GameStatus::BetPlacing1,
-                                                    GameStatus::BetPlacing2 => self.game_status = GameStatus::BetPlacing2,
-                                                    GameStatus::BetPlacing3 => self.game_status = GameStatus::BetPlacing3,
-                                                    GameStatus::BetPlacing4 => self.game_status = GameStatus::BetPlacing4,
-                                                    _ => {}
-                                                }
-                                                te_stack.push_str(&E);
-                                                return Err(te_stack);
-                                            },
+                                            Err(E) => te_stack.push_str(&E),
                                         }
                                     }
                                     _ => {}
                                 }
                             }
-                            // 剩下的玩家继续下注
-                            let mut game_clone = self.clone();
-                            'outer:for (ID, player) in game_clone.players.iter().enumerate() {
-                                if let MyEvent::PlaceABet {..} = event {
-                                    if player.name == "ME" {
-                                        continue;
-                                    }
-                                }
-                                if let GameStatus::Balancing = self.game_status {
-                                    if ID == self.last_XiaoMang_ID as usize {
-                                        return Ok(te_stack + &"Game End Normally!".to_string());
-                                    }
-                                }
-                                match self.players[ID].role {
-                                    Role::XiaoMang(value) => {
-                                        // 下注指定的资金
-                                        match self.players[ID].place_a_bet_with_value(value) {
-                                            Ok(T) => te_stack.push_str(&T),
-                                            Err(E) => te_stack.push_str(&E),
-                                        }
-                                    }
-                                    Role::DaMang(value) => {
-                                        // 检查是否其他人都放弃或者退出了
-                                        let mut last_player_ID = ID;
-                                        loop {
-                                            if last_player_ID == 0 {
-                                                last_player_ID = player_num - 1;
-                                            } else {
-                                                last_player_ID -= 1;
-                                            }
-                                            if last_player_ID == ID {
-                                                self.game_status = GameStatus::Balancing;
-                                                return Ok(te_stack + &format!("{0} win the game because nobody place a bet!", self.players[ID].name));
-                                            }
-                                            if let Role::PlaceBet|Role::DaMang(..)|Role::XiaoMang(..)|Role::Normal = self.players[last_player_ID].role {
-                                                break;
-                                            }
-                                        }
-                                        // 检查前面第一个不是旁观的角色是不是下注或放弃，如果是就可以下注，否则不能下注
-                                        let mut last_player_ID = ID;
-                                        loop {
-                                            if last_player_ID == 0 {
-                                                last_player_ID = player_num - 1;
-                                            } else {
-                                                last_player_ID -= 1;
-                                            }
-                                            if let Role::PlaceBet|Role::Normal|Role::GiveUp|Role::XiaoMang(..)|Role::DaMang(..) = self.players[last_player_ID].role {
-                                                if let Role::PlaceBet|Role::GiveUp = self.players[last_player_ID].role {
-                                                    break;
-                                                } else {
-                                                    continue 'outer;
-                                                }
-                                            }
-                                        }
-                                        // 下注指定的资金
-                                        match self.players[ID].place_a_bet_with_value(value) {
-                                            Ok(T) => te_stack.push_str(&T),
-                                            Err(E) => te_stack.push_str(&E),
-                                        }
-                                    }
-                                    Role::Normal|Role::PlaceBet => {
-                                        // 检查是否其他人都放弃或者退出了
-                                        let mut last_player_ID = ID;
-                                        loop {
-                                            if last_player_ID == 0 {
-                                                last_player_ID = player_num - 1;
-                                            } else {
-                                                last_player_ID -= 1;
-                                            }
-                                            if last_player_ID == ID {
-                                                self.game_status = GameStatus::Balancing;
-                                                return Ok(te_stack + &format!("{0} win the game because nobody place a bet!", self.players[ID].name));
-                                            }
-                                            if let Role::PlaceBet|Role::DaMang(..)|Role::XiaoMang(..)|Role::Normal = self.players[last_player_ID].role {
-                                                break;
-                                            }
-                                        }
-                                        // 检查前面第一个不是旁观的角色是不是下注或放弃，如果是就可以下注，否则不能下注
-                                        let mut last_player_ID = ID;
-                                        loop {
-                                            if last_player_ID == 0 {
-                                                last_player_ID = player_num - 1;
-                                            } else {
-                                                last_player_ID -= 1;
-                                            }
-                                            if let Role::PlaceBet|Role::Normal|Role::GiveUp|Role::XiaoMang(..)|Role::DaMang(..) = self.players[last_player_ID].role {
-                                                if let Role::PlaceBet|Role::GiveUp = self.players[last_player_ID].role {
-                                                    break;
-                                                } else {
-                                                    continue 'outer;
-                                                }
-                                            }
-                                        }
-                                        // 获取需要用于比较的value
-                                        let mut value = 0;
-                                        if let Role::PlaceBet = self.players[last_player_ID].role {
-                                            let players_clone = self.players.clone();
-                                            value = players_clone[last_player_ID].bet_bank.get_values_of_bank();
-                                        } else {
-                                            loop {
-                                                if last_player_ID == 0 {
-                                                    last_player_ID = player_num - 1;
-                                                } else {
-                                                    last_player_ID -= 1;
-                                                }
-                                                if last_player_ID == ID {
-                                                    value = 0;
-                                                    break;
-                                                }
-                                                if let Role::PlaceBet = self.players[last_player_ID].role {
-                                                    let players_clone = self.players.clone();
-                                                    value = players_clone[last_player_ID].bet_bank.get_values_of_bank();
-                                                    break;
-                                                }
-                                            }
-                                        }
-                                        // 根据上一者下注的资金来下注
-                                        let game_clone = self.clone();
-                                        match self.players[ID].place_a_bet_with_last_value(value,game_clone.get_max_bet_value(),game_clone.min_value_unit) {
-                                            Ok(T) => te_stack.push_str(&T),
-                                            Err(E) => te_stack.push_str(&E),
-                                        }
-                                    }
-                                    _ => {}
-                                }
+                            return Ok(te_stack);
+                        },
+                        GameStatus::Balancing => {
+                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
+                        },
+                    }
+                },
+            }
+        }
+    }
+
+    /// 给Game实现Display特性
+    impl Display for Game {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "游戏状态：{0}\n", self.game_status)?;
+            write!(f, "小盲下注：{0}\n", self.get_XiaoMang_value())?;
+            write!(f, "大盲下注：{0}\n", self.get_DaMang_value())?;
+            write!(f, "最大下注：{0}\n", self.get_max_bet_value())?;
+            write!(f, "玩家情况：\n")?;
+            for player in self.players.iter() {
+                write!(f, "{0}\n", player)?;
+            }
+            write!(f, "钱池情况：{0}\n", self.cash_pool)?;
+            write!(f, "卡组情况：{0}\n", self.card_pool)?;
+            write!(f, "五卡情况：{0}\n", self.five_cards)
+        }
+    }
+
+    impl GameStatus {
+        /// 当前下注轮次：0翻前，1翻牌，2转牌，3河牌
+        pub fn get_street(&self) -> usize {
+            match self {
+                GameStatus::BetPlacing2 => 1,
+                GameStatus::BetPlacing3 => 2,
+                GameStatus::BetPlacing4 => 3,
+                _ => 0,
+            }
+        }
+    }
+
+    /// 给状态机实现Display特性
+    impl Display for GameStatus {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                GameStatus::Setting => write!(f, "等待玩家设置/等待玩家开始游戏"),
+                GameStatus::CardsPicking => write!(f, "等待玩家抽卡"),
+                GameStatus::BetPlacing1 => write!(f, "等待玩家第一轮下注"),
+                GameStatus::BetPlacing2 => write!(f, "等待玩家第二轮下注"),
+                GameStatus::BetPlacing3 => write!(f, "等待玩家第三轮下注"),
+                GameStatus::BetPlacing4 => write!(f, "等待玩家第四轮下注"),
+                GameStatus::Balancing => write!(f, "等待玩家结算/已结算，等待玩家开始游戏")
+            }
+        }
+    }
+
+    /// 给事件实现Display特性
+    impl Display for MyEvent<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                MyEvent::AddBot { num, .. } => write!(f, "【添加{0}个机器人】", num),
+                MyEvent::ResetGame { .. } => write!(f, "【重新初始化游戏】"),
+                MyEvent::StartNextGame => write!(f, "【开始下一轮游戏】"),
+                MyEvent::PlaceABet { .. } => write!(f, "【下注】"),
+                MyEvent::PlaceABet_Auto => write!(f, "【自动下注】"),
+                MyEvent::PickCards => write!(f, "【抽卡】"),
+                MyEvent::ConfirmBalance => write!(f, "【确定结算】"),
+                MyEvent::GiveUp => write!(f, "【弃卡】"),
+                MyEvent::Undo { steps } => write!(f, "【悔{0}步】", steps),
+                MyEvent::Straddle { seat } => write!(f, "【{0}号座位抓头】", seat + 1),
+                MyEvent::JoinSeat { name, buy_in } => write!(f, "【{0}买入{1}入座】", name, buy_in),
+                MyEvent::SitOut { seat, .. } => write!(f, "【{0}号座位坐出去】", seat + 1),
+                MyEvent::SitIn { seat } => write!(f, "【{0}号座位坐回来】", seat + 1),
+                MyEvent::Rebuy { seat, value } => write!(f, "【{0}号座位重新买入{1}】", seat + 1, value),
+                MyEvent::TopUp { seat } => write!(f, "【{0}号座位补码】", seat + 1),
+                MyEvent::CashOut { seat } => write!(f, "【{0}号座位兑现离桌】", seat + 1),
+            }
+        }
+    }
+}
+
+///玩家和旁观者能看到的游戏信息：别人的底牌到摊牌才亮出来，卡组顺序永远看不到
+mod module_view {
+    use std::fmt;
+    use std::fmt::Display;
+    use serde::Serialize;
+    use crate::module_game::{Game, GameStatus};
+    use crate::module_player::Role;
+
+    /// 座位上能看到的信息，hole_cards只有摊牌时或者是自己的座位才有
+    #[derive(Clone, Debug, Serialize)]
+    pub struct SeatView {
+        pub seat: usize,
+        pub name: String,
+        pub role: String,
+        /// 这个座位这一手没人打：空座、已经出局或者坐出
+        pub is_empty: bool,
+        /// 玩家暂时坐出
+        pub sitting_out: bool,
+        pub stack: i32,
+        pub bet: i32,
+        pub hole_cards: Option<Vec<String>>,
+    }
+
+    /// 旁观者看到的牌桌
+    #[derive(Clone, Debug, Serialize)]
+    pub struct SpectatorView {
+        pub hand_id: u64,
+        pub status: String,
+        /// 下注轮次：0翻前，1翻牌，2转牌，3河牌
+        pub street: usize,
+        /// 已经翻开的公共牌
+        pub board: Vec<String>,
+        pub pot_value: i32,
+        pub small_blind: i32,
+        pub big_blind: i32,
+        pub ante: i32,
+        pub max_bet_value: i32,
+        /// 庄家的座位
+        pub button: usize,
+        /// 卡组里还剩几张，不给顺序
+        pub cards_left: usize,
+        /// 这一手在打的座位按翻前说话的顺序排好，大盲最后；死庄死小盲时也不会错位
+        pub preflop_order: Vec<usize>,
+        pub seats: Vec<SeatView>,
+    }
+
+    /// 某个玩家看到的牌桌，多了自己的底牌
+    #[derive(Clone, Debug, Serialize)]
+    pub struct PlayerView {
+        pub viewer: String,
+        pub seat: usize,
+        pub hole_cards: Vec<String>,
+        pub table: SpectatorView,
+    }
+
+    /// 已经翻开几张公共牌
+    fn get_visible_board_num(game: &Game) -> usize {
+        match game.game_status {
+            GameStatus::BetPlacing2 => 3,
+            GameStatus::BetPlacing3 => 4,
+            GameStatus::BetPlacing4 => 5,
+            GameStatus::Balancing if is_showdown(game) => 5,
+            GameStatus::Balancing => match game.histories.last() {
+                Some(history) => match history.actions.iter().map(|entry| entry.street).max().unwrap_or(0) {
+                    0 => 0,
+                    street => street + 2,
+                },
+                None => 0,
+            },
+            _ => 0,
+        }
+    }
+
+    /// 结算时两人以上留在场上就是摊牌
+    fn is_showdown(game: &Game) -> bool {
+        matches!(game.game_status, GameStatus::Balancing) && game.players.iter().filter(|player| matches!(player.role, Role::PlaceBet)).count() >= 2
+    }
+
+    impl Game {
+        /// 旁观者视角
+        pub fn get_spectator_view(&self) -> SpectatorView {
+            let showdown = is_showdown(self);
+            let mut seats = Vec::new();
+            for (ID, player) in self.players.iter().enumerate() {
+                let hole_cards = if showdown && matches!(player.role, Role::PlaceBet) {
+                    Some(player.get_cards().iter().map(|card| card.get_short_name()).collect())
+                } else {
+                    None
+                };
+                seats.push(SeatView {
+                    seat: ID,
+                    name: player.name.clone(),
+                    role: player.role.to_string(),
+                    is_empty: matches!(player.role, Role::Quit),
+                    sitting_out: player.sitting_out,
+                    stack: player.owned_bank.get_values_of_bank(),
+                    bet: player.bet_bank.get_values_of_bank(),
+                    hole_cards,
+                });
+            }
+            let board_num = get_visible_board_num(self).min(self.five_cards.board_order.len());
+            let mut preflop_order: Vec<usize> = self.get_action_order(0).into_iter().filter(|ID| !seats[*ID].is_empty).collect();
+            if let Some(index) = preflop_order.iter().position(|ID| *ID == self.last_DaMang_ID as usize) {
+                preflop_order.rotate_left(index + 1);
+            }
+            SpectatorView {
+                hand_id: self.histories.len() as u64,
+                status: self.game_status.to_string(),
+                street: self.game_status.get_street(),
+                board: self.five_cards.board_order[..board_num].iter().map(|card| card.get_short_name()).collect(),
+                pot_value: self.get_pot_value(),
+                small_blind: self.get_XiaoMang_value(),
+                big_blind: self.get_DaMang_value(),
+                ante: self.get_ante_value(),
+                max_bet_value: self.get_max_bet_value(),
+                button: self.button_ID as usize,
+                cards_left: self.card_pool.card_pool.get_basket_vec().len(),
+                preflop_order,
+                seats,
+            }
+        }
+        /// 某个座位上的玩家的视角
+        pub fn get_player_view(&self, ID: usize) -> Result<PlayerView, String> {
+            let player = self.players.get(ID).ok_or(format!("Fail to find the player at seat {0}!", ID))?;
+            let hole_cards: Vec<String> = player.get_cards().iter().map(|card| card.get_short_name()).collect();
+            let mut table = self.get_spectator_view();
+            table.seats[ID].hole_cards = Some(hole_cards.clone());
+            Ok(PlayerView {
+                viewer: player.name.clone(),
+                seat: ID,
+                hole_cards,
+                table,
+            })
+        }
+    }
+
+    /// 给旁观者视角实现Display特性
+    impl Display for SpectatorView {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "游戏状态：{0}\n", self.status)?;
+            write!(f, "盲注：{0}/{1}，前注：{4}，最大下注：{2}，底池：{3}\n", self.small_blind, self.big_blind, self.max_bet_value, self.pot_value, self.ante)?;
+            write!(f, "公共牌：{0}\n", self.board.join(" "))?;
+            for seat in self.seats.iter() {
+                let cards = match &seat.hole_cards {
+                    Some(cards) => cards.join(" "),
+                    None => "??".to_string(),
+                };
+                let button = if seat.seat == self.button { "【庄】" } else { "" };
+                write!(f, "{0}（{1}）{5}：剩余{2}$，下注{3}$，底牌{4}\n", seat.name, seat.role, seat.stack, seat.bet, cards, button)?;
+            }
+            Ok(())
+        }
+    }
+
+    /// 给玩家视角实现Display特性
+    impl Display for PlayerView {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "我是{0}，我的底牌：{1}\n", self.viewer, self.hole_cards.join(" "))?;
+            write!(f, "{0}", self.table)
+        }
+    }
+}
+
+///机器人对战竞技场：用固定种子批量运行对局，轮换座位并复式发牌，统计胜率和Elo排名
+mod module_arena {
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+    use std::fmt;
+    use std::fmt::Display;
+    use std::rc::Rc;
+    use crate::module_bank::StuffType;
+    use crate::module_bot::{BotProfile, ProfileStrategy, Strategy};
+    use crate::module_game::{Game, GameStatus, MyEvent};
+    use crate::module_player::Role;
+
+    /// 根据种子生成策略的工厂
+    pub type StrategyFactory = Box<dyn Fn(u64) -> Rc<RefCell<dyn Strategy>>>;
+
+    /// 参赛的策略
+    pub struct ArenaEntry {
+        pub name: String,
+        factory: StrategyFactory,
+    }
+
+    /// 排行榜上的一行
+    #[derive(Clone, Debug)]
+    pub struct Standing {
+        pub name: String,
+        pub hands: usize,
+        /// 每百手赢的大盲数
+        pub bb_per_100: f64,
+        /// 95%置信区间的半宽，按每组发牌在所有座位轮换上的平均结果算
+        pub ci95: f64,
+        pub elo: f64,
+    }
+
+    /// 一次竞技的结果
+    #[derive(Clone, Debug)]
+    pub struct ArenaReport {
+        pub standings: Vec<Standing>,
+    }
+
+    /// 竞技场
+    pub struct Arena {
+        entries: Vec<ArenaEntry>,
+        pub seed: u64,
+        /// 每组发牌种子会让所有策略轮换所有座位各打一次
+        pub sessions: usize,
+        pub hands_per_session: usize,
+        pub starting_assets: Vec<(StuffType, i32)>,
+        pub elo_k: f64,
+        /// 跨多次运行保留的Elo分
+        pub ratings: HashMap<String, f64>,
+    }
+
+    const INITIAL_ELO: f64 = 1500.0;
+
+    /// 由两个数混合出新的种子
+    pub fn mix_seed(a: u64, b: u64) -> u64 {
+        let mut x = a ^ b.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        x ^ (x >> 31)
+    }
+
+    impl Arena {
+        pub fn new(seed: u64) -> Arena {
+            Arena {
+                entries: Vec::new(),
+                seed,
+                sessions: 10,
+                hands_per_session: 20,
+                starting_assets: vec![(StuffType::GeneralType("筹码", 5), 200)],
+                elo_k: 16.0,
+                ratings: HashMap::new(),
+            }
+        }
+        /// 添加一个策略，名字要唯一
+        pub fn add_strategy(&mut self, name: &str, factory: StrategyFactory) -> Result<String, String> {
+            if self.entries.iter().any(|entry| entry.name == name) {
+                return Err(format!("Fail to add strategy \"{0}\" because the name is used!", name));
+            }
+            if self.entries.len() >= 10 {
+                return Err(format!("Fail to add strategy \"{0}\" because there are too many strategies!", name));
+            }
+            self.entries.push(ArenaEntry { name: name.to_string(), factory });
+            Ok(format!("Succeed to add strategy \"{0}\"!", name))
+        }
+        /// 添加一个按性格打牌的策略
+        pub fn add_profile(&mut self, profile: BotProfile) -> Result<String, String> {
+            let name = profile.name.clone();
+            self.add_strategy(&name, Box::new(move |seed| Rc::new(RefCell::new(ProfileStrategy::new(profile.clone(), seed)))))
+        }
+
+        /// 运行所有对局，返回排行榜
+        pub fn run(&mut self) -> Result<ArenaReport, String> {
+            let num = self.entries.len();
+            if num < 2 {
+                return Err("At least 2 strategies are needed!".to_string());
+            }
+            for entry in self.entries.iter() {
+                self.ratings.entry(entry.name.clone()).or_insert(INITIAL_ELO);
+            }
+            // 每组发牌上每个策略平均每手赢的大盲数，复式发牌的方差在这里抵消掉
+            let mut results: Vec<Vec<f64>> = vec![Vec::new(); num];
+            let mut hands = vec![0; num];
+            for session in 0..self.sessions {
+                let session_seed = mix_seed(self.seed, session as u64);
+                let mut won = vec![0.0; num];
+                let mut played = vec![0; num];
+                // 复式发牌：同一组牌让每个策略都坐一遍每个座位
+                for rotation in 0..num {
+                    let seats: Vec<usize> = (0..num).map(|seat| (seat + rotation) % num).collect();
+                    let nets = self.play_session(session_seed, &seats, &mut won, &mut played)?;
+                    self.update_elo(&nets);
+                }
+                for index in 0..num {
+                    if played[index] > 0 {
+                        results[index].push(won[index] / played[index] as f64);
+                        hands[index] += played[index];
+                    }
+                }
+            }
+            let mut standings = Vec::new();
+            for (index, entry) in self.entries.iter().enumerate() {
+                let hands = hands[index];
+                let (mean, ci95) = get_mean_and_ci95(&results[index]);
+                standings.push(Standing {
+                    name: entry.name.clone(),
+                    hands,
+                    bb_per_100: mean * 100.0,
+                    ci95: ci95 * 100.0,
+                    elo: self.ratings[&entry.name],
+                });
+            }
+            standings.sort_by(|a, b| b.elo.partial_cmp(&a.elo).unwrap());
+            Ok(ArenaReport { standings })
+        }
+
+        /// 打一局，seats[座位] = 策略序号，把每个策略赢的大盲数和打的手数加到won和played上，返回每个策略净赢的筹码
+        fn play_session(&self, session_seed: u64, seats: &Vec<usize>, won: &mut Vec<f64>, played: &mut Vec<usize>) -> Result<Vec<i32>, String> {
+            let names: Vec<String> = seats.iter().map(|index| self.entries[*index].name.clone()).collect();
+            let mut game = Game::new_with_players(&names)?;
+            for (seat, index) in seats.iter().enumerate() {
+                let strategy = (self.entries[*index].factory)(mix_seed(session_seed, seat as u64 + 100));
+                game.set_strategy(&names[seat], strategy);
+            }
+            let assets: Vec<(&StuffType, i32)> = self.starting_assets.iter().map(|item| (&item.0, item.1)).collect();
+            game.receive_my_event(MyEvent::ResetGame { assets: &assets })?;
+            // 盲注在开局时定下来，不随筹码变动，bb/100的单位整局都一样
+            let big_blind = game.get_DaMang_value().max(1);
+            game.config.small_blind = Some(game.get_XiaoMang_value());
+            game.config.big_blind = Some(big_blind);
+            game.assign_blinds(0);
+            let get_totals = |game: &Game| -> Vec<i32> {
+                game.players.iter().map(|player| player.owned_bank.get_values_of_bank() + player.bet_bank.get_values_of_bank()).collect()
+            };
+            let starting = get_totals(&game);
+            for hand in 0..self.hands_per_session {
+                let players_left = game.players.iter().filter(|player| !matches!(player.role, Role::Quit)).count();
+                if players_left <= 1 {
+                    break;
+                }
+                let before = get_totals(&game);
+                play_hand(&mut game, mix_seed(session_seed, hand as u64))?;
+                let after = get_totals(&game);
+                for (seat, index) in seats.iter().enumerate() {
+                    if before[seat] > 0 {
+                        won[*index] += (after[seat] - before[seat]) as f64 / big_blind as f64;
+                        played[*index] += 1;
+                    }
+                }
+            }
+            let ending = get_totals(&game);
+            let mut nets = vec![0; seats.len()];
+            for (seat, index) in seats.iter().enumerate() {
+                nets[*index] = ending[seat] - starting[seat];
+            }
+            Ok(nets)
+        }
+
+        /// 每两个策略之间按净赢筹码比较一次，同时更新Elo分
+        fn update_elo(&mut self, nets: &Vec<i32>) {
+            let names: Vec<String> = self.entries.iter().map(|entry| entry.name.clone()).collect();
+            let mut deltas = vec![0.0; names.len()];
+            for i in 0..names.len() {
+                for j in (i + 1)..names.len() {
+                    let (ri, rj) = (self.ratings[&names[i]], self.ratings[&names[j]]);
+                    let expected = 1.0 / (1.0 + 10f64.powf((rj - ri) / 400.0));
+                    let score = if nets[i] > nets[j] { 1.0 } else if nets[i] < nets[j] { 0.0 } else { 0.5 };
+                    deltas[i] += self.elo_k * (score - expected);
+                    deltas[j] -= self.elo_k * (score - expected);
+                }
+            }
+            for (name, delta) in names.iter().zip(deltas.iter()) {
+                if let Some(rating) = self.ratings.get_mut(name) {
+                    *rating += delta;
+                }
+            }
+        }
+    }
+
+    /// 无界面地打完一手牌，发牌顺序由种子决定
+    pub fn play_hand(game: &mut Game, deal_seed: u64) -> Result<String, String> {
+        let mut te_stack = String::new();
+        if let GameStatus::Setting | GameStatus::Balancing = game.game_status {
+            te_stack.push_str(&game.receive_my_event(MyEvent::StartNextGame)?);
+        }
+        if let GameStatus::CardsPicking = game.game_status {
+            game.card_pool.shuffle_with_seed(deal_seed);
+            te_stack.push_str(&game.receive_my_event(MyEvent::PickCards)?);
+        }
+        for _ in 0..4 {
+            if let GameStatus::Balancing = game.game_status {
+                break;
+            }
+            te_stack.push_str(&game.receive_my_event(MyEvent::PlaceABet_Auto)?);
+        }
+        match game.receive_my_event(MyEvent::ConfirmBalance) {
+            Ok(T) => te_stack.push_str(&T),
+            Err(E) => te_stack.push_str(&E),
+        }
+        Ok(te_stack)
+    }
+
+    /// 平均值和95%置信区间的半宽
+    fn get_mean_and_ci95(values: &Vec<f64>) -> (f64, f64) {
+        let n = values.len() as f64;
+        if values.len() < 2 {
+            return (values.iter().sum::<f64>() / n.max(1.0), f64::INFINITY);
+        }
+        let mean = values.iter().sum::<f64>() / n;
+        let variance = values.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>() / (n - 1.0);
+        (mean, 1.96 * (variance / n).sqrt())
+    }
+
+    /// 给排行榜实现Display特性
+    impl Display for ArenaReport {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            for (rank, standing) in self.standings.iter().enumerate() {
+                writeln!(f, "{0}. {1}：Elo {2:.0}，{3:.1} ± {4:.1} bb/100（{5}手）",
+                         rank + 1, standing.name, standing.elo, standing.bb_per_100, standing.ci95, standing.hands)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+///独立筹码模型ICM：按筹码和奖金算每个人的奖金期望，以及两个人全下时按ICM算的弃牌、全下和跟注的期望
+mod module_icm {
+    use std::collections::HashMap;
+
+    /// 最多能算的人数
+    pub const MAX_ICM_PLAYERS: usize = 20;
+
+    /// 每个人的奖金期望：按Malmuth-Harville模型，每个名次都由还没排上名次的人按筹码比例抽出，
+    /// prizes是还没发出去的奖金，从第一名往下排，没有筹码的人期望是0
+    pub fn get_icm_equities(stacks: &Vec<i32>, prizes: &Vec<i32>) -> Result<Vec<f64>, String> {
+        let players: Vec<usize> = (0..stacks.len()).filter(|ID| stacks[*ID] > 0).collect();
+        if players.len() > MAX_ICM_PLAYERS {
+            return Err(format!("Cannot calculate ICM for more than {0} players!", MAX_ICM_PLAYERS));
+        }
+        if stacks.iter().any(|stack| *stack < 0) {
+            return Err("The stacks cannot be negative!".to_string());
+        }
+        let chips: Vec<f64> = players.iter().map(|ID| stacks[*ID] as f64).collect();
+        let prizes: Vec<f64> = prizes.iter().map(|prize| *prize as f64).collect();
+        let mut memo = HashMap::new();
+        let mask = if players.is_empty() { 0 } else { u32::MAX >> (32 - players.len()) };
+        let equities = get_equities_of_mask(mask, &chips, &prizes, &mut memo);
+        let mut result = vec![0.0; stacks.len()];
+        for (index, ID) in players.iter().enumerate() {
+            result[*ID] = equities[index];
+        }
+        Ok(result)
+    }
+
+    /// mask里的人争剩下的名次时每个人的奖金期望，前面已经排走的人数就是mask外的人数
+    fn get_equities_of_mask(mask: u32, chips: &Vec<f64>, prizes: &Vec<f64>, memo: &mut HashMap<u32, Vec<f64>>) -> Vec<f64> {
+        let num = chips.len();
+        let place = num - mask.count_ones() as usize;
+        if mask == 0 || place >= prizes.len() {
+            return vec![0.0; num];
+        }
+        if let Some(T) = memo.get(&mask) {
+            return T.clone();
+        }
+        let total: f64 = (0..num).filter(|index| mask >> index & 1 == 1).map(|index| chips[index]).sum();
+        let mut equities = vec![0.0; num];
+        for index in 0..num {
+            if mask >> index & 1 == 0 {
+                continue;
+            }
+            let probability = chips[index] / total;
+            equities[index] += probability * prizes[place];
+            let rest = get_equities_of_mask(mask & !(1 << index), chips, prizes, memo);
+            for (equity, value) in equities.iter_mut().zip(rest.iter()) {
+                *equity += probability * value;
+            }
+        }
+        memo.insert(mask, equities.clone());
+        equities
+    }
+
+    /// 按筹码比例分奖池的期望，用来和ICM对比
+    pub fn get_chip_equities(stacks: &Vec<i32>, prize_pool: i32) -> Vec<f64> {
+        let total: i32 = stacks.iter().sum();
+        stacks.iter().map(|stack| if total > 0 { *stack as f64 / total as f64 * prize_pool as f64 } else { 0.0 }).collect()
+    }
+
+    /// 两个人全下对决的局面：stacks是每个人身后还没下进底池的筹码，pot是底池里已经有的筹码，
+    /// 对手已经全下时，他推进来的筹码也算在他身后，这样全下和跟注用同一个局面
+    #[derive(Clone, Debug)]
+    pub struct AllInSpot {
+        pub stacks: Vec<i32>,
+        pub pot: i32,
+        pub hero: usize,
+        pub villain: usize,
+        pub prizes: Vec<i32>,
+    }
+
+    impl AllInSpot {
+        pub fn new(stacks: Vec<i32>, pot: i32, hero: usize, villain: usize, prizes: Vec<i32>) -> Result<AllInSpot, String> {
+            if hero == villain || hero >= stacks.len() || villain >= stacks.len() {
+                return Err("The hero and the villain must be two different seats!".to_string());
+            }
+            if pot < 0 {
+                return Err("The pot cannot be negative!".to_string());
+            }
+            Ok(AllInSpot { stacks, pot, hero, villain, prizes })
+        }
+        /// 某个结果下自己的奖金期望：winner拿走底池，再从输的人那里赢走transfer
+        fn get_hero_equity(&self, winner: usize, transfer: i32) -> Result<f64, String> {
+            let mut stacks = self.stacks.clone();
+            let loser = if winner == self.hero { self.villain } else { self.hero };
+            stacks[winner] += self.pot + transfer;
+            stacks[loser] -= transfer;
+            Ok(get_icm_equities(&stacks, &self.prizes)?[self.hero])
+        }
+        /// 自己弃牌，对手拿走底池
+        pub fn get_fold_ev(&self) -> Result<f64, String> {
+            self.get_hero_equity(self.villain, 0)
+        }
+        /// 对手弃牌，自己拿走底池
+        pub fn get_steal_ev(&self) -> Result<f64, String> {
+            self.get_hero_equity(self.hero, 0)
+        }
+        /// 全下被跟注或者跟注对手的全下后摊牌，equity是自己的胜率
+        pub fn get_showdown_ev(&self, equity: f64) -> Result<f64, String> {
+            let effective = self.stacks[self.hero].min(self.stacks[self.villain]);
+            let win = self.get_hero_equity(self.hero, effective)?;
+            let lose = self.get_hero_equity(self.villain, effective)?;
+            Ok(equity * win + (1.0 - equity) * lose)
+        }
+        /// 全下的期望：对手按call_probability跟注，跟注时自己的胜率是equity
+        pub fn get_push_ev(&self, call_probability: f64, equity: f64) -> Result<f64, String> {
+            Ok((1.0 - call_probability) * self.get_steal_ev()? + call_probability * self.get_showdown_ev(equity)?)
+        }
+        /// 跟注至少要多少胜率才不比弃牌差
+        pub fn get_required_equity(&self) -> Result<f64, String> {
+            let fold = self.get_fold_ev()?;
+            let win = self.get_showdown_ev(1.0)?;
+            let lose = self.get_showdown_ev(0.0)?;
+            if win <= lose {
+                return Ok(1.0);
+            }
+            Ok(((fold - lose) / (win - lose)).max(0.0).min(1.0))
+        }
+    }
+}
+
+///短码全下或弃牌的纳什均衡：按每个位置的大盲数算出全下范围和跟注范围，可以按筹码或按ICM算，结果是13×13的表
+///
+///位置从翻前第一个说话的人开始排，最后两位是小盲和大盲；两个人时第0位是庄家兼小盲。
+///只看第一个跟注的人，他后面的人都弃牌；某个位置弃牌时当作大盲收走底池。
+mod module_pushfold {
+    use std::cell::RefCell;
+    use std::fmt;
+    use std::fmt::Display;
+    use std::rc::Rc;
+    use rand::prelude::*;
+    use crate::module_bot::{BotAction, DecisionContext, Strategy};
+    use crate::module_card::{Card, EquityTable, HandClass, HAND_CLASS_NUM};
+    use crate::module_icm::get_icm_equities;
+
+    /// 要解的局面
+    #[derive(Clone, Debug)]
+    pub struct PushFoldSpot {
+        /// 每个位置下盲注前的筹码，单位是大盲
+        pub stacks: Vec<f64>,
+        /// 每人的前注，单位是大盲
+        pub ante: f64,
+        /// 按ICM算时还没发出去的奖金，为空时按筹码算
+        pub prizes: Vec<i32>,
+    }
+
+    /// 全下和跟注范围表，每个范围按13×13表的格子排，是这一类起手牌全下或跟注的频率
+    #[derive(Clone, Debug)]
+    pub struct PushFoldChart {
+        pub stacks: Vec<f64>,
+        pub ante: f64,
+        /// pushes[位置]，大盲那一位是空的
+        pub pushes: Vec<Vec<f64>>,
+        /// calls[全下的位置][跟注的位置]，只有跟注的位置在全下的位置后面时才有
+        pub calls: Vec<Vec<Vec<f64>>>,
+    }
+
+    impl PushFoldSpot {
+        pub fn new(stacks: Vec<f64>, ante: f64, prizes: Vec<i32>) -> Result<PushFoldSpot, String> {
+            if stacks.len() < 2 || stacks.len() > 10 {
+                return Err(format!("Cannot solve push/fold for {0} players!", stacks.len()));
+            }
+            if stacks.iter().any(|stack| *stack <= 0.0) || ante < 0.0 {
+                return Err("The stacks must be positive and the ante cannot be negative!".to_string());
+            }
+            Ok(PushFoldSpot { stacks, ante, prizes })
+        }
+        /// 所有人都是同样的有效筹码
+        pub fn with_effective_stack(player_num: usize, stack_bb: f64) -> Result<PushFoldSpot, String> {
+            PushFoldSpot::new(vec![stack_bb; player_num], 0.0, Vec::new())
+        }
+        /// 小盲和大盲的位置
+        fn get_blind_positions(&self) -> (usize, usize) {
+            let num = self.stacks.len();
+            if num == 2 { (0, 1) } else { (num - 2, num - 1) }
+        }
+        /// 每个位置下的前注和盲注
+        fn get_posted(&self) -> Vec<f64> {
+            let (small_blind, big_blind) = self.get_blind_positions();
+            (0..self.stacks.len()).map(|position| {
+                let blind = if position == small_blind { 0.5 } else if position == big_blind { 1.0 } else { 0.0 };
+                (self.ante + blind).min(self.stacks[position])
+            }).collect()
+        }
+        /// 某个结果后hero的收益：按筹码算是剩下的大盲数，按ICM算是奖金期望
+        fn get_payoff(&self, stacks: &Vec<f64>, hero: usize) -> Result<f64, String> {
+            if self.prizes.is_empty() {
+                return Ok(stacks[hero]);
+            }
+            let chips: Vec<i32> = stacks.iter().map(|stack| (stack * 100.0).round() as i32).collect();
+            Ok(get_icm_equities(&chips, &self.prizes)?[hero])
+        }
+        /// 除了pusher和caller以外都弃牌，winner拿走底池后的筹码；caller为None时没人跟注
+        fn get_outcome(&self, pusher: usize, caller: Option<usize>, winner: usize) -> Vec<f64> {
+            let posted = self.get_posted();
+            let mut stacks: Vec<f64> = self.stacks.iter().zip(posted.iter()).map(|(stack, posted)| stack - posted).collect();
+            let mut pot: f64 = posted.iter().sum();
+            if let Some(caller) = caller {
+                let effective = self.stacks[pusher].min(self.stacks[caller]);
+                for position in [pusher, caller] {
+                    stacks[position] = self.stacks[position] - effective;
+                    pot += effective - posted[position];
+                }
+            }
+            stacks[winner] += pot;
+            stacks
+        }
+
+        /// 用虚拟对弈迭代iterations次求近似均衡
+        pub fn solve(&self, table: &EquityTable, iterations: usize) -> Result<PushFoldChart, String> {
+            let num = self.stacks.len();
+            let (_, big_blind) = self.get_blind_positions();
+            // 和手牌无关的结果先算好：弃牌、没人跟、和每个跟注的人比牌赢或输
+            let mut fold_payoffs = vec![0.0; num];
+            let mut steal_payoffs = vec![0.0; num];
+            let mut showdown_payoffs = vec![vec![(0.0, 0.0, 0.0, 0.0); num]; num];
+            for pusher in 0..num {
+                if pusher == big_blind {
+                    continue;
+                }
+                fold_payoffs[pusher] = self.get_payoff(&self.get_outcome(pusher, None, big_blind), pusher)?;
+                steal_payoffs[pusher] = self.get_payoff(&self.get_outcome(pusher, None, pusher), pusher)?;
+                for caller in (pusher + 1)..num {
+                    let win = self.get_outcome(pusher, Some(caller), pusher);
+                    let lose = self.get_outcome(pusher, Some(caller), caller);
+                    let caller_fold = self.get_payoff(&self.get_outcome(pusher, None, pusher), caller)?;
+                    showdown_payoffs[pusher][caller] = (self.get_payoff(&win, pusher)?, self.get_payoff(&lose, pusher)?,
+                                                        self.get_payoff(&lose, caller)? - caller_fold, self.get_payoff(&win, caller)? - caller_fold);
+                }
+            }
+            let mut pushes = vec![vec![0.5; HAND_CLASS_NUM]; num];
+            pushes[big_blind] = Vec::new();
+            let mut calls = vec![vec![Vec::new(); num]; num];
+            for pusher in 0..num {
+                for caller in (pusher + 1)..num {
+                    if pusher != big_blind {
+                        calls[pusher][caller] = vec![0.5; HAND_CLASS_NUM];
+                    }
+                }
+            }
+            for iteration in 0..iterations {
+                let step = 1.0 / (iteration + 2) as f64;
+                let mut new_pushes = pushes.clone();
+                let mut new_calls = calls.clone();
+                for pusher in 0..num {
+                    if pusher == big_blind {
+                        continue;
+                    }
+                    for hand in 0..HAND_CLASS_NUM {
+                        // 全下：后面的人按顺序决定跟不跟，第一个跟的人和自己比牌
+                        let mut nobody_called = 1.0;
+                        let mut value = 0.0;
+                        for caller in (pusher + 1)..num {
+                            let probability = table.get_range_probability(hand, &calls[pusher][caller]);
+                            if let Some(equity) = table.get_equity_against_range(hand, &calls[pusher][caller]) {
+                                let (win, lose, _, _) = showdown_payoffs[pusher][caller];
+                                value += nobody_called * probability * (equity * win + (1.0 - equity) * lose);
+                            }
+                            nobody_called *= 1.0 - probability;
+                        }
+                        value += nobody_called * steal_payoffs[pusher];
+                        let best = if value >= fold_payoffs[pusher] { 1.0 } else { 0.0 };
+                        new_pushes[pusher][hand] += (best - pushes[pusher][hand]) * step;
+                        // 跟注：和全下范围比牌，收益是相对弃牌多出来的
+                        for caller in (pusher + 1)..num {
+                            let (_, _, win, lose) = showdown_payoffs[pusher][caller];
+                            let best = match table.get_equity_against_range(hand, &pushes[pusher]) {
+                                Some(equity) if equity * win + (1.0 - equity) * lose >= 0.0 => 1.0,
+                                _ => 0.0,
+                            };
+                            new_calls[pusher][caller][hand] += (best - calls[pusher][caller][hand]) * step;
+                        }
+                    }
+                }
+                pushes = new_pushes;
+                calls = new_calls;
+            }
+            Ok(PushFoldChart { stacks: self.stacks.clone(), ante: self.ante, pushes, calls })
+        }
+    }
+
+    /// 一个范围占所有1326种起手牌的比例
+    pub fn get_range_percent(range: &Vec<f64>) -> f64 {
+        let combos: f64 = range.iter().enumerate().map(|(index, frequency)| HandClass::from_index(index).get_combos().len() as f64 * frequency).sum();
+        combos / 1326.0
+    }
+
+    /// 把范围画成13×13的表，频率超过一半的格子写出名字，否则写“--”
+    pub fn format_range(range: &Vec<f64>) -> String {
+        let mut text = String::new();
+        for row in 0..13 {
+            let cells: Vec<String> = (0..13).map(|column| {
+                let index = row * 13 + column;
+                if range.get(index).copied().unwrap_or(0.0) >= 0.5 { format!("{0:<4}", HandClass::from_index(index).get_name()) } else { "--  ".to_string() }
+            }).collect();
+            text.push_str(cells.join("").trim_end());
+            text.push('\n');
+        }
+        text
+    }
+
+    impl PushFoldChart {
+        /// 某个位置拿着这两张牌全下的频率
+        pub fn get_push_frequency(&self, position: usize, card1: &Card, card2: &Card) -> f64 {
+            match self.pushes.get(position) {
+                Some(range) if !range.is_empty() => range[HandClass::from_cards(card1, card2).get_index()],
+                _ => 0.0,
+            }
+        }
+        /// 面对pusher的全下，caller拿着这两张牌跟注的频率
+        pub fn get_call_frequency(&self, pusher: usize, caller: usize, card1: &Card, card2: &Card) -> f64 {
+            match self.calls.get(pusher).and_then(|calls| calls.get(caller)) {
+                Some(range) if !range.is_empty() => range[HandClass::from_cards(card1, card2).get_index()],
+                _ => 0.0,
+            }
+        }
+    }
+
+    /// 给范围表实现Display特性
+    impl Display for PushFoldChart {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            let stacks: Vec<String> = self.stacks.iter().map(|stack| format!("{0}", stack)).collect();
+            writeln!(f, "筹码（大盲）：{0}，前注：{1}", stacks.join("/"), self.ante)?;
+            for (position, range) in self.pushes.iter().enumerate() {
+                if !range.is_empty() {
+                    writeln!(f, "第{0}位全下（{1:.1}%）：", position, get_range_percent(range) * 100.0)?;
+                    write!(f, "{0}", format_range(range))?;
+                }
+            }
+            for (pusher, calls) in self.calls.iter().enumerate() {
+                for (caller, range) in calls.iter().enumerate() {
+                    if !range.is_empty() {
+                        writeln!(f, "第{0}位跟注第{1}位的全下（{2:.1}%）：", caller, pusher, get_range_percent(range) * 100.0)?;
+                        write!(f, "{0}", format_range(range))?;
+                    }
+                }
+            }
+            Ok(())
+        }
+    }
+
+    /// 短码时查表全下或弃牌的策略，筹码多于max_stack_bb个大盲、翻牌后或者人数和表对不上时交给inner
+    pub struct PushFoldStrategy {
+        pub chart: PushFoldChart,
+        pub max_stack_bb: f64,
+        inner: Rc<RefCell<dyn Strategy>>,
+        rng: StdRng,
+    }
+
+    impl PushFoldStrategy {
+        pub fn new(chart: PushFoldChart, max_stack_bb: f64, inner: Rc<RefCell<dyn Strategy>>, seed: u64) -> PushFoldStrategy {
+            PushFoldStrategy { chart, max_stack_bb, inner, rng: StdRng::seed_from_u64(seed) }
+        }
+        /// 查表决定，表上没有这个局面时返回None
+        fn decide_by_chart(&mut self, context: &DecisionContext) -> Option<BotAction> {
+            let table = &context.view.table;
+            let big_blind = table.big_blind.max(1);
+            if context.street != 0 || ((context.owned_value + context.bet_value) as f64 / big_blind as f64) > self.max_stack_bb {
+                return None;
+            }
+            let positions = &table.preflop_order;
+            if positions.len() != self.chart.stacks.len() || context.hole_cards.len() != 2 {
+                return None;
+            }
+            let hero = positions.iter().position(|seat| *seat == context.view.seat)?;
+            let card1 = Card::parse(&context.hole_cards[0]).ok()?;
+            let card2 = Card::parse(&context.hole_cards[1]).ok()?;
+            let frequency = if context.last_player_value > big_blind {
+                let name = context.opponent.as_ref()?;
+                let seat = table.seats.iter().find(|seat| &seat.name == name)?.seat;
+                let pusher = positions.iter().position(|position| *position == seat)?;
+                self.chart.get_call_frequency(pusher, hero, &card1, &card2)
+            } else {
+                self.chart.get_push_frequency(hero, &card1, &card2)
+            };
+            let action = if self.rng.gen::<f64>() >= frequency {
+                if context.need_to_call() { BotAction::Fold } else { BotAction::Check }
+            } else if context.last_player_value > big_blind {
+                BotAction::Call
+            } else {
+                match context.raise_range {
+                    Some((_, max)) => BotAction::Raise(max),
+                    None => BotAction::Call,
+                }
+            };
+            Some(action)
+        }
+    }
+
+    impl Strategy for PushFoldStrategy {
+        fn decide(&mut self, context: &DecisionContext) -> BotAction {
+            match self.decide_by_chart(context) {
+                Some(action) => action,
+                None => self.inner.borrow_mut().decide(context),
+            }
+        }
+    }
+}
+
+///单桌锦标赛：记录出局名次，有人赢下所有筹码时结束，按奖金表发奖
+mod module_sng {
+    use std::fmt;
+    use std::fmt::Display;
+    use serde::{Deserialize, Serialize};
+    use crate::module_game::Game;
+    use crate::module_arena::{mix_seed, play_hand};
+    use crate::module_icm::get_icm_equities;
+
+    /// 奖金表：第几名拿奖池的百分之几
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    pub struct PayoutTable {
+        pub percents: Vec<u32>,
+    }
+
+    impl PayoutTable {
+        pub fn new(percents: Vec<u32>) -> PayoutTable {
+            PayoutTable { percents }
+        }
+        /// 检查百分比加起来是100，并且拿奖的人数不超过参赛人数
+        pub fn check(&self, entrants: usize) -> Result<(), String> {
+            if self.percents.is_empty() || self.percents.iter().sum::<u32>() != 100 {
+                return Err("The payout percents must add up to 100!".to_string());
+            }
+            if self.percents.len() > entrants {
+                return Err(format!("Cannot pay {0} places with only {1} entrants!", self.percents.len(), entrants));
+            }
+            Ok(())
+        }
+        /// 每个名次的奖金，除不尽的零头给第一名
+        pub fn get_prizes(&self, prize_pool: i32) -> Vec<i32> {
+            let mut prizes: Vec<i32> = self.percents.iter().map(|percent| (prize_pool as i64 * *percent as i64 / 100) as i32).collect();
+            prizes[0] += prize_pool - prizes.iter().sum::<i32>();
+            prizes
+        }
+        /// 还剩players_left个人时还没发出去的奖金，从第一名往下排
+        pub fn get_remaining_prizes(&self, prize_pool: i32, players_left: usize) -> Vec<i32> {
+            self.get_prizes(prize_pool).into_iter().take(players_left).collect()
+        }
+        /// 从TOML文本中读取奖金表
+        pub fn from_toml(text: &str) -> Result<PayoutTable, String> {
+            match toml::from_str(text) {
+                Ok(T) => Ok(T),
+                Err(E) => Err(format!("Fail to parse the payout table: {0}", E)),
+            }
+        }
+    }
+
+    /// 一个人的最终名次
+    #[derive(Clone, Debug, PartialEq, Serialize)]
+    pub struct Finisher {
+        /// 名次，从1开始，同一手一起出局且筹码一样多时名次相同
+        pub place: usize,
+        pub name: String,
+        /// 第几手出局，冠军是比赛结束的那一手
+        pub hand: usize,
+        pub prize: i32,
+    }
+
+    /// 比赛结果，按名次排好
+    #[derive(Clone, Debug, Serialize)]
+    pub struct TournamentResults {
+        pub prize_pool: i32,
+        pub finishers: Vec<Finisher>,
+    }
+
+    impl TournamentResults {
+        /// 按名次从前往后的每一组人和他们出局的那一手排名次，并列的人平分这几个名次的奖金
+        pub fn new(prize_pool: i32, payouts: &PayoutTable, groups: &Vec<(usize, Vec<String>)>) -> TournamentResults {
+            let entrants: usize = groups.iter().map(|group| group.1.len()).sum();
+            let mut prizes = payouts.get_prizes(prize_pool);
+            prizes.resize(entrants.max(prizes.len()), 0);
+            let mut finishers = Vec::new();
+            let mut place = 1;
+            for (hand, group) in groups.iter() {
+                let total: i32 = prizes[place - 1..place - 1 + group.len()].iter().sum();
+                let share = total / group.len() as i32;
+                for (index, name) in group.iter().enumerate() {
+                    let bonus = if index == 0 { total - share * group.len() as i32 } else { 0 };
+                    finishers.push(Finisher { place, name: name.clone(), hand: *hand, prize: share + bonus });
+                }
+                place += group.len();
+            }
+            TournamentResults { prize_pool, finishers }
+        }
+        /// 还有筹码的人按ICM分掉他们那几个名次的奖金，筹码多的名次靠前，零头给筹码最多的人；已经出局的人照常拿奖金
+        pub fn new_with_deal(prize_pool: i32, payouts: &PayoutTable, stacks: &Vec<(String, i32)>, hand: usize, busted: &Vec<(usize, Vec<String>)>) -> Result<TournamentResults, String> {
+            let mut stacks = stacks.clone();
+            stacks.sort_by(|a, b| b.1.cmp(&a.1));
+            let mut groups: Vec<(usize, Vec<String>)> = stacks.iter().map(|item| (hand, vec![item.0.clone()])).collect();
+            groups.extend(busted.iter().rev().cloned());
+            let mut results = TournamentResults::new(prize_pool, payouts, &groups);
+            let prizes = payouts.get_remaining_prizes(prize_pool, stacks.len());
+            let equities = get_icm_equities(&stacks.iter().map(|item| item.1).collect(), &prizes)?;
+            let mut deal: Vec<i32> = equities.iter().map(|equity| equity.floor() as i32).collect();
+            deal[0] += prizes.iter().sum::<i32>() - deal.iter().sum::<i32>();
+            for (finisher, prize) in results.finishers.iter_mut().zip(deal.into_iter()) {
+                finisher.prize = prize;
+            }
+            Ok(results)
+        }
+    }
+
+    /// 单桌锦标赛
+    pub struct SitAndGo {
+        pub game: Game,
+        pub buy_in: i32,
+        pub payouts: PayoutTable,
+        pub hands_played: usize,
+        /// 出局的人和出局的那一手，先出局的在前，每一组是同一手出局、筹码一样多的人
+        busted: Vec<(usize, Vec<String>)>,
+        entrants: usize,
+    }
+
+    impl SitAndGo {
+        /// 用设好玩家和策略的Game开一场比赛，参赛的是所有没有退出的玩家
+        pub fn new(game: Game, buy_in: i32, payouts: PayoutTable) -> Result<SitAndGo, String> {
+            let entrants = SitAndGo::get_stacks(&game).iter().filter(|stack| **stack > 0).count();
+            if entrants < 2 {
+                return Err("At least 2 players are needed for a sit-and-go!".to_string());
+            }
+            if buy_in < 0 {
+                return Err("The buy-in cannot be negative!".to_string());
+            }
+            payouts.check(entrants)?;
+            let mut sng = SitAndGo { game, buy_in, payouts, hands_played: 0, busted: Vec::new(), entrants };
+            sng.update_prizes();
+            Ok(sng)
+        }
+        /// 把还没发出去的奖金告诉牌桌，机器人按它算ICM
+        fn update_prizes(&mut self) {
+            self.game.prizes = self.payouts.get_remaining_prizes(self.get_prize_pool(), self.get_player_num_left());
+        }
+        /// 奖池：买入乘参赛人数
+        pub fn get_prize_pool(&self) -> i32 {
+            self.buy_in * self.entrants as i32
+        }
+        /// 每个座位的筹码，下了注还没结算的也算上
+        fn get_stacks(game: &Game) -> Vec<i32> {
+            game.players.iter().map(|player| player.owned_bank.get_values_of_bank() + player.bet_bank.get_values_of_bank()).collect()
+        }
+        /// 还有筹码的人数
+        pub fn get_player_num_left(&self) -> usize {
+            SitAndGo::get_stacks(&self.game).iter().filter(|stack| **stack > 0).count()
+        }
+        /// 只剩一个人有筹码时比赛结束
+        pub fn is_finished(&self) -> bool {
+            self.get_player_num_left() <= 1
+        }
+        /// 打一手，记下这一手出局的人：开始时筹码多的名次靠前，一样多的并列
+        pub fn play_hand(&mut self, deal_seed: u64) -> Result<String, String> {
+            if self.is_finished() {
+                return Err("The sit-and-go has finished!".to_string());
+            }
+            let before = SitAndGo::get_stacks(&self.game);
+            let te_stack = play_hand(&mut self.game, deal_seed)?;
+            self.hands_played += 1;
+            let after = SitAndGo::get_stacks(&self.game);
+            let mut out: Vec<usize> = (0..before.len()).filter(|ID| before[*ID] > 0 && after[*ID] == 0).collect();
+            out.sort_by_key(|ID| before[*ID]);
+            let mut index = 0;
+            while index < out.len() {
+                let group: Vec<String> = out[index..].iter().take_while(|ID| before[**ID] == before[out[index]])
+                    .map(|ID| self.game.players[*ID].name.clone()).collect();
+                index += group.len();
+                self.busted.push((self.hands_played, group));
+            }
+            self.update_prizes();
+            Ok(te_stack)
+        }
+        /// 一直打到结束，最多打max_hands手
+        pub fn run(&mut self, seed: u64, max_hands: usize) -> Result<TournamentResults, String> {
+            while !self.is_finished() {
+                if self.hands_played >= max_hands {
+                    return Err(format!("The sit-and-go did not finish in {0} hands!", max_hands));
+                }
+                self.play_hand(mix_seed(seed, self.hands_played as u64))?;
+            }
+            self.get_results()
+        }
+        /// 比赛结果
+        pub fn get_results(&self) -> Result<TournamentResults, String> {
+            if !self.is_finished() {
+                return Err("The sit-and-go has not finished yet!".to_string());
+            }
+            let mut groups = Vec::new();
+            let stacks = SitAndGo::get_stacks(&self.game);
+            for (ID, player) in self.game.players.iter().enumerate() {
+                if stacks[ID] > 0 {
+                    groups.push((self.hands_played, vec![player.name.clone()]));
+                }
+            }
+            groups.extend(self.busted.iter().rev().cloned());
+            Ok(TournamentResults::new(self.get_prize_pool(), &self.payouts, &groups))
+        }
+        /// 还有筹码的人按当前筹码的ICM期望分钱，提前结束比赛
+        pub fn get_icm_deal(&self) -> Result<TournamentResults, String> {
+            let stacks = SitAndGo::get_stacks(&self.game);
+            let stacks: Vec<(String, i32)> = self.game.players.iter().enumerate().filter(|(ID, _)| stacks[*ID] > 0)
+                .map(|(ID, player)| (player.name.clone(), stacks[ID])).collect();
+            TournamentResults::new_with_deal(self.get_prize_pool(), &self.payouts, &stacks, self.hands_played, &self.busted)
+        }
+    }
+
+    /// 给比赛结果实现Display特性
+    impl Display for TournamentResults {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            writeln!(f, "奖池：{0}", self.prize_pool)?;
+            for finisher in self.finishers.iter() {
+                writeln!(f, "第{0}名：{1}，奖金{2}（第{3}手）", finisher.place, finisher.name, finisher.prize, finisher.hand)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+///多桌锦标赛：所有桌子一轮各打一手，按人数并桌和平衡桌子，泡沫时逐手进行，最后合成决赛桌
+mod module_mtt {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use crate::module_bot::Strategy;
+    use crate::module_config::GameConfig;
+    use crate::module_game::Game;
+    use crate::module_player::Player;
+    use crate::module_tournament::{BlindTimer, Clock};
+    use crate::module_arena::{mix_seed, play_hand};
+    use crate::module_sng::{PayoutTable, TournamentResults};
+
+    /// 多桌锦标赛
+    pub struct MultiTableTournament {
+        pub tables: Vec<Game>,
+        pub seats_per_table: usize,
+        pub buy_in: i32,
+        pub payouts: PayoutTable,
+        /// 全场共用的盲注计时器，每一轮开始前把这一级的盲注写进每张桌子的规则
+        pub blind_timer: BlindTimer,
+        pub rounds_played: usize,
+        /// 剩下的人数比拿奖的人数多，但最多多出bubble_margin个人时逐手进行
+        pub bubble_margin: usize,
+        /// 这一轮是不是逐手进行
+        pub hand_for_hand: bool,
+        pub final_table: bool,
+        /// 出局的人和出局的那一轮，先出局的在前，每一组是名次并列的人
+        busted: Vec<(usize, Vec<String>)>,
+        entrants: usize,
+    }
+
+    impl MultiTableTournament {
+        /// 按规则开桌，玩家轮流坐到各张桌子上，规则里必须有盲注级别表和起始筹码
+        pub fn new(config: GameConfig, names: &Vec<String>, seats_per_table: usize, buy_in: i32, payouts: PayoutTable, clock: Rc<dyn Clock>) -> Result<MultiTableTournament, String> {
+            let schedule = match config.blind_schedule.clone() {
+                Some(T) => T,
+                None => return Err("A multi-table tournament needs a blind schedule!".to_string()),
+            };
+            if config.starting_stack.is_none() {
+                return Err("A multi-table tournament needs a starting stack!".to_string());
+            }
+            if seats_per_table < 2 || seats_per_table > config.max_players {
+                return Err(format!("Cannot seat {0} players at a table of {1} seats!", seats_per_table, config.max_players));
+            }
+            if names.len() < 2 {
+                return Err("At least 2 players are needed for a tournament!".to_string());
+            }
+            if buy_in < 0 {
+                return Err("The buy-in cannot be negative!".to_string());
+            }
+            payouts.check(names.len())?;
+            let table_num = (names.len() + seats_per_table - 1) / seats_per_table;
+            let level = schedule.get_level(0).clone();
+            let mut table_config = config.clone();
+            table_config.blind_schedule = None;
+            table_config.small_blind = Some(level.small_blind);
+            table_config.big_blind = Some(level.big_blind);
+            table_config.ante = level.ante;
+            let mut tables = Vec::new();
+            for table in 0..table_num {
+                let table_names: Vec<String> = names.iter().skip(table).step_by(table_num).cloned().collect();
+                let mut game = Game::new_with_config(table_config.clone(), &table_names)?;
+                game.assign_blinds(0);
+                tables.push(game);
+            }
+            let mut mtt = MultiTableTournament {
+                tables,
+                seats_per_table,
+                buy_in,
+                payouts,
+                blind_timer: BlindTimer::new(schedule, clock),
+                rounds_played: 0,
+                bubble_margin: 1,
+                hand_for_hand: false,
+                final_table: table_num == 1,
+                busted: Vec::new(),
+                entrants: names.len(),
+            };
+            mtt.update_prizes();
+            Ok(mtt)
+        }
+        /// 给某个玩家设置策略
+        pub fn set_strategy(&mut self, name: &str, strategy: Rc<RefCell<dyn Strategy>>) -> Result<String, String> {
+            match self.find_player(name) {
+                Some((table, _)) => {
+                    self.tables[table].set_strategy(name, strategy);
+                    Ok(format!("Succeed to set the strategy of \"{0}\"!", name))
+                }
+                None => Err(format!("Fail to find \"{0}\" in the tournament!", name)),
+            }
+        }
+        /// 某个玩家坐在哪张桌子的哪个座位
+        pub fn find_player(&self, name: &str) -> Option<(usize, usize)> {
+            for (table, game) in self.tables.iter().enumerate() {
+                if let Some(ID) = game.players.iter().position(|player| player.name == name && MultiTableTournament::get_stack(player) > 0) {
+                    return Some((table, ID));
+                }
+            }
+            None
+        }
+        /// 奖池：买入乘参赛人数
+        pub fn get_prize_pool(&self) -> i32 {
+            self.buy_in * self.entrants as i32
+        }
+        /// 玩家的筹码，下了注还没结算的也算上
+        fn get_stack(player: &Player) -> i32 {
+            player.owned_bank.get_values_of_bank() + player.bet_bank.get_values_of_bank()
+        }
+        /// 每张桌子还有筹码的座位
+        fn get_seats_left(game: &Game) -> Vec<usize> {
+            (0..game.players.len()).filter(|ID| MultiTableTournament::get_stack(&game.players[*ID]) > 0).collect()
+        }
+        /// 每张桌子还剩几个人
+        pub fn get_table_sizes(&self) -> Vec<usize> {
+            self.tables.iter().map(|game| MultiTableTournament::get_seats_left(game).len()).collect()
+        }
+        /// 全场还剩几个人
+        pub fn get_player_num_left(&self) -> usize {
+            self.get_table_sizes().iter().sum()
+        }
+        /// 只剩一个人有筹码时比赛结束
+        pub fn is_finished(&self) -> bool {
+            self.get_player_num_left() <= 1
+        }
+
+        /// 打一轮：每张桌子打一手，记下出局的人，再并桌和平衡桌子
+        pub fn play_round(&mut self, seed: u64) -> Result<String, String> {
+            if self.is_finished() {
+                return Err("The tournament has finished!".to_string());
+            }
+            let mut te_stack = String::new();
+            let players_left = self.get_player_num_left();
+            let paid = self.payouts.percents.len();
+            self.hand_for_hand = !self.final_table && players_left > paid && players_left <= paid + self.bubble_margin;
+            if self.hand_for_hand {
+                te_stack.push_str(&format!("Hand-for-hand play with {0} players left!", players_left));
+            }
+            // 所有桌子按同一级盲注打，升盲后各桌把用不上的小面额筹码进位
+            let changed = self.blind_timer.on_new_hand();
+            if changed {
+                te_stack.push_str(&format!("The blinds go up to level {0}!", self.blind_timer.level + 1));
+            }
+            let level = self.blind_timer.get_level().clone();
+            for game in self.tables.iter_mut() {
+                game.config.small_blind = Some(level.small_blind);
+                game.config.big_blind = Some(level.big_blind);
+                game.config.ante = level.ante;
+                if changed {
+                    te_stack.push_str(&game.color_up());
+                }
+            }
+            self.rounds_played += 1;
+            let mut out: Vec<Vec<(i32, String)>> = Vec::new();
+            for (table, game) in self.tables.iter_mut().enumerate() {
+                let before: Vec<i32> = game.players.iter().map(|player| MultiTableTournament::get_stack(player)).collect();
+                te_stack.push_str(&play_hand(game, mix_seed(seed, table as u64))?);
+                let mut table_out: Vec<(i32, String)> = (0..before.len())
+                    .filter(|ID| before[*ID] > 0 && MultiTableTournament::get_stack(&game.players[*ID]) == 0)
+                    .map(|ID| (before[ID], game.players[ID].name.clone())).collect();
+                table_out.sort_by_key(|item| item.0);
+                out.push(table_out);
+                game.remove_busted_players();
+            }
+            // 逐手进行时各桌同时出局的人按这一手开始时的筹码排名次，否则按桌子的顺序先后出局
+            if self.hand_for_hand {
+                let mut all: Vec<(i32, String)> = out.concat();
+                all.sort_by_key(|item| item.0);
+                out = vec![all];
+            }
+            for table_out in out.iter() {
+                let mut index = 0;
+                while index < table_out.len() {
+                    let group: Vec<String> = table_out[index..].iter().take_while(|item| item.0 == table_out[index].0).map(|item| item.1.clone()).collect();
+                    index += group.len();
+                    self.busted.push((self.rounds_played, group));
+                }
+            }
+            if !self.is_finished() {
+                te_stack.push_str(&self.balance_tables()?);
+            }
+            self.update_prizes();
+            Ok(te_stack)
+        }
+        /// 到了决赛桌就把还没发出去的奖金告诉牌桌，机器人按它算ICM；分桌时一张桌子的筹码算不了ICM
+        fn update_prizes(&mut self) {
+            if self.final_table {
+                let prizes = self.payouts.get_remaining_prizes(self.get_prize_pool(), self.get_player_num_left());
+                self.tables[0].prizes = prizes;
+            }
+        }
+        /// 一直打到结束，最多打max_rounds轮
+        pub fn run(&mut self, seed: u64, max_rounds: usize) -> Result<TournamentResults, String> {
+            while !self.is_finished() {
+                if self.rounds_played >= max_rounds {
+                    return Err(format!("The tournament did not finish in {0} rounds!", max_rounds));
+                }
+                self.play_round(mix_seed(seed, self.rounds_played as u64))?;
+            }
+            self.get_results()
+        }
+
+        /// 人数够坐进少一张桌子时拆掉人最少的桌子，剩下的桌子人数差超过一人时，
+        /// 从人最多的桌子把下一手的大盲挪到人最少的桌子
+        pub fn balance_tables(&mut self) -> Result<String, String> {
+            let mut te_stack = String::new();
+            while self.tables.len() > 1 && self.get_player_num_left() <= (self.tables.len() - 1) * self.seats_per_table {
+                let sizes = self.get_table_sizes();
+                let broken = (0..sizes.len()).min_by_key(|table| sizes[*table]).unwrap();
+                let mut game = self.tables.remove(broken);
+                for ID in MultiTableTournament::get_seats_left(&game) {
+                    let (player, strategy) = game.take_a_player(ID)?;
+                    let sizes = self.get_table_sizes();
+                    let target = (0..sizes.len()).min_by_key(|table| sizes[*table]).unwrap();
+                    te_stack.push_str(&format!("\"{0}\" is moved to table {1}!", player.name, target + 1));
+                    self.tables[target].seat_a_player(player, strategy)?;
+                }
+                te_stack.push_str(&format!("Table {0} is broken!", broken + 1));
+                if self.tables.len() == 1 {
+                    self.final_table = true;
+                    te_stack.push_str("The final table starts!");
+                }
+            }
+            loop {
+                let sizes = self.get_table_sizes();
+                let from = (0..sizes.len()).max_by_key(|table| sizes[*table]).unwrap();
+                let to = (0..sizes.len()).min_by_key(|table| sizes[*table]).unwrap();
+                if sizes[from] <= sizes[to] + 1 {
+                    break;
+                }
+                let game = &mut self.tables[from];
+                let ID = game.get_next_seat(game.last_DaMang_ID as usize);
+                let (player, strategy) = game.take_a_player(ID)?;
+                te_stack.push_str(&format!("\"{0}\" is moved from table {1} to table {2}!", player.name, from + 1, to + 1));
+                self.tables[to].seat_a_player(player, strategy)?;
+            }
+            Ok(te_stack)
+        }
+
+        /// 比赛结果
+        pub fn get_results(&self) -> Result<TournamentResults, String> {
+            if !self.is_finished() {
+                return Err("The tournament has not finished yet!".to_string());
+            }
+            let mut groups = Vec::new();
+            for game in self.tables.iter() {
+                for ID in MultiTableTournament::get_seats_left(game) {
+                    groups.push((self.rounds_played, vec![game.players[ID].name.clone()]));
+                }
+            }
+            groups.extend(self.busted.iter().rev().cloned());
+            Ok(TournamentResults::new(self.get_prize_pool(), &self.payouts, &groups))
+        }
+        /// 还有筹码的人按当前筹码的ICM期望分钱，提前结束比赛
+        pub fn get_icm_deal(&self) -> Result<TournamentResults, String> {
+            let mut stacks = Vec::new();
+            for game in self.tables.iter() {
+                for ID in MultiTableTournament::get_seats_left(game) {
+                    stacks.push((game.players[ID].name.clone(), MultiTableTournament::get_stack(&game.players[ID])));
+                }
+            }
+            TournamentResults::new_with_deal(self.get_prize_pool(), &self.payouts, &stacks, self.rounds_played, &self.busted)
+        }
+    }
+}
+
+///外部机器人协议：通过子进程的标准输入输出，一行一个JSON
+///
+///引擎发送：{"type":"act","id":1,"state":{...},"legal_actions":[{"action":"fold"},{"action":"call","value":20},{"action":"raise","min":25,"max":100}]}
+///机器人回复时带上请求的id：{"id":1,"action":"fold"}、{"id":1,"action":"check"}、{"id":1,"action":"call"}或{"id":1,"action":"raise","value":60}
+///引擎退出时发送：{"type":"quit"}
+mod module_protocol {
+    use std::io::{BufRead, BufReader, Write};
+    use std::process::{Child, ChildStdin, Command, Stdio};
+    use std::sync::mpsc;
+    use std::sync::mpsc::Receiver;
+    use std::thread;
+    use std::time::Duration;
+    use std::time::Instant;
+    use serde::{Deserialize, Serialize};
+    use crate::module_bot::{BotAction, DecisionContext, LegalAction, Strategy};
+
+    /// 规则里没有给行动时限时，外部机器人每次行动最多等这么久
+    pub const DEFAULT_ACTION_TIMEOUT_MS: u64 = 30_000;
+
+    /// 发给机器人的行动请求
+    #[derive(Serialize)]
+    pub struct ActRequest<'a> {
+        #[serde(rename = "type")]
+        pub kind: &'static str,
+        pub id: u64,
+        pub state: &'a DecisionContext,
+        pub legal_actions: Vec<LegalAction>,
+    }
+
+    /// 把行动请求编码成一行JSON
+    pub fn encode_request(id: u64, context: &DecisionContext) -> Result<String, String> {
+        let request = ActRequest {
+            kind: "act",
+            id,
+            state: context,
+            legal_actions: context.get_legal_actions(),
+        };
+        match serde_json::to_string(&request) {
+            Ok(T) => Ok(T),
+            Err(E) => Err(format!("Fail to encode the request: {0}", E)),
+        }
+    }
+
+    /// 机器人回复的一行JSON
+    #[derive(Deserialize)]
+    pub struct ActReply {
+        pub id: u64,
+        #[serde(flatten)]
+        pub action: BotAction,
+    }
+
+    /// 把机器人回复的一行JSON解码成行动，回复的id要和请求的对上，并检查行动是否合法
+    pub fn decode_reply(id: u64, line: &str, context: &DecisionContext) -> Result<BotAction, String> {
+        let reply: ActReply = match serde_json::from_str(line.trim()) {
+            Ok(T) => T,
+            Err(E) => return Err(format!("Fail to decode the reply \"{0}\": {1}", line.trim(), E)),
+        };
+        if reply.id != id {
+            return Err(format!("The reply is for request {0}, not {1}!", reply.id, id));
+        }
+        context.check_action(&reply.action)?;
+        Ok(reply.action)
+    }
+
+    /// 外部机器人策略，每个座位一个子进程
+    pub struct ExternalStrategy {
+        child: Child,
+        stdin: ChildStdin,
+        lines: Receiver<String>,
+        timeout: Duration,
+        next_id: u64,
+        /// 超时、回复无效等问题的记录，出问题时按默认行动处理
+        pub errors: Vec<String>,
+    }
+
+    impl ExternalStrategy {
+        /// 启动子进程
+        pub fn spawn(program: &str, args: &Vec<&str>, timeout: Duration) -> Result<ExternalStrategy, String> {
+            let mut child = match Command::new(program).args(args).stdin(Stdio::piped()).stdout(Stdio::piped()).spawn() {
+                Ok(T) => T,
+                Err(E) => return Err(format!("Fail to spawn \"{0}\": {1}", program, E)),
+            };
+            let stdin = child.stdin.take().ok_or("Fail to open stdin of the bot!".to_string())?;
+            let stdout = child.stdout.take().ok_or("Fail to open stdout of the bot!".to_string())?;
+            // 单独的线程读取输出，主线程才能按超时等待
+            let (sender, lines) = mpsc::channel();
+            thread::spawn(move || {
+                for line in BufReader::new(stdout).lines() {
+                    match line {
+                        Ok(line) => {
+                            if sender.send(line).is_err() {
+                                break;
+                            }
+                        }
+                        Err(_) => break,
+                    }
+                }
+            });
+            Ok(ExternalStrategy {
+                child,
+                stdin,
+                lines,
+                timeout,
+                next_id: 1,
+                errors: Vec::new(),
+            })
+        }
+
+        /// 发送请求并在超时之前等待回复，id对不上的是之前超时后才到的回复，直接丢掉
+        pub fn request(&mut self, context: &DecisionContext) -> Result<BotAction, String> {
+            let id = self.next_id;
+            self.next_id += 1;
+            let message = encode_request(id, context)?;
+            if let Err(E) = writeln!(self.stdin, "{0}", message).and_then(|_| self.stdin.flush()) {
+                return Err(format!("Fail to send the request to the bot: {0}", E));
+            }
+            let deadline = Instant::now() + self.timeout;
+            loop {
+                let left = deadline.saturating_duration_since(Instant::now());
+                match self.lines.recv_timeout(left) {
+                    Ok(line) => {
+                        if let Ok(reply) = serde_json::from_str::<ActReply>(line.trim()) {
+                            if reply.id != id {
+                                self.errors.push(format!("Discard the late reply to request {0}!", reply.id));
+                                continue;
                             }
-                            return Ok(te_stack);
-                        },
-                        GameStatus::Balancing => {
-                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
-                        },
+                        }
+                        return decode_reply(id, &line, context);
+                    }
+                    Err(mpsc::RecvTimeoutError::Timeout) => return Err(format!("The bot does not reply within {0}ms!", self.timeout.as_millis())),
+                    Err(mpsc::RecvTimeoutError::Disconnected) => return Err("The bot has exited!".to_string()),
+                }
+            }
+        }
+    }
+
+    impl Strategy for ExternalStrategy {
+        fn decide(&mut self, context: &DecisionContext) -> BotAction {
+            match self.request(context) {
+                Ok(action) => action,
+                Err(E) => {
+                    self.errors.push(E);
+                    context.get_default_action()
+                }
+            }
+        }
+    }
+
+    impl Drop for ExternalStrategy {
+        fn drop(&mut self) {
+            let _ = writeln!(self.stdin, "{{\"type\":\"quit\"}}");
+            let _ = self.stdin.flush();
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+}
+
+///牌局回放：把记录里的每一手变成引擎事件一步步重放，并检查底池、赢家和行动是否和记录一致
+mod module_replay {
+    use std::cell::RefCell;
+    use std::collections::VecDeque;
+    use std::rc::Rc;
+    use crate::module_bank::StuffType;
+    use crate::module_bot::{BotAction, DecisionContext, Strategy};
+    use crate::module_card::{Card, DealTarget};
+    use crate::module_config::{BettingStructure, RakeRule, Straddle};
+    use crate::module_game::{Game, GameStatus, MyEvent, EMPTY_SEAT_NAME};
+    use crate::module_history::{HandHistory, HistoryAction, HistoryEntry};
+    use crate::module_player::Player;
+
+    /// 按记录行动的策略，和记录对不上时记下错误并按默认行动处理
+    pub struct ReplayStrategy {
+        pub actions: VecDeque<BotAction>,
+        pub errors: Vec<String>,
+    }
+
+    impl Strategy for ReplayStrategy {
+        fn decide(&mut self, context: &DecisionContext) -> BotAction {
+            let action = match self.actions.pop_front() {
+                Some(T) => T,
+                None => {
+                    self.errors.push(format!("{0} has no recorded action left on street {1}!", context.name, context.street));
+                    return context.get_default_action();
+                }
+            };
+            if let Err(E) = context.check_action(&action) {
+                self.errors.push(E);
+                return context.get_default_action();
+            }
+            action
+        }
+    }
+
+    /// 回放器，每次step发一个事件
+    pub struct Replayer {
+        pub history: HandHistory,
+        pub game: Game,
+        strategies: Vec<Rc<RefCell<ReplayStrategy>>>,
+        bets_placed: usize,
+        /// 结算前引擎里的底池
+        pot_value: Option<i32>,
+        finished: bool,
+    }
+
+    fn get_gcd(a: i32, b: i32) -> i32 {
+        if b == 0 { a } else { get_gcd(b, a % b) }
+    }
+
+    impl Replayer {
+        /// 按记录摆好座位、筹码、盲注和要发的牌
+        pub fn new(history: &HandHistory) -> Result<Replayer, String> {
+            if history.seats.iter().any(|seat| seat.name == "ME") {
+                return Err("Fail to replay a hand with a player named ME!".to_string());
+            }
+            // 按记录的座位号摆座位，空座位用出局的占位玩家补上，死庄和死小盲才能还原
+            let seat_num = history.seats.iter().map(|seat| seat.seat + 1).max().unwrap_or(0).max(history.button + 1);
+            let mut names = Vec::new();
+            let mut empty_seats = Vec::new();
+            for ID in 0..seat_num {
+                match history.seats.iter().find(|seat| seat.seat == ID) {
+                    Some(seat) => names.push(seat.name.clone()),
+                    None => {
+                        names.push(format!("{0}{1}", EMPTY_SEAT_NAME, ID + 1));
+                        empty_seats.push(ID);
+                    }
+                }
+            }
+            let mut game = Game::new_with_players(&names)?;
+            game.config.betting_structure = match history.limit.as_str() {
+                "Pot Limit" => BettingStructure::PotLimit,
+                "Limit" | "Fixed Limit" => BettingStructure::FixedLimit,
+                _ => BettingStructure::NoLimit,
+            };
+            // 大小盲按记录找，没记下来的按庄家往后数；只有大盲时小盲是死的，大盲前一个空座位就是死小盲
+            let mut XiaoMang_ID = None;
+            let mut DaMang_ID = None;
+            for entry in history.actions.iter() {
+                let ID = names.iter().position(|name| name == &entry.name);
+                match entry.action {
+                    HistoryAction::SmallBlind(..) if XiaoMang_ID.is_none() => XiaoMang_ID = ID,
+                    HistoryAction::BigBlind(..) if DaMang_ID.is_none() => DaMang_ID = ID,
+                    _ => {}
+                }
+            }
+            let get_next_seat = |ID: usize| (1..=seat_num).map(|i| (ID + i) % seat_num).find(|next| !empty_seats.contains(next)).unwrap_or(ID);
+            let XiaoMang_ID = match (XiaoMang_ID, DaMang_ID) {
+                (Some(T), _) => T,
+                (None, Some(T)) if empty_seats.contains(&((T + seat_num - 1) % seat_num)) => (T + seat_num - 1) % seat_num,
+                _ if history.seats.len() == 2 => history.button,
+                _ => get_next_seat(history.button),
+            };
+            let DaMang_ID = DaMang_ID.unwrap_or(get_next_seat(XiaoMang_ID));
+            // 筹码面值取所有金额的最大公约数，盲注直接按记录写进规则
+            let mut gcd = get_gcd(history.small_blind, history.big_blind);
+            for seat in history.seats.iter() {
+                gcd = get_gcd(gcd, seat.stack);
+            }
+            for entry in history.actions.iter() {
+                if let HistoryAction::Ante(value) | HistoryAction::SmallBlind(value) | HistoryAction::BigBlind(value) | HistoryAction::Straddle(value) | HistoryAction::Call(value) | HistoryAction::Raise(value) = entry.action {
+                    gcd = get_gcd(gcd, value);
+                }
+            }
+            if history.rake > 0 {
+                gcd = get_gcd(gcd, history.rake);
+            }
+            if gcd <= 0 {
+                return Err("Fail to replay a hand without chips!".to_string());
+            }
+            game.config.small_blind = Some(history.small_blind);
+            // 记录里抽了水就按记下的数抽
+            if history.rake > 0 {
+                game.config.rake = Some(RakeRule::new(100.0, Some(history.rake), None, false));
+            }
+            game.config.big_blind = Some(history.big_blind);
+            // 只有大盲一个人下了前注就是大盲前注
+            let antes: Vec<&HistoryEntry> = history.actions.iter().filter(|entry| matches!(entry.action, HistoryAction::Ante(..))).collect();
+            for entry in antes.iter() {
+                if let HistoryAction::Ante(value) = entry.action {
+                    game.config.ante = game.config.ante.max(value);
+                }
+            }
+            game.config.big_blind_ante = antes.len() == 1 && history.seats.len() > 1 && names.iter().position(|name| name == &antes[0].name) == Some(DaMang_ID);
+            game.config.straddles = vec![Straddle::UnderTheGun, Straddle::Button];
+            let chips = StuffType::GeneralType("筹码", gcd);
+            let _ = game.receive_my_event(MyEvent::ResetGame { assets: &vec![(&chips, 1)] });
+            for player in game.players.iter_mut() {
+                player.owned_bank.stuff_clear()?;
+            }
+            for seat in history.seats.iter() {
+                game.players[seat.seat].initial_my_owned_bank(&vec![(&chips, seat.stack / gcd)])?;
+            }
+            for ID in empty_seats.iter() {
+                game.players[*ID] = Player::new_placeholder(&names[*ID]);
+            }
+            game.get_min_value_unit();
+            game.set_positions(history.button, XiaoMang_ID, DaMang_ID);
+            game.receive_my_event(MyEvent::StartNextGame)?;
+            if let Some(entry) = history.actions.iter().find(|entry| matches!(entry.action, HistoryAction::Straddle(..))) {
+                let ID = names.iter().position(|name| name == &entry.name).ok_or(format!("Fail to find the straddler {0}!", entry.name))?;
+                game.receive_my_event(MyEvent::Straddle { seat: ID })?;
+            }
+            // 摆好已知的牌
+            for seat in history.seats.iter() {
+                if !seat.hole_cards.is_empty() {
+                    game.card_pool.pin_cards(DealTarget::Seat(seat.seat), &parse_cards(&seat.hole_cards)?)?;
+                }
+            }
+            let board = parse_cards(&history.board)?;
+            let targets = [DealTarget::Flop, DealTarget::Flop, DealTarget::Flop, DealTarget::Turn, DealTarget::River];
+            for (card, target) in board.into_iter().zip(targets.iter()) {
+                game.card_pool.pin_cards(*target, &vec![card])?;
+            }
+            // 每个人按记录的行动下注
+            let mut strategies = Vec::new();
+            for name in history.seats.iter().map(|seat| &seat.name) {
+                let mut actions = VecDeque::new();
+                for entry in history.actions.iter().filter(|entry| &entry.name == name) {
+                    match entry.action {
+                        HistoryAction::Fold => actions.push_back(BotAction::Fold),
+                        HistoryAction::Check => actions.push_back(BotAction::Check),
+                        HistoryAction::Call(..) => actions.push_back(BotAction::Call),
+                        HistoryAction::Raise(to) => actions.push_back(BotAction::Raise(to)),
+                        _ => {}
+                    }
+                }
+                let strategy = Rc::new(RefCell::new(ReplayStrategy { actions, errors: Vec::new() }));
+                game.set_strategy(name, strategy.clone());
+                strategies.push(strategy);
+            }
+            Ok(Replayer {
+                history: history.clone(),
+                game,
+                strategies,
+                bets_placed: 0,
+                pot_value: None,
+                finished: false,
+            })
+        }
+
+        /// 发下一个事件，一手打完后返回None
+        pub fn step(&mut self) -> Result<Option<String>, String> {
+            if self.finished {
+                return Ok(None);
+            }
+            let result = match self.game.game_status {
+                GameStatus::CardsPicking => self.game.receive_my_event(MyEvent::PickCards),
+                GameStatus::BetPlacing1 | GameStatus::BetPlacing2 | GameStatus::BetPlacing3 | GameStatus::BetPlacing4 if self.bets_placed < 4 => {
+                    self.bets_placed += 1;
+                    self.game.receive_my_event(MyEvent::PlaceABet_Auto)
+                }
+                _ => {
+                    self.finished = true;
+                    self.pot_value = Some(self.game.get_pot_value());
+                    self.game.receive_my_event(MyEvent::ConfirmBalance)
+                }
+            };
+            for strategy in self.strategies.iter() {
+                if let Some(E) = strategy.borrow().errors.first() {
+                    return Err(format!("The replay diverges: {0}", E));
+                }
+            }
+            match result {
+                Ok(T) => Ok(Some(T)),
+                Err(E) => Ok(Some(E)),
+            }
+        }
+
+        /// 一直重放到结束，再检查结果
+        pub fn run(&mut self) -> Result<String, String> {
+            while self.step()?.is_some() {}
+            self.verify()
+        }
+
+        /// 检查行动、底池和赢家是否和记录一致
+        pub fn verify(&self) -> Result<String, String> {
+            let replayed = self.game.histories.last().ok_or("The hand has not been dealt yet!".to_string())?;
+            let recorded: Vec<(String, usize, HistoryAction)> = self.history.actions.iter().map(|entry| (entry.name.clone(), entry.street, entry.action.clone())).collect();
+            let actual: Vec<(String, usize, HistoryAction)> = replayed.actions.iter().map(|entry| (entry.name.clone(), entry.street, entry.action.clone())).collect();
+            if let Some(index) = (0..recorded.len().max(actual.len())).find(|index| recorded.get(*index) != actual.get(*index)) {
+                return Err(format!("Action {0} differs: recorded {1:?}, replayed {2:?}!", index + 1, recorded.get(index), actual.get(index)));
+            }
+            let pot_value: i32 = self.history.get_committed().iter().map(|item| item.1).sum();
+            if self.pot_value != Some(pot_value) {
+                return Err(format!("The pot differs: recorded {0}, replayed {1:?}!", pot_value, self.pot_value));
+            }
+            if replayed.awards != self.history.awards {
+                return Err(format!("The winners differ: recorded {0:?}, replayed {1:?}!", self.history.awards, replayed.awards));
+            }
+            Ok(format!("Succeed to replay hand #{0}!", self.history.hand_id))
+        }
+    }
+
+    fn parse_cards(names: &Vec<String>) -> Result<Vec<Card>, String> {
+        names.iter().map(|name| Card::parse(name)).collect()
+    }
+
+    /// 重放一手并检查结果
+    pub fn replay(history: &HandHistory) -> Result<String, String> {
+        Replayer::new(history)?.run()
+    }
+}
+
+///对小型扑克博弈（Kuhn、Leduc、高度抽象的德州）计算最佳应对和策略的可剥削度
+mod module_exploit {
+    use std::collections::HashMap;
+    use crate::module_card::{EquityTable, HandClass, HAND_CLASS_NUM};
+
+    /// 小型博弈中的动作，用一个字符表示（如Kuhn的'p'/'b'，Leduc的'f'/'c'/'r'）
+    pub type Action = char;
+
+    /// 两人零和小型博弈的接口，新的抽象博弈实现这个特性即可参与评估
+    pub trait SmallGame {
+        type State: Clone;
+
+        /// 发牌后的所有初始状态及其概率
+        fn initial_states(&self) -> Vec<(Self::State, f64)>;
+        /// 是否是终局
+        fn is_terminal(&self, state: &Self::State) -> bool;
+        /// 终局时某玩家的收益（单位：筹码）
+        fn utility(&self, state: &Self::State, player: usize) -> f64;
+        /// 当前行动的玩家，None表示机会节点（发公共牌）
+        fn current_player(&self, state: &Self::State) -> Option<usize>;
+        /// 机会节点的所有结果及其概率
+        fn chance_outcomes(&self, state: &Self::State) -> Vec<(Self::State, f64)>;
+        /// 当前玩家的合法动作
+        fn legal_actions(&self, state: &Self::State) -> Vec<Action>;
+        /// 执行动作后的状态
+        fn next_state(&self, state: &Self::State, action: Action) -> Self::State;
+        /// 某玩家在该状态下的信息集
+        fn info_set(&self, state: &Self::State, player: usize) -> String;
+        /// 一个大盲对应的筹码数，用于换算mbb
+        fn big_blind(&self) -> f64;
+    }
+
+    /// 小型博弈策略的接口：给出信息集下各合法动作的概率
+    pub trait SmallGameStrategy {
+        fn action_probabilities(&self, info_set: &str, legal_actions: &Vec<Action>) -> Vec<f64>;
+    }
+
+    /// 表格策略，没有记录的信息集按均匀分布处理
+    #[derive(Clone)]
+    pub struct TabularStrategy {
+        pub table: HashMap<String, Vec<(Action, f64)>>,
+    }
+
+    impl TabularStrategy {
+        pub fn new() -> TabularStrategy {
+            TabularStrategy {
+                table: HashMap::new(),
+            }
+        }
+        /// 设置某信息集下的动作概率
+        pub fn set(&mut self, info_set: &str, probabilities: Vec<(Action, f64)>) {
+            self.table.insert(info_set.to_string(), probabilities);
+        }
+    }
+
+    impl SmallGameStrategy for TabularStrategy {
+        fn action_probabilities(&self, info_set: &str, legal_actions: &Vec<Action>) -> Vec<f64> {
+            let uniform = 1.0 / legal_actions.len() as f64;
+            match self.table.get(info_set) {
+                Some(probabilities) => {
+                    let mut result: Vec<f64> = legal_actions.iter()
+                        .map(|a| probabilities.iter().find(|p| p.0 == *a).map(|p| p.1).unwrap_or(0.0))
+                        .collect();
+                    let total: f64 = result.iter().sum();
+                    if total <= 0.0 {
+                        return vec![uniform; legal_actions.len()];
                     }
-                },
-                MyEvent::ConfirmBalance => {
-                    match game_status {
-                        GameStatus::Setting => {
-                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
-                        },
-                        GameStatus::CardsPicking => {
-                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
-                        },
-                        GameStatus::BetPlacing1 => {
-                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
-                        },
-                        GameStatus::Balancing | GameStatus::BetPlacing2 | GameStatus::BetPlacing3 | GameStatus::BetPlacing4 => {
-                            match game_status {
-                                GameStatus::BetPlacing2 | GameStatus::BetPlacing3 | GameStatus::BetPlacing4 => {
-                                    let mut game_clone = self.clone();
-                                    let mut players_left = 0;
-                                    for (ID, player) in game_clone.players.iter().enumerate() {
-                                        if let Role::PlaceBet = player.role {
-                                            players_left += 1;
-                                        }
-                                    }
-                                    if players_left == 1 {
-                                        self.game_status = GameStatus::Balancing;
-                                    }
-                                }
-                                _ => {}
-                            }
-                            let mut te_stack = String::new();
-                            if let GameStatus::Balancing = self.game_status {
-                                if self.five_cards.five_cards.get_basket_vec().len() == 0 {
-                                    return Err("Balancing has been done before!".to_string());
-                                }
-                                // 计算所有还留在场上的玩家的卡牌value
-                                // 最大者赢
-                                let mut winner_ID = 0;
-                                let mut max_value = 0;
-                                let mut max_category = FiveCardsCategory::HighCard;
+                    for p in result.iter_mut() {
+                        *p /= total;
+                    }
+                    result
+                }
+                None => vec![uniform; legal_actions.len()],
+            }
+        }
+    }
+
+    /// 最佳应对的计算器，按信息集记住最佳动作
+    struct BestResponse<'a, G: SmallGame, S: SmallGameStrategy> {
+        game: &'a G,
+        strategy: &'a S,
+        br_player: usize,
+        info_sets: HashMap<String, Vec<(G::State, f64)>>,
+        best_actions: HashMap<String, Action>,
+    }
+
+    impl<'a, G: SmallGame, S: SmallGameStrategy> BestResponse<'a, G, S> {
+        /// 收集应对者每个信息集里的状态，以及机会和对手到达该状态的概率
+        fn collect_info_sets(&mut self, state: &G::State, reach: f64) {
+            if self.game.is_terminal(state) {
+                return;
+            }
+            match self.game.current_player(state) {
+                None => {
+                    for (child, p) in self.game.chance_outcomes(state) {
+                        self.collect_info_sets(&child, reach * p);
+                    }
+                }
+                Some(player) if player == self.br_player => {
+                    let info_set = self.game.info_set(state, player);
+                    self.info_sets.entry(info_set).or_default().push((state.clone(), reach));
+                    for action in self.game.legal_actions(state) {
+                        let child = self.game.next_state(state, action);
+                        self.collect_info_sets(&child, reach);
+                    }
+                }
+                Some(player) => {
+                    let info_set = self.game.info_set(state, player);
+                    let legal_actions = self.game.legal_actions(state);
+                    let probabilities = self.strategy.action_probabilities(&info_set, &legal_actions);
+                    for (action, p) in legal_actions.iter().zip(probabilities.iter()) {
+                        let child = self.game.next_state(state, *action);
+                        self.collect_info_sets(&child, reach * p);
+                    }
+                }
+            }
+        }
+
+        /// 应对者在该状态下的期望收益
+        fn value(&mut self, state: &G::State) -> f64 {
+            if self.game.is_terminal(state) {
+                return self.game.utility(state, self.br_player);
+            }
+            match self.game.current_player(state) {
+                None => {
+                    let mut value = 0.0;
+                    for (child, p) in self.game.chance_outcomes(state) {
+                        value += p * self.value(&child);
+                    }
+                    value
+                }
+                Some(player) if player == self.br_player => {
+                    let info_set = self.game.info_set(state, player);
+                    let action = self.best_action(&info_set);
+                    let child = self.game.next_state(state, action);
+                    self.value(&child)
+                }
+                Some(player) => {
+                    let info_set = self.game.info_set(state, player);
+                    let legal_actions = self.game.legal_actions(state);
+                    let probabilities = self.strategy.action_probabilities(&info_set, &legal_actions);
+                    let mut value = 0.0;
+                    for (action, p) in legal_actions.iter().zip(probabilities.iter()) {
+                        if *p > 0.0 {
+                            let child = self.game.next_state(state, *action);
+                            value += p * self.value(&child);
+                        }
+                    }
+                    value
+                }
+            }
+        }
+
+        /// 信息集下使加权收益最大的动作
+        fn best_action(&mut self, info_set: &str) -> Action {
+            if let Some(action) = self.best_actions.get(info_set) {
+                return *action;
+            }
+            let states = self.info_sets.get(info_set).cloned().unwrap_or_default();
+            let legal_actions = self.game.legal_actions(&states[0].0);
+            let mut best = (legal_actions[0], f64::NEG_INFINITY);
+            for action in legal_actions {
+                let mut value = 0.0;
+                for (state, reach) in states.iter() {
+                    let child = self.game.next_state(state, action);
+                    value += reach * self.value(&child);
+                }
+                if value > best.1 {
+                    best = (action, value);
+                }
+            }
+            self.best_actions.insert(info_set.to_string(), best.0);
+            best.0
+        }
+    }
+
+    /// 计算br_player对策略的最佳应对收益（单位：筹码/手）
+    pub fn best_response_value<G: SmallGame, S: SmallGameStrategy>(game: &G, strategy: &S, br_player: usize) -> f64 {
+        let mut br = BestResponse {
+            game,
+            strategy,
+            br_player,
+            info_sets: HashMap::new(),
+            best_actions: HashMap::new(),
+        };
+        let initial_states = game.initial_states();
+        for (state, p) in initial_states.iter() {
+            br.collect_info_sets(state, *p);
+        }
+        let mut value = 0.0;
+        for (state, p) in initial_states.iter() {
+            value += p * br.value(state);
+        }
+        value
+    }
+
+    /// 策略的可剥削度（单位：mbb/手），两个位置的最佳应对收益取平均
+    pub fn exploitability<G: SmallGame, S: SmallGameStrategy>(game: &G, strategy: &S) -> f64 {
+        let value = (best_response_value(game, strategy, 0) + best_response_value(game, strategy, 1)) / 2.0;
+        value / game.big_blind() * 1000.0
+    }
+
+    const RANK_NAME: [&str; 3] = ["J", "Q", "K"];
+
+    /// Kuhn扑克：J、Q、K三张牌，各下1个底注，只有一次下注机会
+    pub struct KuhnPoker;
+
+    #[derive(Clone)]
+    pub struct KuhnState {
+        cards: [usize; 2],
+        history: String,
+    }
+
+    impl SmallGame for KuhnPoker {
+        type State = KuhnState;
+
+        fn initial_states(&self) -> Vec<(KuhnState, f64)> {
+            let mut states = Vec::new();
+            for i in 0..3 {
+                for j in 0..3 {
+                    if i != j {
+                        states.push((KuhnState { cards: [i, j], history: String::new() }, 1.0 / 6.0));
+                    }
+                }
+            }
+            states
+        }
+        fn is_terminal(&self, state: &KuhnState) -> bool {
+            matches!(state.history.as_str(), "pp" | "bp" | "bb" | "pbp" | "pbb")
+        }
+        fn utility(&self, state: &KuhnState, player: usize) -> f64 {
+            let winner_of_showdown = if state.cards[0] > state.cards[1] { 0 } else { 1 };
+            let (winner, amount) = match state.history.as_str() {
+                "pp" => (winner_of_showdown, 1.0),
+                "bp" => (0, 1.0),
+                "pbp" => (1, 1.0),
+                _ => (winner_of_showdown, 2.0),
+            };
+            if winner == player { amount } else { -amount }
+        }
+        fn current_player(&self, state: &KuhnState) -> Option<usize> {
+            Some(state.history.len() % 2)
+        }
+        fn chance_outcomes(&self, _state: &KuhnState) -> Vec<(KuhnState, f64)> {
+            Vec::new()
+        }
+        fn legal_actions(&self, _state: &KuhnState) -> Vec<Action> {
+            vec!['p', 'b']
+        }
+        fn next_state(&self, state: &KuhnState, action: Action) -> KuhnState {
+            let mut next = state.clone();
+            next.history.push(action);
+            next
+        }
+        fn info_set(&self, state: &KuhnState, player: usize) -> String {
+            format!("{0}:{1}", RANK_NAME[state.cards[player]], state.history)
+        }
+        fn big_blind(&self) -> f64 {
+            1.0
+        }
+    }
+
+    /// Leduc扑克：J、Q、K各两张，各下1个底注，两轮下注（2和4），每轮最多加注两次，第二轮前发一张公共牌
+    pub struct LeducPoker;
+
+    #[derive(Clone)]
+    pub struct LeducState {
+        cards: [usize; 2],
+        board: Option<usize>,
+        round: usize,
+        round_history: String,
+        history: String,
+        contrib: [i32; 2],
+        raises: usize,
+        folded: Option<usize>,
+        finished: bool,
+    }
+
+    impl LeducPoker {
+        /// 结束当前下注轮
+        fn end_round(&self, state: &mut LeducState) {
+            if state.round == 0 {
+                state.round = 1;
+                state.round_history.clear();
+                state.history.push('/');
+                state.raises = 0;
+            } else {
+                state.finished = true;
+            }
+        }
+    }
+
+    impl SmallGame for LeducPoker {
+        type State = LeducState;
+
+        fn initial_states(&self) -> Vec<(LeducState, f64)> {
+            let mut states = Vec::new();
+            for i in 0..6 {
+                for j in 0..6 {
+                    if i != j {
+                        states.push((LeducState {
+                            cards: [i, j],
+                            board: None,
+                            round: 0,
+                            round_history: String::new(),
+                            history: String::new(),
+                            contrib: [1, 1],
+                            raises: 0,
+                            folded: None,
+                            finished: false,
+                        }, 1.0 / 30.0));
+                    }
+                }
+            }
+            states
+        }
+        fn is_terminal(&self, state: &LeducState) -> bool {
+            state.folded.is_some() || state.finished
+        }
+        fn utility(&self, state: &LeducState, player: usize) -> f64 {
+            let other = 1 - player;
+            if let Some(folded) = state.folded {
+                return if folded == player { -state.contrib[player] as f64 } else { state.contrib[other] as f64 };
+            }
+            let board = state.board.map(|b| b / 2).unwrap_or(usize::MAX);
+            let strength = |card: usize| if card / 2 == board { 10 + card / 2 } else { card / 2 };
+            let (mine, theirs) = (strength(state.cards[player]), strength(state.cards[other]));
+            if mine > theirs {
+                state.contrib[other] as f64
+            } else if mine < theirs {
+                -state.contrib[player] as f64
+            } else {
+                0.0
+            }
+        }
+        fn current_player(&self, state: &LeducState) -> Option<usize> {
+            if state.round == 1 && state.board.is_none() {
+                return None;
+            }
+            Some(state.round_history.len() % 2)
+        }
+        fn chance_outcomes(&self, state: &LeducState) -> Vec<(LeducState, f64)> {
+            let mut outcomes = Vec::new();
+            for card in 0..6 {
+                if card != state.cards[0] && card != state.cards[1] {
+                    let mut next = state.clone();
+                    next.board = Some(card);
+                    outcomes.push((next, 0.25));
+                }
+            }
+            outcomes
+        }
+        fn legal_actions(&self, state: &LeducState) -> Vec<Action> {
+            let player = state.round_history.len() % 2;
+            let mut actions = Vec::new();
+            if state.contrib[player] < state.contrib[1 - player] {
+                actions.push('f');
+            }
+            actions.push('c');
+            if state.raises < 2 {
+                actions.push('r');
+            }
+            actions
+        }
+        fn next_state(&self, state: &LeducState, action: Action) -> LeducState {
+            let mut next = state.clone();
+            let player = state.round_history.len() % 2;
+            let other = 1 - player;
+            next.round_history.push(action);
+            next.history.push(action);
+            match action {
+                'f' => next.folded = Some(player),
+                'c' => {
+                    let facing_bet = next.contrib[player] < next.contrib[other];
+                    next.contrib[player] = next.contrib[other];
+                    if facing_bet || next.round_history.len() >= 2 {
+                        self.end_round(&mut next);
+                    }
+                }
+                _ => {
+                    let size = if state.round == 0 { 2 } else { 4 };
+                    next.contrib[player] = next.contrib[other] + size;
+                    next.raises += 1;
+                }
+            }
+            next
+        }
+        fn info_set(&self, state: &LeducState, player: usize) -> String {
+            let board = match state.board {
+                Some(card) => RANK_NAME[card / 2],
+                None => "",
+            };
+            format!("{0}{1}:{2}", RANK_NAME[state.cards[player] / 2], board, state.history)
+        }
+        fn big_blind(&self) -> f64 {
+            1.0
+        }
+    }
+
+    /// 高度抽象的单挑限注德州：起手牌按对随机牌的平均胜率分进几个桶，只打翻前一轮，
+    /// 小盲0.5、大盲1，每次加注1个大盲，最多加注三次，叫到底就按两个桶之间的胜率分底池
+    pub struct AbstractHoldem {
+        /// equities[i][j]：桶i对桶j的胜率，平局算一半
+        pub equities: Vec<Vec<f64>>,
+        /// probabilities[i][j]：小盲发到桶i、大盲发到桶j的概率
+        pub probabilities: Vec<Vec<f64>>,
+    }
+
+    #[derive(Clone)]
+    pub struct AbstractHoldemState {
+        buckets: [usize; 2],
+        history: String,
+        contrib: [f64; 2],
+        raises: usize,
+        folded: Option<usize>,
+        finished: bool,
+    }
+
+    impl AbstractHoldem {
+        /// 把169类起手牌按平均胜率从低到高排好，按组合数平均分进buckets个桶
+        pub fn from_equity_table(table: &EquityTable, buckets: usize) -> Result<AbstractHoldem, String> {
+            if buckets == 0 || buckets > HAND_CLASS_NUM {
+                return Err(format!("The bucket count {0} must be between 1 and {1}!", buckets, HAND_CLASS_NUM));
+            }
+            let counts: Vec<f64> = (0..HAND_CLASS_NUM).map(|index| HandClass::from_index(index).get_combos().len() as f64).collect();
+            let total: f64 = counts.iter().sum();
+            let average = |a: usize| {
+                let weight: f64 = table.combos[a].iter().sum();
+                (0..HAND_CLASS_NUM).map(|b| table.combos[a][b] * table.equities[a][b]).sum::<f64>() / weight
+            };
+            let mut order: Vec<usize> = (0..HAND_CLASS_NUM).collect();
+            order.sort_by(|a, b| average(*a).partial_cmp(&average(*b)).unwrap());
+            let mut bucket_of = vec![0; HAND_CLASS_NUM];
+            let mut seen = 0.0;
+            for index in order {
+                bucket_of[index] = ((seen / total * buckets as f64) as usize).min(buckets - 1);
+                seen += counts[index];
+            }
+            // 拿着a类时对手是b类的概率是b类不冲突的组合数除以剩下的1225种组合
+            let mut weights = vec![vec![0.0; buckets]; buckets];
+            let mut equities = vec![vec![0.0; buckets]; buckets];
+            for a in 0..HAND_CLASS_NUM {
+                let others: f64 = table.combos[a].iter().sum();
+                for b in 0..HAND_CLASS_NUM {
+                    let p = counts[a] / total * table.combos[a][b] / others;
+                    weights[bucket_of[a]][bucket_of[b]] += p;
+                    equities[bucket_of[a]][bucket_of[b]] += p * table.equities[a][b];
+                }
+            }
+            for i in 0..buckets {
+                for j in 0..buckets {
+                    equities[i][j] = if weights[i][j] > 0.0 { equities[i][j] / weights[i][j] } else { 0.5 };
+                }
+            }
+            Ok(AbstractHoldem { equities, probabilities: weights })
+        }
+    }
+
+    impl SmallGame for AbstractHoldem {
+        type State = AbstractHoldemState;
+
+        fn initial_states(&self) -> Vec<(AbstractHoldemState, f64)> {
+            let mut states = Vec::new();
+            for (i, row) in self.probabilities.iter().enumerate() {
+                for (j, p) in row.iter().enumerate() {
+                    if *p > 0.0 {
+                        states.push((AbstractHoldemState {
+                            buckets: [i, j],
+                            history: String::new(),
+                            contrib: [0.5, 1.0],
+                            raises: 0,
+                            folded: None,
+                            finished: false,
+                        }, *p));
+                    }
+                }
+            }
+            states
+        }
+        fn is_terminal(&self, state: &AbstractHoldemState) -> bool {
+            state.folded.is_some() || state.finished
+        }
+        fn utility(&self, state: &AbstractHoldemState, player: usize) -> f64 {
+            let other = 1 - player;
+            if let Some(folded) = state.folded {
+                return if folded == player { -state.contrib[player] } else { state.contrib[other] };
+            }
+            let equity = self.equities[state.buckets[player]][state.buckets[other]];
+            equity * (state.contrib[0] + state.contrib[1]) - state.contrib[player]
+        }
+        fn current_player(&self, state: &AbstractHoldemState) -> Option<usize> {
+            Some(state.history.len() % 2)
+        }
+        fn chance_outcomes(&self, _state: &AbstractHoldemState) -> Vec<(AbstractHoldemState, f64)> {
+            Vec::new()
+        }
+        fn legal_actions(&self, state: &AbstractHoldemState) -> Vec<Action> {
+            let player = state.history.len() % 2;
+            let mut actions = Vec::new();
+            if state.contrib[player] < state.contrib[1 - player] {
+                actions.push('f');
+            }
+            actions.push('c');
+            if state.raises < 3 {
+                actions.push('r');
+            }
+            actions
+        }
+        fn next_state(&self, state: &AbstractHoldemState, action: Action) -> AbstractHoldemState {
+            let mut next = state.clone();
+            let player = state.history.len() % 2;
+            let other = 1 - player;
+            next.history.push(action);
+            match action {
+                'f' => next.folded = Some(player),
+                // 小盲补齐后大盲还能加注，之后的跟注都结束这一轮
+                'c' => {
+                    next.contrib[player] = next.contrib[other];
+                    next.finished = next.history.len() >= 2;
+                }
+                _ => {
+                    next.contrib[player] = next.contrib[other] + 1.0;
+                    next.raises += 1;
+                }
+            }
+            next
+        }
+        fn info_set(&self, state: &AbstractHoldemState, player: usize) -> String {
+            format!("{0}:{1}", state.buckets[player], state.history)
+        }
+        fn big_blind(&self) -> f64 {
+            1.0
+        }
+    }
+}
+
+
+// 测试用：把名字列表转成座位上的玩家名
+#[cfg(test)]
+fn get_names(names: &[&str]) -> Vec<String> {
+    names.iter().map(|name| name.to_string()).collect()
+}
+
+// 测试用：每人500筹码、第0位小盲的脚本牌桌，已经开始第一手，seed不为空时洗牌
+#[cfg(test)]
+fn scripted_table(names: &[&str], scripts: &[&str], seed: Option<u64>) -> (Game, Vec<std::rc::Rc<std::cell::RefCell<module_bot::ScriptedStrategy>>>) {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use module_game::MyEvent;
+    use module_bot::ScriptedStrategy;
+    use StuffType::GeneralType;
+
+    let names = get_names(names);
+    let mut game = Game::new_with_players(&names).unwrap();
+    let mut strategies = Vec::new();
+    for (name, script) in names.iter().zip(scripts.iter()) {
+        let strategy = Rc::new(RefCell::new(ScriptedStrategy::parse(script).unwrap()));
+        game.set_strategy(name, strategy.clone());
+        strategies.push(strategy);
+    }
+    let stuff:Vec<(&StuffType,i32)> = vec![(&GeneralType("筹码",5),100)];
+    let _ = game.receive_my_event(MyEvent::ResetGame {assets:&stuff});
+    game.assign_blinds(0);
+    let _ = game.receive_my_event(MyEvent::StartNextGame);
+    if let Some(seed) = seed {
+        game.card_pool.shuffle_with_seed(seed);
+    }
+    (game, strategies)
+}
+
+
+// #[test]
+fn test_card() {
+    use module_card::*;
+
+    for i in 0..100 {
+        let mut cards = CardPool::new();
+        let mut game = Game::new();
+
+        println!("{0}\n", cards);
+        match game.five_cards.get_five_cards(&mut cards) {
+            Ok(T) => println!("{}", T),
+            Err(E) => println!("{}", E),
+        }
+        println!("{0}\n", game);
+    }
+}
+
+#[test]
+fn test_game() {
+    use module_game::*;
+    use StuffType::GeneralType;
+    let mut game = Game::new();
+
+    match game.receive_my_event(MyEvent::AddBot {num:9, profiles:None}) {
+        Ok(T) => println!("{}",T),
+        Err(E) => println!("{}",E),
+    }
+    println!("{0}\n", game);
+
+    let stuff:Vec<(&StuffType,i32)> = vec![(&GeneralType("洗发水",50),2),
+                                           (&GeneralType("牛肉干",30),4),
+                                           (&GeneralType("书本",20),6),
+                                           (&GeneralType("水杯",10),8),
+                                           (&GeneralType("直尺",5),10)];
+
+    match game.receive_my_event(MyEvent::ResetGame {assets:&stuff}) {
+        Ok(T) => println!("{}",T),
+        Err(E) => println!("{}",E),
+    }
+    println!("{0}\n", game);
+
+    for k in 0..10 {
+        match game.receive_my_event(MyEvent::StartNextGame) {
+            Ok(T) => println!("{}",T),
+            Err(E) => println!("{}",E),
+        }
+        println!("{0}\n", game);
+
+        match game.receive_my_event(MyEvent::PickCards) {
+            Ok(T) => println!("{}",T),
+            Err(E) => println!("{}",E),
+        }
+        println!("{0}\n", game);
+
+        for i in 0..4 {
+            match game.receive_my_event(MyEvent::PlaceABet_Auto) {
+                Ok(T) => println!("{}",T),
+                Err(E) => println!("{}",E),
+            }
+            println!("{0}\n", game);
+        }
 
-                                let mut game_clone = self.clone();
-                                for (ID, player) in game_clone.players.iter().enumerate() {
-                                    if let Role::PlaceBet = player.role {
-                                        let (value, category) = player.get_cards_max_value_and_category(&self.five_cards);
-                                        if value > max_value {
-                                            max_value = value;
-                                            max_category = category;
-                                            winner_ID = ID;
-                                        }
-                                    }
-                                }
-                                // 将所有玩家的bet输入到cashpool，并还卡
-                                let mut game_clone = self.clone();
-                                for (ID, player) in game_clone.players.iter_mut().enumerate() {
-                                    if self.players[ID].owned_bank.get_values_of_bank() < self.get_XiaoMang_value() {
-                                        match self.players[ID].send_owned_to_pool(&mut self.cash_pool) {
-                                            Ok(T) => te_stack.push_str(&T),
-                                            Err(E) => te_stack.push_str(&E),
-                                        }
-                                    }
-                                    match self.players[ID].send_bets_to_pool(&mut self.cash_pool) {
-                                        Ok(T) => te_stack.push_str(&T),
-                                        Err(E) => te_stack.push_str(&E),
-                                    }
-                                    match self.players[ID].send_cards_back(&mut self.card_pool) {
-                                        Ok(T) => te_stack.push_str(&T),
-                                        Err(E) => te_stack.push_str(&E),
-                                    }
-                                }
+        match game.receive_my_event(MyEvent::ConfirmBalance) {
+            Ok(T) => println!("{}",T),
+            Err(E) => println!("{}",E),
+        }
+        println!("{0}\n", game);
+    }
+}
 
-                                // 将cashpool输入到赢家
-                                match self.players[winner_ID].get_bets_from_pool(&mut self.cash_pool) {
-                                    Ok(T) => te_stack.push_str(&T),
-                                    Err(E) => te_stack.push_str(&E),
-                                }
-                                // 把五卡还回去
-                                match self.five_cards.send_cards_back(&mut self.card_pool) {
-                                    Ok(T) => te_stack.push_str(&T),
-                                    Err(E) => te_stack.push_str(&E),
-                                }
-                                // owned_bank为空的玩家设置为quit
-                                let mut game_clone = self.clone();
-                                for (ID, player) in game_clone.players.iter().enumerate() {
-                                    if player.owned_bank.get_values_of_bank() == 0 {
-                                        self.players[ID].role = Role::Quit;
-                                    }
-                                }
-                                return Ok(format!("{0} is the Winner of this game! He/She gets the cards group of {1}!", self.players[winner_ID].name, max_category) + &te_stack);
-                            }
-                            return Err(te_stack);
-                        },
-                    }
-                }
-                MyEvent::GiveUp => {
-                    match game_status {
-                        GameStatus::Setting => {
-                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
-                        },
-                        GameStatus::CardsPicking => {
-                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
-                        },
-                        GameStatus::BetPlacing1|GameStatus::BetPlacing2|GameStatus::BetPlacing3|GameStatus::BetPlacing4 => {
-                            match game_status {
-                                GameStatus::BetPlacing1 => self.game_status = GameStatus::BetPlacing2,
-                                GameStatus::BetPlacing2 => self.game_status = GameStatus::BetPlacing3,
-                                GameStatus::BetPlacing3 => self.game_status = GameStatus::BetPlacing4,
-                                GameStatus::BetPlacing4 => self.game_status = GameStatus::Balancing,
-                                _ => {}
-                            }
-                            let player_num = self.players.len();
-                            let mut te_stack = String::new();
-                            let ID = 0;
 
-                            // 我放弃
-                            self.players[ID].role = Role::GiveUp;
-                            // 剩下的玩家继续下注
-                            let mut game_clone = self.clone();
-                            'outer:for (ID, player) in game_clone.players.iter().enumerate() {
-                                if let MyEvent::PlaceABet {..} = event {
-                                    if player.name == "ME" {
-                                        continue;
-                                    }
-                                }
-                                if let GameStatus::Balancing = self.game_status {
-                                    if ID == self.last_XiaoMang_ID as usize {
-                                        return Ok(te_stack + &"Game End Normally!".to_string());
-                                    }
-                                }
-                                match self.players[ID].role {
-                                    Role::XiaoMang(value) => {
-                                        // 下注指定的资金
-                                        match self.players[ID].place_a_bet_with_value(value) {
-                                            Ok(T) => te_stack.push_str(&T),
-                                            Err(E) => te_stack.push_str(&E),
-                                        }
-                                    }
-                                    Role::DaMang(value) => {
-                                        // 检查是否其他人都放弃或者退出了
-                                        let mut last_player_ID = ID;
-                                        loop {
-                                            if last_player_ID == 0 {
-                                                last_player_ID = player_num - 1;
-                                            } else {
-                                                last_player_ID -= 1;
-                                            }
-                                            if last_player_ID == ID {
-                                                self.game_status = GameStatus::Balancing;
-                                                return Ok(te_stack + &format!("{0} win the game because nobody place a bet!", self.players[ID].name));
-                                            }
-                                            if let Role::PlaceBet|Role::DaMang(..)|Role::XiaoMang(..)|Role::Normal = self.players[last_player_ID].role {
-                                                break;
-                                            }
-                                        }
-                                        // 检查前面第一个不是旁观的角色是不是下注或放弃，如果是就可以下注，否则不能下注
-                                        let mut last_player_ID = ID;
-                                        loop {
-                                            if last_player_ID == 0 {
-                                                last_player_ID = player_num - 1;
-                                            } else {
-                                                last_player_ID -= 1;
-                                            }
-                                            if let Role::PlaceBet|Role::Normal|Role::GiveUp|Role::XiaoMang(..)|Role::DaMang(..) = self.players[last_player_ID].role {
-                                                if let Role::PlaceBet|Role::GiveUp = self.players[last_player_ID].role {
-                                                    break;
-                                                } else {
-                                                    continue 'outer;
-                                                }
-                                            }
-                                        }
-                                        // 下注指定的资金
-                                        match self.players[ID].place_a_bet_with_value(value) {
-                                            Ok(T) => te_stack.push_str(&T),
-                                            Err(E) => te_stack.push_str(&E),
-                                        }
-                                    }
-                                    Role::Normal|Role::PlaceBet => {
-                                        // 检查是否其他人都放弃或者退出了
-                                        let mut last_player_ID = ID;
-                                        loop {
-                                            if last_player_ID == 0 {
-                                                last_player_ID = player_num - 1;
-                                            } else {
-                                                last_player_ID -= 1;
-                                            }
-                                            if last_player_ID == ID {
-                                                self.game_status = GameStatus::Balancing;
-                                                return Ok(te_stack + &format!("{0} win the game because nobody place a bet!", self.players[ID].name));
-                                            }
-                                            if let Role::PlaceBet|Role::DaMang(..)|Role::XiaoMang(..)|Role::Normal = self.players[last_player_ID].role {
-                                                break;
-                                            }
-                                        }
-                                        // 检查前面第一个不是旁观的角色是不是下注或放弃，如果是就可以下注，否则不能下注
-                                        let mut last_player_ID = ID;
-                                        loop {
-                                            if last_player_ID == 0 {
-                                                last_player_ID = player_num - 1;
-                                            } else {
-                                                last_player_ID -= 1;
-                                            }
-                                            if let Role::PlaceBet|Role::Normal|Role::GiveUp|Role::XiaoMang(..)|Role::DaMang(..) = self.players[last_player_ID].role {
-                                                if let Role::PlaceBet|Role::GiveUp = self.players[last_player_ID].role {
-                                                    break;
-                                                } else {
-                                                    continue 'outer;
-                                                }
-                                            }
-                                        }
-                                        // 获取需要用于比较的value
-                                        let mut value = 0;
-                                        if let Role::PlaceBet = self.players[last_player_ID].role {
-                                            let players_clone = self.players.clone();
-                                            value = players_clone[last_player_ID].bet_bank.get_values_of_bank();
-                                        } else {
-                                            loop {
-                                                if last_player_ID == 0 {
-                                                    last_player_ID = player_num - 1;
-                                                } else {
-                                                    last_player_ID -= 1;
-                                                }
-                                                if last_player_ID == ID {
-                                                    value = 0;
-                                                    break;
-                                                }
-                                                if let Role::PlaceBet = self.players[last_player_ID].role {
-                                                    let players_clone = self.players.clone();
-                                                    value = players_clone[last_player_ID].bet_bank.get_values_of_bank();
-                                                    break;
-                                                }
-                                            }
-                                        }
-                                        // 根据上一者下注的资金来下注
-                                        let game_clone = self.clone();
-                                        match self.players[ID].place_a_bet_with_last_value(value,game_clone.get_max_bet_value(),game_clone.min_value_unit) {
-                                            Ok(T) => te_stack.push_str(&T),
-                                            Err(E) => te_stack.push_str(&E),
-                                        }
-                                    }
-                                    _ => {}
-                                }
-                            }
-                            return Ok(te_stack);
-                        },
-                        GameStatus::Balancing => {
-                            return Err(format!("Cannot do {0} while status of game is {1}!", event, game_status));
-                        },
-                    }
-                },
+#[test]
+fn test_exploitability() {
+    use module_exploit::*;
+
+    // Kuhn扑克的纳什均衡（alpha=0）不可被剥削
+    let mut nash = TabularStrategy::new();
+    for (info_set, bet) in [("J:", 0.0), ("Q:", 0.0), ("K:", 0.0),
+                            ("J:pb", 0.0), ("Q:pb", 1.0 / 3.0), ("K:pb", 1.0),
+                            ("J:b", 0.0), ("Q:b", 1.0 / 3.0), ("K:b", 1.0),
+                            ("J:p", 1.0 / 3.0), ("Q:p", 0.0), ("K:p", 1.0)] {
+        nash.set(info_set, vec![('p', 1.0 - bet), ('b', bet)]);
+    }
+    assert!(exploitability(&KuhnPoker, &nash).abs() < 1e-9);
+
+    // 均匀随机策略可以被剥削
+    // 均匀随机策略的可剥削度是已知的：Kuhn每手0.4583个底注，Leduc每手2.3736个底注
+    let uniform = TabularStrategy::new();
+    assert!((exploitability(&KuhnPoker, &uniform) - 1375.0 / 3.0).abs() < 1e-6);
+    assert!((exploitability(&LeducPoker, &uniform) - 2373.611111).abs() < 1e-3);
+
+    // 抽象德州：分成5个桶的概率加起来是1，强的桶对弱的桶胜率过半
+    let table = module_card::EquityTable::compute(20, 7);
+    let holdem = AbstractHoldem::from_equity_table(&table, 5).unwrap();
+    assert!((holdem.probabilities.iter().flatten().sum::<f64>() - 1.0).abs() < 1e-9);
+    assert!((0..4).all(|bucket| holdem.equities[bucket + 1][bucket] > 0.5));
+    assert!(AbstractHoldem::from_equity_table(&table, 0).is_err());
+    // 能弃牌就弃牌的策略：小盲位被加注抢走1个大盲，大盲位被弃掉小盲，平均0.75个大盲
+    let mut folder = TabularStrategy::new();
+    for bucket in 0..5 {
+        for history in ["", "r", "cr", "rr", "crr", "rrr", "crrr"] {
+            folder.set(&format!("{0}:{1}", bucket, history), vec![('f', 1.0)]);
+        }
+        folder.set(&format!("{0}:c", bucket), vec![('c', 1.0)]);
+    }
+    assert!((exploitability(&holdem, &folder) - 750.0).abs() < 1e-6);
+    // 只有一个桶时摊牌都是平分，从不弃牌的策略就是均衡，不可被剥削
+    let holdem = AbstractHoldem::from_equity_table(&table, 1).unwrap();
+    let mut caller = TabularStrategy::new();
+    for history in ["", "c", "r", "cr", "rr", "crr", "rrr", "crrr"] {
+        caller.set(&format!("0:{0}", history), vec![('c', 1.0)]);
+    }
+    assert!(exploitability(&holdem, &caller).abs() < 1e-6);
+}
+
+#[test]
+fn test_bot_profiles() {
+    use rand::prelude::*;
+    use module_game::*;
+    use module_bot::BotProfile;
+    use StuffType::GeneralType;
+
+    let profiles = BotProfile::load_profiles("config/bot_profiles.toml").unwrap();
+    assert_eq!(BotProfile::find(&profiles, "maniac"), Some(&BotProfile::maniac()));
+    assert!(BotProfile::parse_profiles("[[profile]]\nname = \"bad\"\nvpip = 0.1\npfr = 0.2\naggression_factor = 1.0\nbig_raise_ratio = 1.0\nbluff_frequency = 0.0\n").is_err());
+    assert!(BotProfile::new("bad", 0.3, 0.2, -1.0, 1.0, 0.0).is_err());
+    assert!(BotProfile::parse_profiles("[[profile]]\nname = \"old\"\nvpip = 0.3\npfr = 0.2\nbig_raise_ratio = 1.0\nbluff_frequency = 0.0\n").is_err());
+
+    let mut game = Game::new();
+    assert!(game.receive_my_event(MyEvent::AddBot {num:2, profiles:Some(&profiles)}).is_err());
+    let seats = vec![BotProfile::tight_passive(), BotProfile::loose_aggressive(), BotProfile::maniac()];
+    assert!(game.receive_my_event(MyEvent::AddBot {num:3, profiles:Some(&seats)}).is_ok());
+    assert_eq!(game.players[3].profile.name, "maniac");
+
+    // 固定一个要跟注的局面，只换底牌强度，数出每种性格的入池率、翻前加注率和翻牌后的激进度，和参数对得上
+    let stuff:Vec<(&StuffType,i32)> = vec![(&GeneralType("筹码",5),100)];
+    let _ = game.receive_my_event(MyEvent::ResetGame {assets:&stuff});
+    let mut context = game.get_decision_context(1, 0, 20, 0);
+    context.bet_value = 0;
+    context.last_player_value = 20;
+    let mut rng = StdRng::seed_from_u64(1);
+    let num = 20000;
+    let mut factors = Vec::new();
+    for profile in profiles.iter() {
+        let mut counts = [[0; 3]; 2];
+        for street in 0..2 {
+            context.street = street;
+            for index in 0..num {
+                context.strength = (index as f64 + 0.5) / num as f64;
+                let kind = match profile.choose(&mut rng, &context) {
+                    None => 0,
+                    Some(false) => 1,
+                    Some(true) => 2,
+                };
+                counts[street][kind] += 1;
             }
         }
+        let (bluff, vpip) = (profile.bluff_frequency, profile.vpip);
+        let get_rate = |count: i32| count as f64 / num as f64;
+        assert!((1.0 - get_rate(counts[0][0]) - (bluff + (1.0 - bluff) * vpip)).abs() < 0.02, "{0}", profile);
+        assert!((get_rate(counts[0][2]) - (bluff + (1.0 - bluff) * profile.pfr)).abs() < 0.02, "{0}", profile);
+        // 诈唬总是下注加注，范围内的牌按AF/(1+AF)下注加注
+        let rate = profile.get_aggressive_rate();
+        let expected = (bluff + (1.0 - bluff) * vpip * rate) / ((1.0 - bluff) * vpip * (1.0 - rate));
+        let factor = counts[1][2] as f64 / counts[1][1] as f64;
+        assert!((factor / expected - 1.0).abs() < 0.1, "{0}: {1} != {2}", profile, factor, expected);
+        factors.push((profile.name.clone(), factor));
+    }
+    let get_factor = |name: &str| factors.iter().find(|item| item.0 == name).unwrap().1;
+    assert!(get_factor("tight-passive") < 1.0);
+    assert!(get_factor("tight-passive") < get_factor("loose-aggressive") && get_factor("loose-aggressive") < get_factor("maniac"));
+}
+
+#[test]
+fn test_opponent_stats() {
+    use module_game::*;
+    use module_stats::*;
+
+    // Alice翻前加注并在翻牌圈持续下注，Bob跟注后面对持续下注弃牌
+    let mut stats = OpponentStats::new();
+    let names = get_names(&["Alice", "Bob"]);
+    stats.start_hand(&names);
+    stats.record_action("Alice", 0, ActionType::Raise);
+    stats.record_action("Bob", 0, ActionType::Call);
+    stats.record_action("Alice", 1, ActionType::Raise);
+    stats.record_action("Bob", 1, ActionType::Fold);
+    stats.start_hand(&names);
+    stats.record_action("Alice", 0, ActionType::Fold);
+    stats.record_action("Bob", 0, ActionType::Check);
+    let alice = stats.get("Alice").unwrap();
+    let bob = stats.get("Bob").unwrap();
+    // AF只算翻牌后的行动，Alice翻前的加注不算
+    assert_eq!((alice.vpip(), alice.pfr(), alice.aggression_factor()), (0.5, 0.5, 1.0));
+    assert_eq!((bob.calls, bob.aggression_factor()), (0, 0.0));
+    assert_eq!((bob.vpip(), bob.fold_to_cbet(), bob.showdown_frequency()), (0.5, 1.0, 0.0));
+
+    // 游戏中自动统计：Cara跟大盲，Alice补齐，Bob过牌；翻牌Alice下注80，Bob跟注，Cara弃牌；Alice和Bob过牌到摊牌
+    let (mut game, _) = scripted_table(&["Alice", "Bob", "Cara"], &["c r80 x", "x c x", "c f"], Some(7));
+    let _ = game.receive_my_event(MyEvent::PickCards);
+    for _ in 0..4 {
+        let _ = game.receive_my_event(MyEvent::PlaceABet_Auto);
+    }
+    game.receive_my_event(MyEvent::ConfirmBalance).unwrap();
+    // 手数、入池、翻前加注、下注加注、跟注、看到翻牌、摊牌
+    let counts = |name: &str| {
+        let stats = game.opponent_stats.get(name).unwrap();
+        (stats.hands, stats.vpip_hands, stats.pfr_hands, stats.bets_and_raises, stats.calls, stats.flops_seen, stats.showdowns)
+    };
+    assert_eq!(counts("Alice"), (1, 1, 0, 1, 0, 1, 1));
+    assert_eq!(counts("Bob"), (1, 0, 0, 0, 1, 1, 1));
+    assert_eq!(counts("Cara"), (1, 1, 0, 0, 0, 1, 0));
+}
+
+#[test]
+fn test_arena() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use module_arena::*;
+    use module_bot::{BotAction, BotProfile, DecisionContext, Strategy};
+
+    let run = |seed:u64| -> ArenaReport {
+        let mut arena = Arena::new(seed);
+        arena.sessions = 2;
+        arena.hands_per_session = 10;
+        arena.add_profile(BotProfile::tight_passive()).unwrap();
+        arena.add_profile(BotProfile::loose_aggressive()).unwrap();
+        arena.add_profile(BotProfile::maniac()).unwrap();
+        assert!(arena.add_profile(BotProfile::maniac()).is_err());
+        arena.run().unwrap()
+    };
+    let report = run(42);
+    assert_eq!(report.standings.len(), 3);
+    assert_eq!(report.to_string().lines().count(), 3);
+    assert!(report.standings.iter().all(|standing| standing.hands > 0 && standing.ci95.is_finite()));
+    // Elo是零和的
+    let elo_sum:f64 = report.standings.iter().map(|standing| standing.elo).sum();
+    assert!((elo_sum - 4500.0).abs() < 1e-6);
+    // 相同种子结果相同
+    let again = run(42);
+    for (a, b) in report.standings.iter().zip(again.standings.iter()) {
+        assert_eq!((&a.name, a.hands, a.bb_per_100), (&b.name, b.hands, b.bb_per_100));
+    }
+
+    // 能过牌就过牌、否则弃牌的策略，每手都被总是加注的策略抢走盲注
+    struct Folder;
+    impl Strategy for Folder {
+        fn decide(&mut self, context: &DecisionContext) -> BotAction {
+            context.get_default_action()
+        }
+    }
+    struct Raiser;
+    impl Strategy for Raiser {
+        fn decide(&mut self, context: &DecisionContext) -> BotAction {
+            match context.raise_range {
+                Some((min, _)) => BotAction::Raise(min),
+                None if context.need_to_call() => BotAction::Call,
+                None => BotAction::Check,
+            }
+        }
+    }
+    let mut arena = Arena::new(7);
+    arena.sessions = 5;
+    arena.hands_per_session = 10;
+    arena.add_strategy("folder", Box::new(|_| Rc::new(RefCell::new(Folder)))).unwrap();
+    arena.add_strategy("raiser", Box::new(|_| Rc::new(RefCell::new(Raiser)))).unwrap();
+    let report = arena.run().unwrap();
+    let raiser = report.standings.iter().find(|standing| standing.name == "raiser").unwrap();
+    let folder = report.standings.iter().find(|standing| standing.name == "folder").unwrap();
+    assert!(raiser.bb_per_100 > 0.0 && raiser.bb_per_100 - raiser.ci95 > 0.0);
+    assert!(folder.bb_per_100 + folder.ci95 < 0.0);
+    assert_eq!(report.standings[0].name, "raiser");
+}
+
+#[test]
+fn test_external_bot() {
+    use module_game::*;
+    use module_config::GameConfig;
+    use module_protocol::*;
+
+    // 把请求的id原样带回去，总是跟注
+    const ECHO_ID_BOT: &str = r#"while read line; do id=$(echo "$line" | sed 's/^{"type":"act","id":\([0-9]*\).*/\1/'); echo "{\"id\":$id,\"action\":\"call\"}"; done"#;
+
+    // 挂上的机器人按规则里的时限等回复
+    let config = GameConfig::builder().starting_stack(500).action_timeout_ms(300).build().unwrap();
+    let mut game = Game::new_with_config(config, &vec!["Alice".to_string(), "Bob".to_string()]).unwrap();
+    let caller = game.attach_external_bot("Alice", "sh", &vec!["-c", ECHO_ID_BOT]).unwrap();
+    let sleeper = game.attach_external_bot("Bob", "sh", &vec!["-c", "while read line; do sleep 1; done"]).unwrap();
+    assert!(game.attach_external_bot("Nobody", "sh", &vec![]).is_err());
+    game.assign_blinds(0);
+    let _ = game.receive_my_event(MyEvent::StartNextGame);
+    let _ = game.receive_my_event(MyEvent::PickCards);
+    let _ = game.receive_my_event(MyEvent::PlaceABet_Auto);
+    assert!(caller.borrow().errors.is_empty());
+    assert_eq!(sleeper.borrow().errors[0], "The bot does not reply within 300ms!");
+
+    // 不合法的回复会被拒绝
+    let context = game.get_decision_context(0, 1, 1000, 0);
+    assert!(decode_reply(1, "{\"id\":1,\"action\":\"check\"}", &context).is_err());
+    assert!(decode_reply(1, "{\"id\":1,\"action\":\"raise\",\"value\":1}", &context).is_err());
+    assert!(decode_reply(1, "{\"action\":\"fold\"}", &context).is_err());
+    assert!(decode_reply(2, "{\"id\":1,\"action\":\"fold\"}", &context).is_err());
+    assert_eq!(decode_reply(1, "{\"id\":1,\"action\":\"fold\"}", &context), Ok(module_bot::BotAction::Fold));
+    // 请求是一行JSON，带上编号、局面和合法行动
+    let request = encode_request(1, &context).unwrap();
+    assert!(request.starts_with("{\"type\":\"act\",\"id\":1,") && !request.contains('\n'));
+    let request: serde_json::Value = serde_json::from_str(&request).unwrap();
+    assert_eq!(request["state"], serde_json::to_value(&context).unwrap());
+    assert_eq!(request["legal_actions"], serde_json::to_value(context.get_legal_actions()).unwrap());
+
+    // 先到的是上一个请求迟到的回复，丢掉后继续等这一次的
+    let late = r#"while read line; do id=$(echo "$line" | sed 's/^{"type":"act","id":\([0-9]*\).*/\1/'); echo "{\"id\":$((id-1)),\"action\":\"call\"}"; echo "{\"id\":$id,\"action\":\"fold\"}"; done"#;
+    let mut bot = ExternalStrategy::spawn("sh", &vec!["-c", late], std::time::Duration::from_secs(5)).unwrap();
+    assert_eq!(bot.request(&context), Ok(module_bot::BotAction::Fold));
+    assert_eq!(bot.request(&context), Ok(module_bot::BotAction::Fold));
+    assert_eq!(bot.errors, vec!["Discard the late reply to request 0!".to_string(), "Discard the late reply to request 1!".to_string()]);
+}
+
+#[test]
+fn test_scripted_hand() {
+    use module_game::*;
+
+    let (mut game, strategies) = scripted_table(&["Alice", "Bob", "Cara"], &["c r80 x", "x c x", "c f"], Some(7));
+    let bets = |game:&Game| -> Vec<i32> { game.players.iter().map(|player| player.bet_bank.get_values_of_bank()).collect() };
+
+    // 小盲20，大盲40，Cara跟注
+    let _ = game.receive_my_event(MyEvent::PickCards);
+    assert_eq!(bets(&game), vec![20, 40, 40]);
+    // Alice补齐，Bob过牌，行动回到跟过大盲的Cara，这一轮结束
+    let _ = game.receive_my_event(MyEvent::PlaceABet_Auto);
+    assert_eq!(bets(&game), vec![40, 40, 40]);
+    // Alice加注到80，Bob跟注，Cara弃牌
+    let _ = game.receive_my_event(MyEvent::PlaceABet_Auto);
+    assert_eq!(bets(&game), vec![80, 80, 40]);
+    // Alice和Bob过牌
+    let _ = game.receive_my_event(MyEvent::PlaceABet_Auto);
+    let _ = game.receive_my_event(MyEvent::PlaceABet_Auto);
+    assert_eq!(bets(&game), vec![80, 80, 40]);
+    for strategy in strategies.iter() {
+        assert_eq!(strategy.borrow().remaining(), 0);
     }
+    // 公共牌J Q 6 4 10，Alice的A7高牌赢Bob的73，拿走200的底池
+    let result = game.receive_my_event(MyEvent::ConfirmBalance).unwrap();
+    assert!(result.starts_with("Alice is the Winner of this game!"));
+    let totals:Vec<i32> = game.players.iter().map(|player| player.owned_bank.get_values_of_bank()).collect();
+    assert_eq!(totals, vec![620, 420, 460]);
+}
 
-    /// 给Game实现Display特性
-    impl Display for Game {
-        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            write!(f, "游戏状态：{0}\n", self.game_status)?;
-            write!(f, "小盲下注：{0}\n", self.get_XiaoMang_value())?;
-            write!(f, "大盲下注：{0}\n", self.get_DaMang_value())?;
-            write!(f, "最大下注：{0}\n", self.get_max_bet_value())?;
-            write!(f, "玩家情况：\n")?;
-            for player in self.players.iter() {
-                write!(f, "{0}\n", player)?;
-            }
-            write!(f, "钱池情况：{0}\n", self.cash_pool)?;
-            write!(f, "卡组情况：{0}\n", self.card_pool)?;
-            write!(f, "五卡情况：{0}\n", self.five_cards)
-        }
+#[test]
+fn test_rigged_deck() {
+    use module_game::*;
+    use module_card::*;
+
+    assert!(Card::parse("Td").unwrap() == Card::parse("方块10").unwrap());
+    assert_eq!(Card::parse("10♠").unwrap().get_short_name(), "Ts");
+    assert!(Card::parse("1x").is_err());
+
+    let (mut game, _) = scripted_table(&["Alice", "Bob", "Cara"], &["c r80 x", "x c x", "c f"], None);
+    game.card_pool.pin_cards(DealTarget::Seat(0), &Card::parse_cards("As Ah").unwrap()).unwrap();
+    game.card_pool.pin_cards(DealTarget::Seat(1), &Card::parse_cards("Ks Kh").unwrap()).unwrap();
+    game.card_pool.pin_cards(DealTarget::Flop, &Card::parse_cards("2c 7d 9h").unwrap()).unwrap();
+    game.card_pool.pin_cards(DealTarget::Turn, &Card::parse_cards("3s").unwrap()).unwrap();
+    game.card_pool.pin_cards(DealTarget::River, &Card::parse_cards("Jc").unwrap()).unwrap();
+    // 同一张牌不能指定两次，也不能超过去向的容量
+    assert!(game.card_pool.pin_cards(DealTarget::Seat(2), &Card::parse_cards("As").unwrap()).is_err());
+    assert!(game.card_pool.pin_cards(DealTarget::River, &Card::parse_cards("Qc").unwrap()).is_err());
+
+    let _ = game.receive_my_event(MyEvent::PickCards);
+    assert_eq!(game.players[0].get_cards_names(), vec!["黑桃A".to_string(), "红桃A".to_string()]);
+    let board:Vec<String> = game.five_cards.board_order.iter().map(|card| card.get_short_name()).collect();
+    assert_eq!(board, vec!["2c", "7d", "9h", "3s", "Jc"]);
+    assert!(game.card_pool.pinned.is_empty());
+    for _ in 0..4 {
+        let _ = game.receive_my_event(MyEvent::PlaceABet_Auto);
     }
+    let result = game.receive_my_event(MyEvent::ConfirmBalance).unwrap();
+    assert!(result.starts_with("Alice"));
+    let totals:Vec<i32> = game.players.iter().map(|player| player.owned_bank.get_values_of_bank()).collect();
+    assert_eq!(totals, vec![620, 420, 460]);
 
-    /// 给状态机实现Display特性
-    impl Display for GameStatus {
-        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            match self {
-                GameStatus::Setting => write!(f, "等待玩家设置/等待玩家开始游戏"),
-                GameStatus::CardsPicking => write!(f, "等待玩家抽卡"),
-                GameStatus::BetPlacing1 => write!(f, "等待玩家第一轮下注"),
-                GameStatus::BetPlacing2 => write!(f, "等待玩家第二轮下注"),
-                GameStatus::BetPlacing3 => write!(f, "等待玩家第三轮下注"),
-                GameStatus::BetPlacing4 => write!(f, "等待玩家第四轮下注"),
-                GameStatus::Balancing => write!(f, "等待玩家结算/已结算，等待玩家开始游戏")
-            }
-        }
+    let pool = CardPool::from_order(&Card::parse_cards("As Ah Ks").unwrap()).unwrap();
+    assert_eq!(pool.deal_order.len(), 52);
+    assert_eq!(pool.deal_order[2].get_short_name(), "Ks");
+    assert!(CardPool::from_order(&Card::parse_cards("As As").unwrap()).is_err());
+}
+
+#[test]
+fn test_hand_history() {
+    use module_game::*;
+    use module_card::*;
+
+    let (mut game, _) = scripted_table(&["Alice", "Bob", "Cara"], &["c r80 x", "x c x", "c f"], None);
+    game.card_pool.pin_cards(DealTarget::Seat(0), &Card::parse_cards("As Ah").unwrap()).unwrap();
+    game.card_pool.pin_cards(DealTarget::Seat(1), &Card::parse_cards("Ks Kh").unwrap()).unwrap();
+    game.card_pool.pin_cards(DealTarget::Seat(2), &Card::parse_cards("Qs Qh").unwrap()).unwrap();
+    game.card_pool.pin_cards(DealTarget::Flop, &Card::parse_cards("2c 7d 9h").unwrap()).unwrap();
+    game.card_pool.pin_cards(DealTarget::Turn, &Card::parse_cards("3s").unwrap()).unwrap();
+    game.card_pool.pin_cards(DealTarget::River, &Card::parse_cards("Jc").unwrap()).unwrap();
+    let _ = game.receive_my_event(MyEvent::PickCards);
+    for _ in 0..4 {
+        let _ = game.receive_my_event(MyEvent::PlaceABet_Auto);
     }
+    let _ = game.receive_my_event(MyEvent::ConfirmBalance);
 
-    /// 给事件实现Display特性
-    impl Display for MyEvent<'_> {
-        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            match self {
-                MyEvent::AddBot { num, .. } => write!(f, "【添加{0}个机器人】", num),
-                MyEvent::ResetGame { .. } => write!(f, "【重新初始化游戏】"),
-                MyEvent::StartNextGame => write!(f, "【开始下一轮游戏】"),
-                MyEvent::PlaceABet { .. } => write!(f, "【下注】"),
-                MyEvent::PlaceABet_Auto => write!(f, "【自动下注】"),
-                MyEvent::PickCards => write!(f, "【抽卡】"),
-                MyEvent::ConfirmBalance => write!(f, "【确定结算】"),
-                MyEvent::GiveUp => write!(f, "【弃卡】"),
-            }
+    assert_eq!(game.histories.len(), 1);
+    let history = &game.histories[0];
+    assert_eq!(history.get_total_pot(), 200);
+    let text = history.to_pokerstars(Some("Cara"));
+    assert!(text.starts_with("PokerStars Hand #1: Hold'em No Limit (20/40) - "));
+    assert!(text.contains("Table 'TexasPoker' 10-max Seat #3 is the button\n"));
+    assert!(text.contains("Seat 1: Alice (500 in chips)\n"));
+    assert!(text.contains("Alice: posts small blind 20\nBob: posts big blind 40\n*** HOLE CARDS ***\n"));
+    // 只有hero自己的底牌写在Dealt to里，别人的牌只在摊牌时亮出来
+    assert!(text.contains("*** HOLE CARDS ***\nDealt to Cara [Qs Qh]\nCara: calls 40\n"));
+    assert!(!text.contains("Dealt to Alice") && !text.contains("Dealt to Bob"));
+    assert!(!history.to_pokerstars(None).contains("Dealt to"));
+    assert!(!history.to_pokerstars(Some("Alice")).contains("Qs Qh"));
+    let mut mucked = history.clone();
+    mucked.showdown.retain(|item| item.0 != "Bob");
+    let mucked_text = mucked.to_pokerstars(None);
+    assert!(mucked_text.contains("Seat 2: Bob (big blind) mucked [Ks Kh]\n"));
+    assert_eq!(module_history::HandHistory::parse_pokerstars(&mucked_text).unwrap().seats[1].hole_cards, vec!["Ks".to_string(), "Kh".to_string()]);
+    assert!(text.contains("*** FLOP *** [2c 7d 9h]\nAlice: bets 40\nBob: calls 40\nCara: folds\n"));
+    assert!(text.contains("*** RIVER *** [2c 7d 9h 3s] [Jc]\n"));
+    assert!(text.contains("*** SHOW DOWN ***\nAlice: shows [As Ah] (a pair)\nBob: shows [Ks Kh] (a pair)\nAlice collected 200 from pot\n"));
+    assert!(text.contains("Board [2c 7d 9h 3s Jc]\n"));
+    assert!(text.contains("Seat 1: Alice (small blind) showed [As Ah] and won (200) with a pair\n"));
+    assert!(text.contains("Seat 3: Cara (button) folded on the Flop\n"));
+}
+
+#[test]
+fn test_replay() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use module_game::*;
+    use module_bot::{BotProfile, ProfileStrategy};
+    use module_history::*;
+    use module_replay::*;
+    use StuffType::GeneralType;
+
+    // 用机器人打一批牌作为回放的语料
+    let names = get_names(&["Alice", "Bob", "Cara", "David"]);
+    let profiles = [BotProfile::default_profile(), BotProfile::tight_passive(), BotProfile::loose_aggressive(), BotProfile::maniac()];
+    let mut game = Game::new_with_players(&names).unwrap();
+    for (index, (name, profile)) in names.iter().zip(profiles.iter()).enumerate() {
+        game.set_strategy(name, Rc::new(RefCell::new(ProfileStrategy::new(profile.clone(), index as u64))));
+    }
+    let stuff:Vec<(&StuffType,i32)> = vec![(&GeneralType("筹码",5),100)];
+    let _ = game.receive_my_event(MyEvent::ResetGame {assets:&stuff});
+    game.assign_blinds(0);
+    for hand in 0..30 {
+        let _ = module_arena::play_hand(&mut game, hand);
+    }
+    assert!(game.histories.len() >= 10);
+    let mut text = String::new();
+    for history in game.histories.iter() {
+        let json = history.to_json().unwrap();
+        let result = replay(&HandHistory::from_json(&json).unwrap());
+        assert!(result.is_ok(), "{:?}\n{}", result, history.to_pokerstars(None));
+        text.push_str(&history.to_pokerstars(None));
+        text.push_str("\n\n");
+    }
+    let parsed = parse_pokerstars_file(&text).unwrap();
+    assert_eq!(parsed.len(), game.histories.len());
+    for history in parsed.iter() {
+        let result = replay(history);
+        assert!(result.is_ok(), "{:?}\n{}", result, history.to_pokerstars(None));
+    }
+
+    // 一步一步地回放，改过的记录对不上
+    let mut replayer = Replayer::new(&parsed[0]).unwrap();
+    assert!(replayer.step().unwrap().is_some());
+    assert!(matches!(replayer.game.game_status, GameStatus::BetPlacing1 | GameStatus::Balancing));
+    let mut tampered = parsed[0].clone();
+    tampered.awards[0].1 += 5;
+    assert!(replay(&tampered).is_err());
+
+    // 没人跟的加注退回的部分从最后一次加注里减掉，按减完的加注回放
+    let text = "PokerStars Hand #7: Hold'em No Limit (20/40) - 2026/10/19 12:00:00
+Table 'TexasPoker' 10-max Seat #3 is the button
+Seat 1: Alice (500 in chips)
+Seat 2: Bob (200 in chips)
+Seat 3: Cara (500 in chips)
+Alice: posts small blind 20
+Bob: posts big blind 40
+*** HOLE CARDS ***
+Cara: raises 260 to 300
+Alice: folds
+Bob: calls 160 and is all-in
+Uncalled bet (100) returned to Cara
+*** FLOP *** [2c 7d 9h]
+*** TURN *** [2c 7d 9h] [3s]
+*** RIVER *** [2c 7d 9h 3s] [Jc]
+*** SHOW DOWN ***
+Bob: shows [Ks Kh] (a pair)
+Cara: shows [As Ah] (a pair)
+Cara collected 420 from pot
+*** SUMMARY ***
+Total pot 420 | Rake 0
+";
+    let history = HandHistory::parse_pokerstars(text).unwrap();
+    let cara: Vec<HistoryAction> = history.actions.iter().filter(|entry| entry.name == "Cara").map(|entry| entry.action.clone()).collect();
+    assert_eq!(cara, vec![HistoryAction::Raise(200)]);
+    assert_eq!(history.get_total_pot(), 420);
+    let result = replay(&history);
+    assert!(result.is_ok(), "{:?}", result);
+    // 整个下注都退回时按跟注或过牌记下
+    let folded = text.replace("Bob: calls 160 and is all-in\nUncalled bet (100) returned to Cara", "Bob: folds\nUncalled bet (260) returned to Cara");
+    let history = HandHistory::parse_pokerstars(&folded).unwrap();
+    assert_eq!(history.actions.iter().find(|entry| entry.name == "Cara").unwrap().action, HistoryAction::Call(40));
+    assert!(HandHistory::parse_pokerstars(&text.replace("returned to Cara", "returned to Alice")).is_err());
+}
+
+#[test]
+fn test_save_and_load() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use module_game::*;
+    use module_bot::ScriptedStrategy;
+    use StuffType::GeneralType;
+
+    let names = get_names(&["Alice", "Bob", "Cara"]);
+    let (mut game, _) = scripted_table(&["Alice", "Bob", "Cara"], &["c", "x", "c x"], Some(7));
+    let _ = game.receive_my_event(MyEvent::PickCards);
+    let _ = game.receive_my_event(MyEvent::PlaceABet_Auto);
+
+    // 翻牌圈开始前存档再读档
+    let json = game.save_to_json().unwrap();
+    let mut loaded = Game::load_from_json(&json).unwrap();
+    assert!(loaded.strategies.is_empty());
+    assert!(matches!(loaded.game_status, GameStatus::BetPlacing1 | GameStatus::BetPlacing2));
+    assert_eq!(loaded.card_pool.card_pool.get_values_of_bank(), game.card_pool.card_pool.get_values_of_bank());
+    assert_eq!(loaded.card_pool.card_pool.get_basket_vec().len(), 52 - 6 - 5);
+    assert!(loaded.five_cards.board_order == game.five_cards.board_order);
+    // 读出来的筹码和原来的筹码是同一种东西
+    assert!(StuffType::OwnedType("筹码".to_string(),5) == GeneralType("筹码",5));
+    assert!(loaded.players[0].bet_bank.get_basket_vec() == game.players[0].bet_bank.get_basket_vec());
+
+    // 两边用同样的脚本打完这一手，结果一样
+    for game in [&mut game, &mut loaded] {
+        for (name, script) in names.iter().zip(["r80 x", "c x", "f"].iter()) {
+            game.set_strategy(name, Rc::new(RefCell::new(ScriptedStrategy::parse(script).unwrap())));
+        }
+        for _ in 0..3 {
+            let _ = game.receive_my_event(MyEvent::PlaceABet_Auto);
         }
+        let _ = game.receive_my_event(MyEvent::ConfirmBalance);
     }
+    let totals = |game:&Game| -> Vec<i32> { game.players.iter().map(|player| player.owned_bank.get_values_of_bank()).collect() };
+    assert_eq!(totals(&loaded), totals(&game));
+    assert_eq!(totals(&loaded)[2], 460);
+    assert_eq!(loaded.histories[0].to_pokerstars(None), game.histories[0].to_pokerstars(None));
 }
 
+#[test]
+fn test_undo() {
+    use module_game::*;
 
-// #[test]
-fn test_card() {
+    let (mut game, _) = scripted_table(&["Alice", "Bob", "Cara"], &["c c", "x x", "c x x"], None);
+    // 正式游戏不能悔棋
+    assert!(game.receive_my_event(MyEvent::Undo {steps:1}).is_err());
+
+    game.set_practice_mode(true);
+    let bets = |game:&Game| -> Vec<i32> { game.players.iter().map(|player| player.bet_bank.get_values_of_bank()).collect() };
+    let _ = game.receive_my_event(MyEvent::PickCards);
+    let cards = game.players[0].get_cards_names();
+    assert_eq!(bets(&game), vec![20, 40, 40]);
+    let _ = game.receive_my_event(MyEvent::PlaceABet_Auto);
+    assert_eq!(bets(&game), vec![40, 40, 40]);
+    assert_eq!(game.get_undo_steps(), 2);
+    // 被拒绝的事件不留快照，撤销一步撤的还是上一次下注
+    assert!(game.receive_my_event(MyEvent::PickCards).is_err());
+    assert!(game.receive_my_event(MyEvent::ConfirmBalance).is_err());
+    assert_eq!(game.get_undo_steps(), 2);
+
+    // 撤销一步，下注回到上一步
+    game.receive_my_event(MyEvent::Undo {steps:1}).unwrap();
+    assert_eq!(bets(&game), vec![20, 40, 40]);
+    assert_eq!(game.players[0].get_cards_names(), cards);
+    assert!(matches!(game.game_status, GameStatus::BetPlacing1));
+    // 重新下注
+    let _ = game.receive_my_event(MyEvent::PlaceABet_Auto);
+    assert_eq!(bets(&game), vec![40, 40, 40]);
+
+    // 一直撤销到发牌前，牌和筹码都回到原处
+    assert!(game.receive_my_event(MyEvent::Undo {steps:3}).is_err());
+    game.receive_my_event(MyEvent::Undo {steps:2}).unwrap();
+    assert_eq!(bets(&game), vec![0, 0, 0]);
+    assert!(game.players.iter().all(|player| player.get_cards_names().is_empty()));
+    assert_eq!(game.card_pool.card_pool.get_basket_vec().len(), 52);
+    assert!(game.cash_pool.cash_pool.get_basket_vec().is_empty());
+    assert!(game.histories.is_empty());
+    assert!(matches!(game.game_status, GameStatus::CardsPicking));
+    game.set_practice_mode(false);
+    assert_eq!(game.get_undo_steps(), 0);
+}
+
+#[test]
+fn test_game_events() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use module_game::*;
     use module_card::*;
+    use module_event::*;
+    use module_history::HistoryAction;
 
-    for i in 0..100 {
-        let mut cards = CardPool::new();
-        let mut game = Game::new();
+    let (mut game, _) = scripted_table(&["Alice", "Bob", "Cara"], &["c r80 x", "x c x", "c f"], None);
+    let log = Rc::new(RefCell::new(EventLog::default()));
+    game.add_observer_for("Alice", log.clone());
+    let receiver = game.subscribe();
+    game.card_pool.pin_cards(DealTarget::Seat(0), &Card::parse_cards("As Ah").unwrap()).unwrap();
+    game.card_pool.pin_cards(DealTarget::Flop, &Card::parse_cards("2c 7d 9h").unwrap()).unwrap();
+    let _ = game.receive_my_event(MyEvent::PickCards);
+    for _ in 0..4 {
+        let _ = game.receive_my_event(MyEvent::PlaceABet_Auto);
+    }
+    let _ = game.receive_my_event(MyEvent::ConfirmBalance);
 
-        println!("{0}\n", cards);
-        match game.five_cards.get_five_cards(&mut cards) {
-            Ok(T) => println!("{}", T),
-            Err(E) => println!("{}", E),
-        }
-        println!("{0}\n", game);
+    // 旁观的通道看不到底牌，以Alice身份订阅的只多了Alice自己的底牌
+    let events: Vec<GameEvent> = receiver.try_iter().collect();
+    assert!(events.iter().all(|event| !matches!(event, GameEvent::HoleCards { .. })));
+    let private: Vec<GameEvent> = log.borrow().events.iter().filter(|event| matches!(event, GameEvent::HoleCards { .. })).cloned().collect();
+    assert_eq!(private, vec![GameEvent::HoleCards { name: "Alice".to_string(), cards: vec!["As".to_string(), "Ah".to_string()] }]);
+    let public: Vec<GameEvent> = log.borrow().events.iter().filter(|event| !matches!(event, GameEvent::HoleCards { .. })).cloned().collect();
+    assert!(events == public);
+    assert_eq!(events[0], GameEvent::HandStarted { hand_id: 1, button: 2, seats: vec![(0, "Alice".to_string(), 500), (1, "Bob".to_string(), 500), (2, "Cara".to_string(), 500)] });
+    assert_eq!(events[1], GameEvent::CardsDealt { name: "Alice".to_string() });
+    assert_eq!(events[4], GameEvent::BlindPosted { name: "Alice".to_string(), value: 20, is_big: false });
+    assert_eq!(events[5], GameEvent::BlindPosted { name: "Bob".to_string(), value: 40, is_big: true });
+    assert_eq!(events[6], GameEvent::PlayerActed { name: "Cara".to_string(), street: 0, action: HistoryAction::Call(40), all_in: false });
+    let flop = events.iter().position(|event| matches!(event, GameEvent::StreetAdvanced { street: 1, .. })).unwrap();
+    assert_eq!(events[flop], GameEvent::StreetAdvanced { street: 1, board: vec!["2c".to_string(), "7d".to_string(), "9h".to_string()] });
+    assert_eq!(events[flop + 1], GameEvent::PlayerActed { name: "Alice".to_string(), street: 1, action: HistoryAction::Raise(80), all_in: false });
+    let streets: Vec<usize> = events.iter().filter_map(|event| if let GameEvent::StreetAdvanced { street, .. } = event { Some(*street) } else { None }).collect();
+    assert_eq!(streets, vec![1, 2, 3]);
+    let showdown: Vec<&GameEvent> = events.iter().filter(|event| matches!(event, GameEvent::Showdown { .. })).collect();
+    assert_eq!(showdown.len(), 2);
+    assert_eq!(events.last(), Some(&GameEvent::PotAwarded { name: game.histories[0].awards[0].0.clone(), value: 200 }));
+}
+
+#[test]
+fn test_views() {
+    use module_game::*;
+    use module_card::*;
+
+    let (mut game, _) = scripted_table(&["Alice", "Bob", "Cara"], &["c r80 x", "x c x", "c f"], None);
+    game.card_pool.pin_cards(DealTarget::Seat(0), &Card::parse_cards("As Ah").unwrap()).unwrap();
+    game.card_pool.pin_cards(DealTarget::Seat(1), &Card::parse_cards("Ks Kh").unwrap()).unwrap();
+    game.card_pool.pin_cards(DealTarget::Flop, &Card::parse_cards("2c 7d 9h").unwrap()).unwrap();
+    let _ = game.receive_my_event(MyEvent::PickCards);
+
+    // 翻牌前只能看到自己的底牌
+    let view = game.get_player_view(0).unwrap();
+    assert_eq!(view.hole_cards, vec!["As", "Ah"]);
+    assert!(view.table.seats[1].hole_cards.is_none());
+    assert!(view.table.board.is_empty());
+    assert_eq!(view.table.cards_left, 52 - 6 - 5);
+    let spectator = game.get_spectator_view();
+    assert!(spectator.seats.iter().all(|seat| seat.hole_cards.is_none()));
+    assert_eq!(spectator.pot_value, 100);
+    // 机器人拿到的信息里也没有别人的底牌
+    let context = game.get_decision_context(1, 0, 40, 0);
+    let request = module_protocol::encode_request(1, &context).unwrap();
+    assert!(request.contains("\"Ks\"") && !request.contains("\"As\"") && !request.contains("\"2c\""));
+
+    let _ = game.receive_my_event(MyEvent::PlaceABet_Auto);
+    assert_eq!(game.get_spectator_view().board, vec!["2c", "7d", "9h"]);
+    for _ in 0..3 {
+        let _ = game.receive_my_event(MyEvent::PlaceABet_Auto);
     }
+    // 摊牌时亮出留到最后的人的底牌
+    let spectator = game.get_spectator_view();
+    assert_eq!(spectator.board.len(), 5);
+    assert_eq!(spectator.seats[0].hole_cards, Some(vec!["As".to_string(), "Ah".to_string()]));
+    assert!(spectator.seats[2].hole_cards.is_none());
+    assert!(spectator.to_string().contains("Cara（暂时放弃）"));
 }
 
 #[test]
-fn test_game() {
+fn test_game_config() {
     use module_game::*;
-    use StuffType::GeneralType;
-    let mut game = Game::new();
+    use module_config::*;
 
-    match game.receive_my_event(MyEvent::AddBot {num:9}) {
-        Ok(T) => println!("{}",T),
-        Err(E) => println!("{}",E),
+    let config = GameConfig::load("config/table.toml").unwrap();
+    assert_eq!(config.max_players, 6);
+    assert_eq!(config.betting_structure, BettingStructure::NoLimit);
+    assert_eq!(config.get_action_timeout(), Some(std::time::Duration::from_millis(2000)));
+    // 没写的字段用默认值
+    let config_short = GameConfig::from_toml("small_blind = 10\nbig_blind = 20\n").unwrap();
+    assert_eq!(config_short.max_players, 10);
+    assert!(GameConfig::from_toml("variant = \"omaha\"").is_err());
+    assert!(GameConfig::from_toml("small_blind = 10").is_err());
+
+    // 构造器检查规则
+    assert!(GameConfig::builder().blinds(10, 5).build().is_err());
+    assert!(GameConfig::builder().blinds(3, 6).build().is_err());
+    assert!(GameConfig::builder().max_players(11).build().is_err());
+    assert!(GameConfig::builder().betting_structure(BettingStructure::FixedLimit).build().is_err());
+    assert!(GameConfig::builder().blinds(10, 20).starting_stack(15).build().is_err());
+    let config_built = GameConfig::builder().max_players(6).starting_stack(1000).blinds(5, 10).action_timeout_ms(2000).build().unwrap();
+    assert_eq!(config_built, config);
+
+    // 按规则建游戏，盲注和座位数都按规则来
+    let names = get_names(&["Alice", "Bob", "Cara"]);
+    let mut game = Game::new_with_config(config, &names).unwrap();
+    assert!(game.players.iter().all(|player| player.owned_bank.get_values_of_bank() == 1000));
+    assert_eq!((game.get_XiaoMang_value(), game.get_DaMang_value()), (5, 10));
+    for name in ["David", "Ederson", "Ford"] {
+        game.add_a_player(name).unwrap();
     }
-    println!("{0}\n", game);
+    assert!(game.add_a_player("Gavin").is_err());
+    game.players.truncate(3);
+    game.assign_blinds(0);
+    let _ = module_arena::play_hand(&mut game, 1);
+    let blinds: Vec<_> = game.histories[0].actions.iter().take(2).map(|entry| entry.action.clone()).collect();
+    assert!(blinds == vec![module_history::HistoryAction::SmallBlind(5), module_history::HistoryAction::BigBlind(10)]);
+    assert_eq!(game.players.iter().map(|player| player.owned_bank.get_values_of_bank()).sum::<i32>(), 3000);
+}
 
-    let stuff:Vec<(&StuffType,i32)> = vec![(&GeneralType("洗发水",50),2),
-                                           (&GeneralType("牛肉干",30),4),
-                                           (&GeneralType("书本",20),6),
-                                           (&GeneralType("水杯",10),8),
-                                           (&GeneralType("直尺",5),10)];
+#[test]
+fn test_betting_structures() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use module_game::*;
+    use module_config::*;
+    use module_bot::{LegalAction, ScriptedStrategy};
 
-    match game.receive_my_event(MyEvent::ResetGame {assets:&stuff}) {
-        Ok(T) => println!("{}",T),
-        Err(E) => println!("{}",E),
+    let names = get_names(&["Alice", "Bob", "Cara"]);
+    let start = |structure: BettingStructure, script: &str| -> Game {
+        let config = GameConfig::builder().betting_structure(structure).blinds(10, 20).starting_stack(1000).build().unwrap();
+        let mut game = Game::new_with_config(config, &names).unwrap();
+        game.set_strategy("Cara", Rc::new(RefCell::new(ScriptedStrategy::parse(script).unwrap())));
+        game.assign_blinds(0);
+        let _ = game.receive_my_event(MyEvent::StartNextGame);
+        let _ = game.receive_my_event(MyEvent::PickCards);
+        game
+    };
+
+    // 无限注：最小加注等于上一次加注的幅度，最多全下
+    let game = start(BettingStructure::NoLimit, "r60");
+    assert_eq!(game.players[2].bet_bank.get_values_of_bank(), 60);
+    assert_eq!(game.get_raise_range(0, 60, 0), Some((100, 1000)));
+    assert!(game.check_raise(0, 60, 0, 80).is_err());
+    assert!(game.check_raise(0, 60, 0, 100).is_ok());
+
+    // 底池限注：最多加到跟注后的底池大小
+    let game = start(BettingStructure::PotLimit, "r70");
+    assert_eq!(game.players[2].bet_bank.get_values_of_bank(), 70);
+    assert_eq!(game.get_raise_range(0, 70, 0), Some((120, 230)));
+
+    // 固定限注：每次加一个大盲，转牌后加两个大盲，每轮最多四次下注
+    let game = start(BettingStructure::FixedLimit, "r40");
+    assert_eq!(game.players[2].bet_bank.get_values_of_bank(), 40);
+    assert_eq!(game.get_raise_range(0, 40, 0), Some((60, 60)));
+    let context = game.get_decision_context(0, 2, 40, 0);
+    assert!(context.get_legal_actions().contains(&LegalAction::Raise { min: 60, max: 60 }));
+    assert!(context.check_action(&module_bot::BotAction::Raise(80)).is_err());
+    assert_eq!(game.get_raise_range(0, 40, 2), Some((80, 80)));
+    let mut capped = game.clone();
+    for (name, to) in [("Alice", 60), ("Bob", 80)] {
+        capped.histories[0].record(name, 0, module_history::HistoryAction::Raise(to), false);
     }
-    println!("{0}\n", game);
+    assert_eq!(capped.get_raise_range(2, 80, 0), None);
+}
 
-    for k in 0..10 {
-        match game.receive_my_event(MyEvent::StartNextGame) {
-            Ok(T) => println!("{}",T),
-            Err(E) => println!("{}",E),
-        }
-        println!("{0}\n", game);
+#[test]
+fn test_button_rules() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use module_game::*;
+    use module_config::GameConfig;
+    use module_player::Role;
+    use module_bot::ScriptedStrategy;
+    use module_history::HistoryAction;
 
-        match game.receive_my_event(MyEvent::PickCards) {
-            Ok(T) => println!("{}",T),
-            Err(E) => println!("{}",E),
+    let start = |names: &Vec<String>| -> Game {
+        let config = GameConfig::builder().blinds(10, 20).starting_stack(1000).build().unwrap();
+        let mut game = Game::new_with_config(config, names).unwrap();
+        game.assign_blinds(0);
+        game
+    };
+    let get_actions = |game: &Game| -> Vec<(String, usize, HistoryAction)> {
+        game.histories[0].actions.iter().map(|entry| (entry.name.clone(), entry.street, entry.action.clone())).collect()
+    };
+
+    // 庄家、小盲、大盲每手往下走一位
+    let names = get_names(&["Alice", "Bob", "Cara", "David", "Ederson"]);
+    let mut game = start(&names);
+    assert_eq!((game.button_ID, game.last_XiaoMang_ID, game.last_DaMang_ID), (4, 0, 1));
+    game.move_button();
+    assert_eq!((game.button_ID, game.last_XiaoMang_ID, game.last_DaMang_ID), (0, 1, 2));
+    assert_eq!(game.get_action_order(0), vec![1, 2, 3, 4, 0]);
+    // 小盲出局后庄家留在空座位上
+    game.players[1].role = Role::Quit;
+    game.move_button();
+    assert_eq!((game.button_ID, game.last_XiaoMang_ID, game.last_DaMang_ID), (1, 2, 3));
+    // 大盲出局后下一手是死小盲
+    game.players[3].role = Role::Quit;
+    game.move_button();
+    assert_eq!((game.button_ID, game.last_XiaoMang_ID, game.last_DaMang_ID), (2, 3, 4));
+    assert!(game.is_XiaoMang_dead());
+    assert!(matches!(game.players[4].role, Role::DaMang(20)));
+    assert!(!game.players.iter().any(|player| matches!(player.role, Role::XiaoMang(..))));
+    assert_eq!(game.get_action_order(1), vec![3, 4, 0, 1, 2]);
+
+    // 死小盲时大盲照样下，翻前枪口先说话，翻后从庄家下一位开始
+    for (name, script) in [("Alice", "c x x x"), ("Cara", "c x x x"), ("Ederson", "x x x")] {
+        game.set_strategy(name, Rc::new(RefCell::new(ScriptedStrategy::parse(script).unwrap())));
+    }
+    let _ = game.receive_my_event(MyEvent::StartNextGame);
+    let _ = game.receive_my_event(MyEvent::PickCards);
+    for _ in 0..4 {
+        let _ = game.receive_my_event(MyEvent::PlaceABet_Auto);
+    }
+    let _ = game.receive_my_event(MyEvent::ConfirmBalance);
+    assert_eq!(game.histories[0].button, 2);
+    let actions = get_actions(&game);
+    assert_eq!(actions[..3].to_vec(), vec![("Ederson".to_string(), 0, HistoryAction::BigBlind(20)),
+                                           ("Alice".to_string(), 0, HistoryAction::Call(20)),
+                                           ("Cara".to_string(), 0, HistoryAction::Call(20))]);
+    let flop: Vec<String> = actions.iter().filter(|action| action.1 == 1).map(|action| action.0.clone()).collect();
+    assert_eq!(flop, vec!["Ederson", "Alice", "Cara"]);
+    let result = module_replay::replay(&game.histories[0]);
+    assert!(result.is_ok(), "{:?}", result);
+
+    // 两人时庄家下小盲，翻前先说话，翻后后说话
+    let names = get_names(&["Alice", "Bob"]);
+    let mut game = start(&names);
+    game.move_button();
+    assert_eq!((game.button_ID, game.last_XiaoMang_ID, game.last_DaMang_ID), (1, 1, 0));
+    assert_eq!(game.get_action_order(0), vec![1, 0]);
+    assert_eq!(game.get_action_order(1), vec![0, 1]);
+    for (name, script) in [("Alice", "x x"), ("Bob", "c x")] {
+        game.set_strategy(name, Rc::new(RefCell::new(ScriptedStrategy::parse(script).unwrap())));
+    }
+    let _ = game.receive_my_event(MyEvent::StartNextGame);
+    let _ = game.receive_my_event(MyEvent::PickCards);
+    let _ = game.receive_my_event(MyEvent::PlaceABet_Auto);
+    let _ = game.receive_my_event(MyEvent::PlaceABet_Auto);
+    assert_eq!(get_actions(&game), vec![("Bob".to_string(), 0, HistoryAction::SmallBlind(10)),
+                                        ("Alice".to_string(), 0, HistoryAction::BigBlind(20)),
+                                        ("Bob".to_string(), 0, HistoryAction::Call(10)),
+                                        ("Alice".to_string(), 0, HistoryAction::Check),
+                                        ("Alice".to_string(), 1, HistoryAction::Check),
+                                        ("Bob".to_string(), 1, HistoryAction::Check)]);
+}
+
+#[test]
+fn test_antes_and_straddles() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use module_game::*;
+    use module_config::{GameConfig, Straddle};
+    use module_bot::ScriptedStrategy;
+    use module_history::{HandHistory, HistoryAction};
+
+    let names = get_names(&["Alice", "Bob", "Cara", "David"]);
+    let start = |config: GameConfig, scripts: [&str; 4]| -> Game {
+        let mut game = Game::new_with_config(config, &names).unwrap();
+        for (name, script) in names.iter().zip(scripts.iter()) {
+            game.set_strategy(name, Rc::new(RefCell::new(ScriptedStrategy::parse(script).unwrap())));
         }
-        println!("{0}\n", game);
+        game.assign_blinds(0);
+        let _ = game.receive_my_event(MyEvent::StartNextGame);
+        game
+    };
+    let finish = |game: &mut Game| {
+        for _ in 0..4 {
+            let _ = game.receive_my_event(MyEvent::PlaceABet_Auto);
+        }
+        let _ = game.receive_my_event(MyEvent::ConfirmBalance);
+        let total: i32 = game.players.iter().map(|player| player.owned_bank.get_values_of_bank()).sum();
+        assert_eq!(total, 4000);
+    };
 
-        for i in 0..4 {
-            match game.receive_my_event(MyEvent::PlaceABet_Auto) {
-                Ok(T) => println!("{}",T),
-                Err(E) => println!("{}",E),
+    // 没有抓头时大盲过牌就结束翻前，已经跟过的Cara和David不再说话
+    let config = GameConfig::builder().blinds(10, 20).starting_stack(1000).build().unwrap();
+    let mut game = start(config, ["c x x", "x x x", "c x x", "c x x"]);
+    let _ = game.receive_my_event(MyEvent::PickCards);
+    let _ = game.receive_my_event(MyEvent::PlaceABet_Auto);
+    let preflop: Vec<(String, HistoryAction)> = game.histories[0].actions.iter().filter(|entry| entry.street == 0 && !entry.action.is_forced()).map(|entry| (entry.name.clone(), entry.action.clone())).collect();
+    assert_eq!(preflop, vec![("Cara".to_string(), HistoryAction::Call(20)), ("David".to_string(), HistoryAction::Call(20)),
+                             ("Alice".to_string(), HistoryAction::Call(10)), ("Bob".to_string(), HistoryAction::Check)]);
+    assert_eq!(game.get_pot_value(), 80);
+    let _ = game.receive_my_event(MyEvent::PlaceABet_Auto);
+    let flop: Vec<String> = game.histories[0].actions.iter().filter(|entry| entry.street == 1).map(|entry| entry.name.clone()).collect();
+    assert_eq!(flop, vec!["Alice", "Bob", "Cara", "David"]);
+    finish(&mut game);
+
+    // 加注的人被所有人跟注后也不用再说话
+    let config = GameConfig::builder().blinds(10, 20).starting_stack(1000).build().unwrap();
+    let mut game = start(config, ["c x x", "c x x", "r60 x x", "c x x"]);
+    let _ = game.receive_my_event(MyEvent::PickCards);
+    let _ = game.receive_my_event(MyEvent::PlaceABet_Auto);
+    let preflop: Vec<(String, HistoryAction)> = game.histories[0].actions.iter().filter(|entry| entry.street == 0 && !entry.action.is_forced()).map(|entry| (entry.name.clone(), entry.action.clone())).collect();
+    assert_eq!(preflop, vec![("Cara".to_string(), HistoryAction::Raise(60)), ("David".to_string(), HistoryAction::Call(60)),
+                             ("Alice".to_string(), HistoryAction::Call(50)), ("Bob".to_string(), HistoryAction::Call(40))]);
+    finish(&mut game);
+
+    // 每人前注5，枪口位Cara抓头40，庄家David要跟40，Cara翻前最后说话
+    let config = GameConfig::builder().blinds(10, 20).ante(5).straddles(vec![Straddle::UnderTheGun, Straddle::Button]).starting_stack(1000).build().unwrap();
+    let mut game = start(config, ["c x x", "c x x", "x x x", "c x x"]);
+    assert!(game.receive_my_event(MyEvent::Straddle { seat: 0 }).is_err());
+    assert!(game.receive_my_event(MyEvent::Straddle { seat: 2 }).is_ok());
+    let _ = game.receive_my_event(MyEvent::PickCards);
+    let actions: Vec<HistoryAction> = game.histories[0].actions.iter().map(|entry| entry.action.clone()).collect();
+    assert_eq!(actions, vec![HistoryAction::Ante(5), HistoryAction::Ante(5), HistoryAction::Ante(5), HistoryAction::Ante(5),
+                             HistoryAction::Straddle(40), HistoryAction::SmallBlind(10), HistoryAction::BigBlind(20), HistoryAction::Call(40)]);
+    assert_eq!(game.cash_pool.cash_pool.get_values_of_bank(), 20);
+    assert_eq!(game.get_pot_value(), 130);
+    assert_eq!(game.get_raise_range(0, 40, 0), Some((80, 995)));
+    let _ = game.receive_my_event(MyEvent::PlaceABet_Auto);
+    let preflop: Vec<(String, HistoryAction)> = game.histories[0].actions[8..].iter().map(|entry| (entry.name.clone(), entry.action.clone())).collect();
+    assert_eq!(preflop, vec![("Alice".to_string(), HistoryAction::Call(30)), ("Bob".to_string(), HistoryAction::Call(20)),
+                             ("Cara".to_string(), HistoryAction::Check)]);
+    assert_eq!(game.get_pot_value(), 180);
+    finish(&mut game);
+    assert!(game.histories[0].to_pokerstars(None).contains("Alice: posts the ante 5\n"));
+    let result = module_replay::replay(&game.histories[0]);
+    assert!(result.is_ok(), "{:?}", result);
+
+    // 大盲Bob替全桌下前注20，庄家David抓头，枪口位要跟40，David翻前最后说话
+    let config = GameConfig::builder().blinds(10, 20).ante(20).big_blind_ante(true).straddles(vec![Straddle::Button]).starting_stack(1000).build().unwrap();
+    let mut game = start(config, ["f", "c x x", "c x x", "x x x"]);
+    assert!(game.receive_my_event(MyEvent::Straddle { seat: 2 }).is_err());
+    assert!(game.receive_my_event(MyEvent::Straddle { seat: 3 }).is_ok());
+    let _ = game.receive_my_event(MyEvent::PickCards);
+    assert_eq!(game.players[2].bet_bank.get_values_of_bank(), 40);
+    assert_eq!(game.get_pot_value(), 130);
+    let _ = game.receive_my_event(MyEvent::PlaceABet_Auto);
+    let preflop: Vec<String> = game.histories[0].actions.iter().filter(|entry| !entry.action.is_forced()).map(|entry| entry.name.clone()).collect();
+    assert_eq!(preflop, vec!["Cara", "Alice", "Bob", "David"]);
+    finish(&mut game);
+    let text = game.histories[0].to_pokerstars(None);
+    assert!(text.contains("Bob: posts the ante 20\n") && text.contains("David: posts straddle 40\n"));
+    let result = module_replay::replay(&HandHistory::parse_pokerstars(&text).unwrap());
+    assert!(result.is_ok(), "{:?}", result);
+}
+
+#[test]
+fn test_blind_schedule() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use module_game::*;
+    use module_config::GameConfig;
+    use module_bot::ScriptedStrategy;
+    use module_event::GameEvent;
+    use module_tournament::*;
+
+    let schedule = BlindSchedule::from_toml("
+[[levels]]
+small_blind = 10
+big_blind = 20
+hands = 2
+
+[[levels]]
+small_blind = 20
+big_blind = 40
+ante = 5
+minutes = 10
+
+[[levels]]
+small_blind = 50
+big_blind = 100
+ante = 10
+hands = 1
+").unwrap();
+    assert!(schedule.levels[1].duration == LevelDuration::Minutes(10));
+    assert!(BlindSchedule::new(Vec::new()).check(5).is_err());
+
+    // 第一级打两手，第二级打十分钟，最后一级一直打下去
+    let names = get_names(&["Alice", "Bob", "Cara"]);
+    let config = GameConfig::builder().starting_stack(1000).build().unwrap();
+    let mut game = Game::new_with_config(config, &names).unwrap();
+    for name in names.iter() {
+        game.set_strategy(name, Rc::new(RefCell::new(ScriptedStrategy::parse(&["f"; 20].join(" ")).unwrap())));
+    }
+    let clock = MockClock::new();
+    assert!(game.start_tournament(schedule, Rc::new(clock.clone())).is_ok());
+    let receiver = game.subscribe();
+    let mut play = |game: &mut Game| -> (i32, i32) {
+        let _ = module_arena::play_hand(game, 0);
+        let history = game.histories.last().unwrap();
+        (history.small_blind, history.big_blind)
+    };
+    assert_eq!(play(&mut game), (10, 20));
+    assert_eq!(play(&mut game), (10, 20));
+    assert_eq!(play(&mut game), (20, 40));
+    assert_eq!(game.get_ante_value(), 5);
+    clock.advance_minutes(9);
+    assert_eq!(play(&mut game), (20, 40));
+    assert_eq!(game.blind_timer.as_ref().unwrap().get_time_left_ms(), Some(60_000));
+    clock.advance_minutes(1);
+    assert_eq!(play(&mut game), (50, 100));
+    assert_eq!(play(&mut game), (50, 100));
+    assert!(game.blind_timer.as_ref().unwrap().is_last_level());
+    let levels: Vec<usize> = receiver.try_iter().filter_map(|event| match event {
+        GameEvent::BlindLevelChanged { level, .. } => Some(level),
+        _ => None,
+    }).collect();
+    assert_eq!(levels, vec![2, 3]);
+    let total: i32 = game.players.iter().map(|player| player.owned_bank.get_values_of_bank()).sum();
+    assert_eq!(total, 3000);
+}
+
+#[test]
+fn test_sit_and_go() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use module_game::*;
+    use module_config::GameConfig;
+    use module_bot::{BotProfile, ProfileStrategy};
+    use module_tournament::*;
+    use module_sng::*;
+
+    let payouts = PayoutTable::from_toml("percents = [50, 30, 20]").unwrap();
+    assert!(PayoutTable::new(vec![60, 30]).check(4).is_err());
+    assert!(payouts.check(2).is_err());
+    assert_eq!(payouts.get_prizes(999), vec![501, 299, 199]);
+
+    // 四个疯子打到只剩一个人，前三名拿奖金
+    let names = get_names(&["Alice", "Bob", "Cara", "David"]);
+    let schedule = BlindSchedule::new(vec![
+        BlindLevel::new(25, 50, 0, LevelDuration::Hands(4)),
+        BlindLevel::new(50, 100, 10, LevelDuration::Hands(4)),
+        BlindLevel::new(100, 200, 25, LevelDuration::Hands(4)),
+        BlindLevel::new(200, 400, 50, LevelDuration::Hands(1)),
+    ]);
+    let config = GameConfig::builder().starting_stack(1000).blind_schedule(schedule).build().unwrap();
+    let mut game = Game::new_with_config(config, &names).unwrap();
+    for (index, name) in names.iter().enumerate() {
+        game.set_strategy(name, Rc::new(RefCell::new(ProfileStrategy::new(BotProfile::maniac(), index as u64))));
+    }
+    game.assign_blinds(0);
+    let mut sng = SitAndGo::new(game, 100, payouts).unwrap();
+    assert_eq!(sng.get_prize_pool(), 400);
+    assert!(sng.get_results().is_err());
+    let results = sng.run(7, 500).unwrap();
+    assert!(sng.is_finished());
+    assert_eq!(results.finishers.len(), 4);
+    assert_eq!(results.finishers.iter().map(|finisher| finisher.prize).sum::<i32>(), 400);
+    let winner = &results.finishers[0];
+    assert_eq!((winner.place, winner.prize, winner.hand), (1, 200, sng.hands_played));
+    let total: i32 = sng.game.players.iter().map(|player| player.owned_bank.get_values_of_bank()).sum();
+    let winner_ID = sng.game.players.iter().position(|player| player.name == winner.name).unwrap();
+    assert_eq!(sng.game.players[winner_ID].owned_bank.get_values_of_bank(), total);
+    for pair in results.finishers.windows(2) {
+        assert!(pair[0].place <= pair[1].place && pair[0].hand >= pair[1].hand);
+    }
+    assert!(sng.play_hand(0).is_err());
+    assert!(results.to_string().contains(&format!("第1名：{0}，奖金200", winner.name)));
+    assert_eq!(total, 4000);
+}
+
+#[test]
+fn test_last_player_standing() {
+    use module_game::*;
+    use module_player::Role;
+
+    let (mut game, _) = scripted_table(&["Alice", "Bob", "Cara"], &["c", "c", "c"], None);
+    let _ = game.receive_my_event(MyEvent::PickCards);
+    assert_eq!(game.get_pot_value(), 100);
+
+    // 别人都放弃了，最后留下的Cara没有处在已下注的状态，底池也要给她而不是座位0
+    game.players[0].role = Role::GiveUp;
+    game.players[1].role = Role::GiveUp;
+    game.players[2].role = Role::Normal;
+    game.game_status = GameStatus::Balancing;
+    let result = game.receive_my_event(MyEvent::ConfirmBalance).unwrap();
+    assert!(result.starts_with("Cara"), "{0}", result);
+    let totals:Vec<i32> = game.players.iter().map(|player| player.owned_bank.get_values_of_bank()).collect();
+    assert_eq!(totals, vec![480, 460, 560]);
+}
+
+#[test]
+fn test_multi_table_tournament() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use module_game::*;
+    use module_config::GameConfig;
+    use module_bot::{BotProfile, ProfileStrategy};
+    use module_tournament::*;
+    use module_sng::PayoutTable;
+    use module_mtt::MultiTableTournament;
+
+    // 两手之间才能换桌，名字不能重复
+    let names = get_names(&["Alice", "Bob", "Cara"]);
+    let mut game = Game::new_with_config(GameConfig::builder().starting_stack(1000).build().unwrap(), &names).unwrap();
+    let (player, strategy) = game.take_a_player(1).unwrap();
+    assert!(game.take_a_player(1).is_err());
+    assert!(game.seat_a_player(player.clone(), None).is_ok());
+    assert!(game.seat_a_player(player, strategy).is_err());
+    assert_eq!(game.get_player_num_left(), 3);
+
+    // 十个人四张桌子，每桌最多三人，前三名拿奖金
+    let names: Vec<String> = (0..10).map(|index| format!("Player{0}", index + 1)).collect();
+    let schedule = BlindSchedule::new(vec![
+        BlindLevel::new(25, 50, 0, LevelDuration::Hands(5)),
+        BlindLevel::new(50, 100, 10, LevelDuration::Hands(5)),
+        BlindLevel::new(100, 200, 25, LevelDuration::Hands(1)),
+    ]);
+    let config = GameConfig::builder().starting_stack(1000).blind_schedule(schedule).build().unwrap();
+    let payouts = PayoutTable::new(vec![50, 30, 20]);
+    assert!(MultiTableTournament::new(config.clone(), &names, 1, 100, payouts.clone(), Rc::new(MockClock::new())).is_err());
+    let mut mtt = MultiTableTournament::new(config, &names, 3, 100, payouts, Rc::new(MockClock::new())).unwrap();
+    mtt.bubble_margin = 2;
+    assert_eq!(mtt.get_table_sizes(), vec![3, 3, 2, 2]);
+    for (index, name) in names.iter().enumerate() {
+        let strategy = Rc::new(RefCell::new(ProfileStrategy::new(BotProfile::maniac(), index as u64)));
+        assert!(mtt.set_strategy(name, strategy).is_ok());
+    }
+    let mut hand_for_hand = false;
+    while !mtt.is_finished() {
+        assert!(mtt.rounds_played < 500);
+        let _ = mtt.play_round(mtt.rounds_played as u64).unwrap();
+        hand_for_hand |= mtt.hand_for_hand;
+        let sizes = mtt.get_table_sizes();
+        let players_left = mtt.get_player_num_left();
+        assert!(sizes.iter().max().unwrap() - sizes.iter().min().unwrap() <= 1 || players_left <= 1);
+        assert_eq!(sizes.len(), ((players_left + 2) / 3).max(1));
+        assert_eq!(mtt.final_table, sizes.len() == 1);
+        // 换过来的人坐到出局的人留下的座位上，桌子不会多出座位
+        assert!(mtt.tables.iter().all(|game| game.players.len() <= 3));
+        let total: i32 = mtt.tables.iter().flat_map(|game| game.players.iter()).map(|player| player.owned_bank.get_values_of_bank()).sum();
+        assert_eq!(total, 10000);
+    }
+    assert!(hand_for_hand);
+    let results = mtt.get_results().unwrap();
+    assert_eq!(results.finishers.len(), 10);
+    assert_eq!(results.finishers.iter().map(|finisher| finisher.prize).sum::<i32>(), 1000);
+    assert_eq!((results.finishers[0].place, results.finishers[0].prize), (1, 500));
+    assert!(mtt.find_player(&results.finishers[0].name).is_some());
+    assert!(mtt.find_player(&results.finishers[9].name).is_none());
+}
+
+#[test]
+fn test_icm() {
+    use module_game::*;
+    use module_config::GameConfig;
+    use module_icm::*;
+    use module_sng::*;
+
+    let close = |a: f64, b: f64| (a - b).abs() < 1e-6;
+    let equities = get_icm_equities(&vec![3000, 1000], &vec![70, 30]).unwrap();
+    assert!(close(equities[0], 60.0) && close(equities[1], 40.0));
+    // 第一名按筹码比例抽，第二名在剩下的人里再按比例抽
+    let equities = get_icm_equities(&vec![50, 30, 20, 0], &vec![50, 30, 20]).unwrap();
+    assert!(close(equities[0], 25.0 + 0.3 * 50.0 / 70.0 * 30.0 + 0.2 * 50.0 / 80.0 * 30.0 + 0.3 * 20.0 / 70.0 * 20.0 + 0.2 * 30.0 / 80.0 * 20.0));
+    assert!(close(equities.iter().sum::<f64>(), 100.0) && equities[3] == 0.0);
+    assert!(get_icm_equities(&vec![100; 21], &vec![100]).is_err());
+    assert_eq!(get_chip_equities(&vec![50, 30, 20], 1000), vec![500.0, 300.0, 200.0]);
+
+    // 赢家通吃时ICM和筹码期望一样，跟注要的胜率就是底池赔率
+    let spot = AllInSpot::new(vec![1000, 800], 200, 0, 1, vec![100]).unwrap();
+    assert!(close(spot.get_fold_ev().unwrap(), 50.0));
+    assert!(close(spot.get_required_equity().unwrap(), 800.0 / 1800.0));
+    assert!(close(spot.get_push_ev(0.0, 0.0).unwrap(), spot.get_steal_ev().unwrap()));
+    // 泡沫上短码还没出局，两个大码对决要比五五开更高的胜率
+    let spot = AllInSpot::new(vec![1000, 1000, 200], 0, 0, 1, vec![50, 50]).unwrap();
+    assert!(spot.get_required_equity().unwrap() > 0.55);
+    assert!(spot.get_push_ev(1.0, 0.5).unwrap() < spot.get_fold_ev().unwrap());
+    assert!(AllInSpot::new(vec![1000, 1000], 0, 1, 1, vec![100]).is_err());
+
+    // 单桌赛把奖金告诉牌桌，机器人能算ICM，也能按ICM提前分钱
+    let names = get_names(&["Alice", "Bob", "Cara"]);
+    let game = Game::new_with_config(GameConfig::builder().starting_stack(1000).build().unwrap(), &names).unwrap();
+    let sng = SitAndGo::new(game, 100, PayoutTable::new(vec![50, 30, 20])).unwrap();
+    assert_eq!(sng.game.prizes, vec![150, 90, 60]);
+    let equities = sng.game.get_decision_context(0, 0, 0, 0).get_icm_equities().unwrap();
+    assert!(equities.iter().all(|equity| close(*equity, 100.0)));
+    let deal = sng.get_icm_deal().unwrap();
+    assert!(deal.finishers.iter().all(|finisher| finisher.prize == 100));
+}
+
+#[test]
+fn test_push_fold_chart() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use rand::prelude::*;
+    use module_card::*;
+    use module_game::*;
+    use module_config::GameConfig;
+    use module_bot::ScriptedStrategy;
+    use module_history::HistoryAction;
+    use module_pushfold::*;
+
+    // 七张牌的快速算法和枚举21种组合的结果一样
+    let mut rng = StdRng::seed_from_u64(3);
+    let mut deck: Vec<usize> = (0..52).collect();
+    for _ in 0..500 {
+        deck.shuffle(&mut rng);
+        let seven = [deck[0], deck[1], deck[2], deck[3], deck[4], deck[5], deck[6]];
+        let cards: Vec<Card> = seven.iter().map(|index| get_card_by_index(*index)).collect();
+        let mut best = 0;
+        for i in 0..7 {
+            for j in (i + 1)..7 {
+                let five: Vec<&Card> = (0..7).filter(|k| *k != i && *k != j).map(|k| &cards[k]).collect();
+                best = best.max(get_five_cards_value(&five));
             }
-            println!("{0}\n", game);
         }
+        assert_eq!(get_seven_cards_value(&seven), best);
+    }
+    assert!((0..52).all(|index| get_card_index(&get_card_by_index(index)) == index));
+    let class = HandClass::parse("AKs").unwrap();
+    assert_eq!((class.get_grid_position(), class.get_name()), ((0, 1), "AKs".to_string()));
+    assert_eq!(HandClass::parse("AKo").unwrap().get_grid_position(), (1, 0));
+    assert!(HandClass::parse("AAs").is_err());
+    assert_eq!((0..HAND_CLASS_NUM).map(|index| HandClass::from_index(index).get_combos().len()).sum::<usize>(), 1326);
+    assert!((0..HAND_CLASS_NUM).all(|index| HandClass::parse(&HandClass::from_index(index).get_name()).unwrap().get_index() == index));
 
-        match game.receive_my_event(MyEvent::ConfirmBalance) {
-            Ok(T) => println!("{}",T),
-            Err(E) => println!("{}",E),
+    let table = EquityTable::compute(20, 7);
+    let (aces, seven_deuce) = (HandClass::parse("AA").unwrap().get_index(), HandClass::parse("72o").unwrap().get_index());
+    assert!(table.equities[aces][seven_deuce] > 0.8);
+    assert_eq!(table.combos[aces][aces], 1.0);
+
+    // 单挑时筹码越短全下越宽，大盲跟注比小盲全下紧
+    let solve = |stack: f64| PushFoldSpot::with_effective_stack(2, stack).unwrap().solve(&table, 200).unwrap();
+    let chart = solve(10.0);
+    let (push, call) = (get_range_percent(&chart.pushes[0]), get_range_percent(&chart.calls[0][1]));
+    assert!(push > 0.4 && push < 0.8 && call < push);
+    assert!(get_range_percent(&solve(4.0).pushes[0]) > push);
+    let (ace, king) = (Card::parse("As").unwrap(), Card::parse("Ks").unwrap());
+    assert!(chart.get_push_frequency(0, &ace, &king) > 0.99 && chart.get_call_frequency(0, 1, &ace, &king) > 0.99);
+    assert_eq!(chart.get_push_frequency(1, &ace, &king), 0.0);
+    assert!(chart.to_string().contains("第0位全下"));
+    // 三个人两个拿奖时的泡沫上，按ICM跟注比按筹码紧得多，所以第一个全下的人反而可以更宽
+    let chips = PushFoldSpot::new(vec![10.0; 3], 0.0, Vec::new()).unwrap().solve(&table, 100).unwrap();
+    let icm = PushFoldSpot::new(vec![10.0; 3], 0.0, vec![50, 50]).unwrap().solve(&table, 100).unwrap();
+    assert!(get_range_percent(&icm.calls[0][1]) < get_range_percent(&chips.calls[0][1]));
+    assert!(get_range_percent(&icm.calls[0][2]) < get_range_percent(&chips.calls[0][2]));
+    assert!(get_range_percent(&icm.pushes[0]) > get_range_percent(&chips.pushes[0]));
+
+    // 短码的机器人查表，要么全下要么弃牌
+    let names = get_names(&["Alice", "Bob"]);
+    let config = GameConfig::builder().blinds(10, 20).starting_stack(200).build().unwrap();
+    let mut game = Game::new_with_config(config, &names).unwrap();
+    for (index, name) in names.iter().enumerate() {
+        let inner = Rc::new(RefCell::new(ScriptedStrategy::parse("").unwrap()));
+        game.set_strategy(name, Rc::new(RefCell::new(PushFoldStrategy::new(chart.clone(), 15.0, inner, index as u64))));
+    }
+    game.assign_blinds(0);
+    assert_eq!(game.get_spectator_view().preflop_order, vec![0, 1]);
+    let _ = module_arena::play_hand(&mut game, 11);
+    let first = game.histories[0].actions.iter().find(|entry| !entry.action.is_forced()).unwrap();
+    assert!(first.action == HistoryAction::Fold || first.action == HistoryAction::Raise(200));
+
+    // 死庄时位置从引擎的说话顺序来：庄家坐出了，枪口还是大盲后面的人
+    let names = get_names(&["Alice", "Bob", "Cara", "David"]);
+    let config = GameConfig::builder().blinds(10, 20).starting_stack(200).build().unwrap();
+    let mut game = Game::new_with_config(config, &names).unwrap();
+    game.sit_out(0, false).unwrap();
+    game.set_positions(0, 1, 2);
+    let view = game.get_spectator_view();
+    assert_eq!(view.preflop_order, vec![3, 1, 2]);
+    assert!(view.seats[0].is_empty && view.seats[0].sitting_out && !view.seats[3].is_empty);
+}
+
+#[test]
+fn test_seat_management() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use module_game::*;
+    use module_config::GameConfig;
+    use module_bank::StuffType;
+    use module_bot::{BotProfile, ProfileStrategy};
+    use module_event::{EventLog, GameEvent};
+    use module_player::Role;
+    use module_arena::play_hand;
+
+    fn get_total(game: &Game) -> i32 {
+        game.cashier.bank.get_values_of_bank() + game.cash_pool.cash_pool.get_values_of_bank()
+            + game.players.iter().map(|player| player.owned_bank.get_values_of_bank() + player.bet_bank.get_values_of_bank()).sum::<i32>()
+    }
+    let config = GameConfig::builder().blinds(5, 10).buy_in(200, 1000).build().unwrap();
+    assert!(GameConfig::builder().blinds(5, 10).buy_in(1000, 200).build().is_err());
+    let mut game = Game::new_with_config(config, &Vec::new()).unwrap();
+    let chip = StuffType::OwnedType("筹码".to_string(), 5);
+    game.cashier.stock(&vec![(&chip, 2000)]).unwrap();
+    let log = Rc::new(RefCell::new(EventLog::default()));
+    game.add_observer(log.clone());
+
+    // 买入要在范围里，重名入座失败时筹码退回收银台
+    assert!(game.receive_my_event(MyEvent::JoinSeat { name: "Alice", buy_in: 100 }).is_err());
+    assert!(game.receive_my_event(MyEvent::JoinSeat { name: "Alice", buy_in: 1200 }).is_err());
+    let names = ["Alice", "Bob", "Cara", "David", "Ed"];
+    for (index, name) in names.iter().enumerate() {
+        game.receive_my_event(MyEvent::JoinSeat { name, buy_in: 500 }).unwrap();
+        game.set_strategy(name, Rc::new(RefCell::new(ProfileStrategy::new(BotProfile::tight_passive(), index as u64))));
+    }
+    assert!(game.receive_my_event(MyEvent::JoinSeat { name: "Alice", buy_in: 500 }).is_err());
+    assert_eq!(game.cashier.bank.get_values_of_bank(), 10000 - 2500);
+    assert_eq!(game.cashier.get_net_buy_in("Alice"), 500);
+    assert_eq!(get_total(&game), 10000);
+    game.assign_blinds(0);
+    play_hand(&mut game, 1).unwrap();
+
+    // Cara坐出去错过盲注，David坐出去照交盲注
+    game.receive_my_event(MyEvent::SitOut { seat: 2, post_blinds: false }).unwrap();
+    game.receive_my_event(MyEvent::SitOut { seat: 3, post_blinds: true }).unwrap();
+    assert!(game.receive_my_event(MyEvent::SitOut { seat: 2, post_blinds: false }).is_err());
+    let Cara_stack = game.players[2].owned_bank.get_values_of_bank();
+    let David_stack = game.players[3].owned_bank.get_values_of_bank();
+    for seed in 2..7 {
+        play_hand(&mut game, seed).unwrap();
+        assert!(matches!(game.players[2].role, Role::Quit) && matches!(game.players[3].role, Role::Quit));
+        assert_eq!(get_total(&game), 10000);
+    }
+    assert_eq!(game.players[2].owned_bank.get_values_of_bank(), Cara_stack);
+    assert_eq!(game.players[2].missed_blinds, 15);
+    let dead: i32 = log.borrow().events.iter().map(|event| match event {
+        GameEvent::DeadBlindPosted { name, value } if name == "David" => *value,
+        _ => 0,
+    }).sum();
+    assert!(dead >= 15);
+    assert_eq!(game.players[3].owned_bank.get_values_of_bank(), David_stack - dead);
+    assert!(game.histories.last().unwrap().seats.iter().all(|seat| seat.name != "Cara" && seat.name != "David"));
+
+    // 坐回来时补上错过的盲注
+    game.receive_my_event(MyEvent::SitIn { seat: 2 }).unwrap();
+    assert!(game.receive_my_event(MyEvent::SitIn { seat: 2 }).is_err());
+    assert_eq!(game.players[2].owned_bank.get_values_of_bank(), Cara_stack - 15);
+    assert_eq!(game.cash_pool.cash_pool.get_values_of_bank(), 15);
+    assert_eq!(game.players[2].missed_blinds, 0);
+
+    // 补码到最多买入，牌局中间不能动座位
+    let Bob_stack = game.players[1].owned_bank.get_values_of_bank();
+    game.receive_my_event(MyEvent::TopUp { seat: 1 }).unwrap();
+    assert_eq!(game.players[1].owned_bank.get_values_of_bank(), 1000);
+    assert_eq!(game.cashier.get_net_buy_in("Bob"), 500 + 1000 - Bob_stack);
+    assert!(game.receive_my_event(MyEvent::TopUp { seat: 1 }).is_err());
+    game.receive_my_event(MyEvent::StartNextGame).unwrap();
+    game.receive_my_event(MyEvent::PickCards).unwrap();
+    assert!(game.receive_my_event(MyEvent::CashOut { seat: 0 }).is_err());
+    assert!(game.receive_my_event(MyEvent::JoinSeat { name: "Fay", buy_in: 500 }).is_err());
+    play_hand(&mut game, 7).unwrap();
+    assert_eq!(get_total(&game), 10000);
+
+    // David兑现离桌，Fay坐到他的座位上
+    let David_stack = game.players[3].owned_bank.get_values_of_bank();
+    game.receive_my_event(MyEvent::CashOut { seat: 3 }).unwrap();
+    assert_eq!(game.players[3].name, "空座4");
+    assert_eq!(game.cashier.get_net_buy_in("David"), 500 - David_stack);
+    assert!(game.receive_my_event(MyEvent::CashOut { seat: 3 }).is_err());
+    game.receive_my_event(MyEvent::JoinSeat { name: "Fay", buy_in: 300 }).unwrap();
+    assert_eq!(game.players[3].name, "Fay");
+    assert!(log.borrow().events.contains(&GameEvent::PlayerSeated { name: "Fay".to_string(), seat: 3 }));
+
+    // Ed输光后重新买入
+    assert!(game.receive_my_event(MyEvent::Rebuy { seat: 4, value: 500 }).is_err());
+    let Ed_stack = game.cashier.redeem("Ed", &mut game.players[4].owned_bank).unwrap();
+    game.players[4].role = Role::Quit;
+    // 输光的Ed还没买入也占着座位，新来的人坐到别的座位上
+    let seat = game.players.len();
+    game.receive_my_event(MyEvent::JoinSeat { name: "Gus", buy_in: 300 }).unwrap();
+    assert_eq!((game.players[4].name.as_str(), game.players[seat].name.as_str()), ("Ed", "Gus"));
+    assert!(game.receive_my_event(MyEvent::Rebuy { seat: 4, value: 100 }).is_err());
+    game.receive_my_event(MyEvent::Rebuy { seat: 4, value: 400 }).unwrap();
+    assert!(matches!(game.players[4].role, Role::Normal));
+    assert_eq!(game.cashier.get_net_buy_in("Ed"), 900 - Ed_stack);
+    play_hand(&mut game, 8).unwrap();
+    assert_eq!(get_total(&game), 10000);
+    // 占位玩家靠标记认出来，名字像占位的真玩家照样能坐出和兑现
+    assert!(!game.players[3].is_placeholder);
+    let seat = game.players.len();
+    game.receive_my_event(MyEvent::JoinSeat { name: "空座9", buy_in: 300 }).unwrap();
+    assert_eq!(game.players[seat].name, "空座9");
+    game.receive_my_event(MyEvent::SitOut { seat, post_blinds: false }).unwrap();
+    game.receive_my_event(MyEvent::CashOut { seat }).unwrap();
+    assert!(game.players[seat].is_placeholder);
+    assert!(game.receive_my_event(MyEvent::SitOut { seat, post_blinds: false }).is_err());
+    // 桌上的筹码都是从收银台买的
+    let net: i32 = game.cashier.ledger.values().sum();
+    assert_eq!(net, 10000 - game.cashier.bank.get_values_of_bank());
+}
+
+#[test]
+fn test_rake() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use module_game::*;
+    use module_config::{GameConfig, RakeRule};
+    use module_tournament::*;
+    use module_bot::{BotProfile, ProfileStrategy, ScriptedStrategy};
+    use module_history::HandHistory;
+    use module_event::{EventLog, GameEvent};
+
+    // 底池5%，封顶15，整手最多20，没翻牌不抽水
+    let rule = RakeRule::new(5.0, Some(15), Some(20), true);
+    assert_eq!(rule.get_rake(100, false, 5), 0);
+    assert_eq!(rule.get_rake(100, true, 5), 5);
+    assert_eq!(rule.get_rake(90, true, 5), 0);
+    assert_eq!(rule.get_rake(1000, true, 5), 15);
+    assert_eq!(RakeRule::new(5.0, None, Some(20), true).get_rake(1000, true, 5), 20);
+    assert!(GameConfig::builder().rake(RakeRule::new(120.0, None, None, true)).build().is_err());
+    assert!(GameConfig::builder().rake(RakeRule::new(5.0, Some(7), None, true)).build().is_err());
+    let schedule = BlindSchedule::new(vec![BlindLevel::new(5, 10, 0, LevelDuration::Hands(10))]);
+    assert!(GameConfig::builder().blind_schedule(schedule).rake(rule.clone()).build().is_err());
+    let config = GameConfig::from_toml("starting_stack = 1000\nsmall_blind = 5\nbig_blind = 10\n[rake]\npercent = 5\ncap = 15\nmax_per_hand = 20\n").unwrap();
+    assert_eq!(config.rake, Some(rule));
+
+    let names = get_names(&["Alice", "Bob", "Cara", "David"]);
+    let mut game = Game::new_with_config(config, &names).unwrap();
+    for (index, name) in names.iter().enumerate() {
+        game.set_strategy(name, Rc::new(RefCell::new(ProfileStrategy::new(BotProfile::loose_aggressive(), index as u64))));
+    }
+    let log = Rc::new(RefCell::new(EventLog::default()));
+    game.add_observer(log.clone());
+    game.assign_blinds(0);
+    for seed in 0..30 {
+        if game.get_player_num_left() < 2 {
+            break;
         }
-        println!("{0}\n", game);
+        module_arena::play_hand(&mut game, seed).unwrap();
+        // 筹码守恒：玩家、钱池和庄家加起来不变
+        let total = game.house.bank.get_values_of_bank() + game.cash_pool.cash_pool.get_values_of_bank()
+            + game.players.iter().map(|player| player.owned_bank.get_values_of_bank()).sum::<i32>();
+        assert_eq!(total, 4000);
+    }
+    let rakes: Vec<i32> = game.histories.iter().map(|history| history.rake).collect();
+    assert!(rakes.iter().any(|rake| *rake > 0));
+    assert!(rakes.iter().all(|rake| *rake >= 0 && *rake <= 15));
+    for history in game.histories.iter() {
+        if history.actions.iter().all(|entry| entry.street == 0) && history.showdown.len() < 2 {
+            assert_eq!(history.rake, 0);
+        }
+    }
+    let taken: i32 = log.borrow().events.iter().map(|event| if let GameEvent::RakeTaken { value } = event { *value } else { 0 }).sum();
+    assert_eq!(taken, rakes.iter().sum::<i32>());
+
+    // 翻前就结束的一手不抽水，底池再大也一样
+    let config = GameConfig::builder().blinds(50, 100).starting_stack(1000).rake(RakeRule::new(5.0, None, None, true)).build().unwrap();
+    let mut folded = Game::new_with_config(config, &get_names(&["Alice", "Bob", "Cara"])).unwrap();
+    for (name, script) in [("Alice", "f"), ("Bob", "f"), ("Cara", "r400")] {
+        folded.set_strategy(name, Rc::new(RefCell::new(ScriptedStrategy::parse(script).unwrap())));
     }
+    folded.assign_blinds(0);
+    module_arena::play_hand(&mut folded, 0).unwrap();
+    assert_eq!((folded.street, folded.histories[0].rake), (0, 0));
+    assert_eq!(folded.house.bank.get_values_of_bank(), 0);
+
+    // 这一场的统计
+    let report = game.end_rake_session();
+    assert_eq!(report.hands as usize, game.histories.len());
+    assert_eq!(report.raked_hands as usize, rakes.iter().filter(|rake| **rake > 0).count());
+    assert_eq!(report.total, game.house.bank.get_values_of_bank());
+    assert_eq!(report.max, *rakes.iter().max().unwrap());
+    assert_eq!(game.end_rake_session().total, 0);
+
+    // 抽水写进手牌记录，导出读回后还能重放
+    let history = game.histories.iter().find(|history| history.rake > 0).unwrap();
+    let text = history.to_pokerstars(None);
+    assert!(text.contains(&format!("Total pot {0} | Rake {1}\n", history.get_total_pot(), history.rake)));
+    let parsed = HandHistory::parse_pokerstars(&text).unwrap();
+    assert_eq!(parsed.rake, history.rake);
+    let result = module_replay::replay(&parsed);
+    assert!(result.is_ok(), "{:?}\n{}", result, text);
 }
 
+#[test]
+fn test_chip_set() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use module_game::*;
+    use module_config::GameConfig;
+    use module_bank::{ChipSet, MyBank};
+    use module_tournament::*;
+    use module_bot::{BotProfile, ProfileStrategy};
+    use module_event::{EventLog, GameEvent};
+    use module_history::HistoryAction;
+
+    // 只有一个100的筹码要下25，到筹码架上换零
+    let chip_set = ChipSet::new(vec![("绿", 100), ("白", 5), ("红", 25), ("黑", 500)]);
+    assert!(chip_set.check().is_ok());
+    assert!(ChipSet::new(vec![("白", 5), ("蓝", 12)]).check().is_err());
+    assert!(chip_set.get_chips(1130, 0).unwrap() == vec![(chip_set.get_stuff_type(3), 2), (chip_set.get_stuff_type(2), 1), (chip_set.get_stuff_type(1), 1), (chip_set.get_stuff_type(0), 1)]);
+    assert!(chip_set.get_chips(130, 2).is_err());
+    let (white, red, green) = (chip_set.get_stuff_type(0), chip_set.get_stuff_type(1), chip_set.get_stuff_type(2));
+    let mut bank = MyBank::new();
+    bank.stuff_in(&vec![(&green, 1)], true).unwrap();
+    let mut house = MyBank::new();
+    house.stuff_in(&vec![(&white, 20), (&red, 4)], true).unwrap();
+    assert!(bank.clone().collect_stuff(25).is_err());
+    bank.make_change(25, &MyBank::new(), &mut house, 5).unwrap();
+    assert!(bank.clone().collect_stuff(25).is_ok());
+    assert_eq!(bank.get_num_of(&red), 4);
+    assert_eq!(bank.get_values_of_bank() + house.get_values_of_bank(), 300);
+    // 换不开的就失败，不小于min_value的才拿来找零
+    assert!(bank.make_change(10, &MyBank::new(), &mut house, 25).is_err());
+    assert!(bank.make_change(1000, &MyBank::new(), &mut house, 5).is_err());
+
+    assert!(GameConfig::from_toml("chip_value = 25\n[chip_set]\ndenominations = [{ name = \"白\", value = 5 }, { name = \"红\", value = 25 }]\n").is_err());
+    let config = GameConfig::from_toml("chip_value = 5\nstarting_stack = 1000\n[chip_set]\ndenominations = [{ name = \"白\", value = 5 }, { name = \"红\", value = 25 }]\n").unwrap();
+    assert!(config.get_starting_assets().unwrap() == vec![(config.chip_set.as_ref().unwrap().get_stuff_type(1), 40)]);
+
+    // 锦标赛每人两个黑色筹码开局，下盲注时换零，盲注涨上去后把白色和红色筹码进位
+    let schedule = BlindSchedule::new(vec![
+        BlindLevel::new(5, 10, 0, LevelDuration::Hands(3)),
+        BlindLevel::new(25, 50, 0, LevelDuration::Hands(3)),
+        BlindLevel::new(100, 200, 0, LevelDuration::Hands(100)),
+    ]);
+    let config = GameConfig::builder().chip_set(chip_set.clone()).starting_stack(1000).blind_schedule(schedule).build().unwrap();
+    let names = get_names(&["Alice", "Bob", "Cara", "David"]);
+    let mut game = Game::new_with_config(config, &names).unwrap();
+    assert_eq!(game.players[0].owned_bank.get_num_of(&chip_set.get_stuff_type(3)), 2);
+    for (index, name) in names.iter().enumerate() {
+        game.set_strategy(name, Rc::new(RefCell::new(ProfileStrategy::new(BotProfile::loose_aggressive(), index as u64))));
+    }
+    let log = Rc::new(RefCell::new(EventLog::default()));
+    game.add_observer(log.clone());
+    game.assign_blinds(0);
+    let total = 4000 + game.chip_bank.get_values_of_bank();
+    let get_total = |game: &Game| game.chip_bank.get_values_of_bank() + game.cash_pool.cash_pool.get_values_of_bank()
+        + game.players.iter().map(|player| player.owned_bank.get_values_of_bank() + player.bet_bank.get_values_of_bank()).sum::<i32>();
+    let mut hands = 0;
+    while hands < 9 && game.get_player_num_left() > 1 {
+        module_arena::play_hand(&mut game, hands).unwrap();
+        hands += 1;
+        assert_eq!(get_total(&game), total);
+        if hands <= 3 {
+            assert_eq!(game.colored_up, 0);
+        }
+    }
+    assert_eq!(game.histories[0].actions[0].action, HistoryAction::SmallBlind(5));
+    assert_eq!(game.histories[0].actions[1].action, HistoryAction::BigBlind(10));
+    assert!(game.histories[0].actions.iter().any(|entry| !entry.action.is_forced()));
+    assert_eq!(hands, 9);
+    assert_eq!(game.colored_up, 2);
+    assert_eq!(game.min_value_unit, 100);
+    for player in game.players.iter() {
+        assert_eq!(player.owned_bank.get_num_of(&white) + player.owned_bank.get_num_of(&red), 0);
+        assert_eq!(player.owned_bank.get_values_of_bank() % 100, 0);
+    }
+    let colored: Vec<GameEvent> = log.borrow().events.iter().filter(|event| matches!(event, GameEvent::ChipsColoredUp { .. })).cloned().collect();
+    assert!(!colored.is_empty());
+    for event in colored {
+        if let GameEvent::ChipsColoredUp { removed, added, .. } = event {
+            assert!((added - removed).abs() * 2 <= 100);
+        }
+    }
+
+    // 从收银台入座的现金桌按最大买入备好筹码架；没挂策略的机器人加注时按最后下的数换零
+    let config = GameConfig::builder().chip_set(chip_set.clone()).blinds(5, 10).buy_in(200, 1000).build().unwrap();
+    let mut game = Game::new_with_config(config, &Vec::new()).unwrap();
+    assert_eq!(game.chip_bank.get_num_of(&white), 1000 * module_config::MAX_SEAT_NUM as i32 / 5);
+    let log = Rc::new(RefCell::new(EventLog::default()));
+    game.add_observer(log.clone());
+    let black = chip_set.get_stuff_type(3);
+    game.cashier.stock(&vec![(&black, 10)]).unwrap();
+    for name in ["Alice", "Bob"] {
+        game.receive_my_event(MyEvent::JoinSeat { name, buy_in: 500 }).unwrap();
+    }
+    assert_eq!(game.players[1].owned_bank.get_num_of(&black), 1);
+    game.assign_blinds(0);
+    game.post_blind(0, 5).unwrap();
+    game.post_blind(1, 10).unwrap();
+    let total = get_total(&game);
+    game.players[0].profile = BotProfile { name: "raiser".to_string(), vpip: 1.0, pfr: 1.0, aggression_factor: 1.0, big_raise_ratio: 1.0, bluff_frequency: 1.0 };
+    game.bot_place_a_bet(0, 1, 10, 0).unwrap();
+    assert!(game.players[0].bet_bank.get_values_of_bank() > 10);
+    assert!(matches!(log.borrow().events.last(), Some(GameEvent::PlayerActed { action: HistoryAction::Raise(_), .. })));
+    assert_eq!(get_total(&game), total);
+}